@@ -4,7 +4,7 @@ use crate::error::{ContractError, ContractResult};
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, UpdateConfig};
 use crate::state::{Config, CONFIG};
 use crate::utils::{*, validate_instantiate_msg, validate_update_config};
-use crate::external_types::{AllApyResponse, CalculatedFeeTiers};
+use crate::external_types::{AllApyResponse, CalculatedFeeTiers, SpotPriceResponse};
 use cosmwasm_std::{attr, entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, Addr, Decimal};
 use cw2::set_contract_version;
 
@@ -13,6 +13,9 @@ use serde_json::to_vec;
 const CONTRACT_NAME: &str = concat!("crates.io:neutron-contracts__", env!("CARGO_PKG_NAME"));
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_ASSET_CONFIG_LIMIT: u32 = 10;
+const MAX_ASSET_CONFIG_LIMIT: u32 = 30;
+
 ///////////////////
 /// INSTANTIATE ///
 ///////////////////
@@ -116,12 +119,19 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> ContractResult<Binary> {
             let mut calculated_tiers = Vec::<CalculatedFeeTiers>::new();
             
             for asset in config.assets {
-                let apy = match query_apy_contract(&deps, &config.apy_contract, &asset.core_contract, asset.query_period_hours) {
-                    Ok(apy) => apy,
-                    Err(_) => Decimal::zero(),
+                let apy = match asset.apy_source {
+                    crate::msg::ApySource::ApyContract => {
+                        match query_apy_contract(&deps, &config.apy_contract, &asset.core_contract, asset.query_period_hours) {
+                            Ok(apy) => apy,
+                            Err(_) => Decimal::zero(),
+                        }
+                    }
+                    crate::msg::ApySource::RedemptionRateGrowth => {
+                        derive_apy_for_asset(&deps, &asset, _env.block.time.seconds())?
+                    }
                 };
 
-                if apy.is_zero() {
+                if asset.fixed_fee.is_none() && apy.is_zero() {
                     // For zero APY, add entry with zeros
                     calculated_tiers.push(CalculatedFeeTiers {
                         denom: asset.denom,
@@ -131,9 +141,15 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> ContractResult<Binary> {
                         fee_tiers: vec![],
                     });
                 } else {
-                    let base_fee = calculate_fee_tier(apy, asset.unbonding_period, asset.fee_dempening_amount)?;
-                    let fee_tiers = create_fee_tiers(base_fee, &asset.fee_spacings, &asset.percentages)?;
-                    let oracle_skew = (base_fee + 1) as i32;
+                    // fixed_fee bypasses the APY-derived computation entirely;
+                    // otherwise clamp the computed base fee into [min_fee, max_fee].
+                    let base_fee = match asset.fixed_fee {
+                        Some(fixed_fee) => fixed_fee,
+                        None => calculate_fee_tier(apy, asset.unbonding_period, asset.fee_dempening_amount)?
+                            .clamp(asset.min_fee, asset.max_fee),
+                    };
+                    let fee_tiers = create_fee_tiers_for_asset(base_fee, &asset.fee_spacings, &asset.percentages, &asset.distribution_mode)?;
+                    let oracle_skew = compute_oracle_skew(base_fee, asset.normalization_factor)?;
 
                     // Convert to simple (fee, percentage) pairs
                     let fee_tier_pairs: Vec<(u64, u64)> = fee_tiers
@@ -154,6 +170,123 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> ContractResult<Binary> {
             let serialized_response = to_vec(&calculated_tiers).map_err(|_| ContractError::SerializationError)?;
             Ok(Binary::from(serialized_response))
         }
+        QueryMsg::GetAssetConfigs { start_after, limit } => {
+            let config = CONFIG.load(deps.storage)?;
+            let limit = limit.unwrap_or(DEFAULT_ASSET_CONFIG_LIMIT).min(MAX_ASSET_CONFIG_LIMIT) as usize;
+            let start_index = match &start_after {
+                Some(denom) => config
+                    .assets
+                    .iter()
+                    .position(|asset| &asset.denom == denom)
+                    .map(|i| i + 1)
+                    .unwrap_or(config.assets.len()),
+                None => 0,
+            };
+            let page: Vec<_> = config.assets.into_iter().skip(start_index).take(limit).collect();
+
+            let serialized_response = to_vec(&page).map_err(|_| ContractError::SerializationError)?;
+            Ok(Binary::from(serialized_response))
+        }
+        QueryMsg::SimulateFeeTiers { denom } => {
+            let config = CONFIG.load(deps.storage)?;
+            let asset = config
+                .assets
+                .iter()
+                .find(|asset| asset.denom == denom)
+                .ok_or_else(|| ContractError::InvalidFeeTier {
+                    reason: format!("asset {} not configured", denom),
+                })?;
+
+            let apy = match asset.apy_source {
+                crate::msg::ApySource::ApyContract => {
+                    query_apy_contract(&deps, &config.apy_contract, &asset.core_contract, asset.query_period_hours)?
+                }
+                crate::msg::ApySource::RedemptionRateGrowth => {
+                    derive_apy_for_asset(&deps, asset, _env.block.time.seconds())?
+                }
+            };
+
+            let simulated = if asset.fixed_fee.is_none() && apy.is_zero() {
+                CalculatedFeeTiers {
+                    denom: asset.denom.clone(),
+                    apy,
+                    base_fee: 0,
+                    oracle_skew: 0,
+                    fee_tiers: vec![],
+                }
+            } else {
+                let base_fee = match asset.fixed_fee {
+                    Some(fixed_fee) => fixed_fee,
+                    None => calculate_fee_tier(apy, asset.unbonding_period, asset.fee_dempening_amount)?
+                        .clamp(asset.min_fee, asset.max_fee),
+                };
+                let fee_tiers = create_fee_tiers_for_asset(base_fee, &asset.fee_spacings, &asset.percentages, &asset.distribution_mode)?;
+                let oracle_skew = compute_oracle_skew(base_fee, asset.normalization_factor)?;
+
+                CalculatedFeeTiers {
+                    denom: asset.denom.clone(),
+                    apy,
+                    base_fee,
+                    oracle_skew,
+                    fee_tiers: fee_tiers.iter().map(|tier| (tier.fee, tier.percentage)).collect(),
+                }
+            };
+
+            let serialized_response = to_vec(&simulated).map_err(|_| ContractError::SerializationError)?;
+            Ok(Binary::from(serialized_response))
+        }
+        QueryMsg::SpotPrice {
+            base_asset_denom,
+            quote_asset_denom,
+        } => {
+            if base_asset_denom == quote_asset_denom {
+                return Err(ContractError::InvalidFeeTier {
+                    reason: "base_asset_denom and quote_asset_denom must differ".to_string(),
+                });
+            }
+
+            let config = CONFIG.load(deps.storage)?;
+            let asset = config
+                .assets
+                .iter()
+                .find(|asset| asset.denom == base_asset_denom)
+                .ok_or_else(|| ContractError::InvalidFeeTier {
+                    reason: format!("asset {} not configured", base_asset_denom),
+                })?;
+
+            let apy = match asset.apy_source {
+                crate::msg::ApySource::ApyContract => {
+                    query_apy_contract(&deps, &config.apy_contract, &asset.core_contract, asset.query_period_hours)?
+                }
+                crate::msg::ApySource::RedemptionRateGrowth => {
+                    derive_apy_for_asset(&deps, asset, _env.block.time.seconds())?
+                }
+            };
+
+            let tiers = if asset.fixed_fee.is_none() && apy.is_zero() {
+                vec![]
+            } else {
+                let base_fee = match asset.fixed_fee {
+                    Some(fixed_fee) => fixed_fee,
+                    None => calculate_fee_tier(apy, asset.unbonding_period, asset.fee_dempening_amount)?
+                        .clamp(asset.min_fee, asset.max_fee),
+                };
+                let fee_tiers = create_fee_tiers_for_asset(base_fee, &asset.fee_spacings, &asset.percentages, &asset.distribution_mode)?;
+                let oracle_skew = compute_oracle_skew(base_fee, asset.normalization_factor)?;
+
+                resolve_tier_spot_prices(&fee_tiers, oracle_skew, asset.normalization_factor)?
+            };
+
+            let response = SpotPriceResponse {
+                base_asset_denom,
+                quote_asset_denom,
+                oracle_price: asset.normalization_factor,
+                tiers,
+            };
+
+            let serialized_response = to_vec(&response).map_err(|_| ContractError::SerializationError)?;
+            Ok(Binary::from(serialized_response))
+        }
     }
 }
 
@@ -219,7 +352,7 @@ fn execute_update_config(
 }
 
 fn execute_run_vault_update(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
@@ -237,48 +370,69 @@ fn execute_run_vault_update(
 
     // Process each asset vault
     for asset in &config.assets {
-        // Query APY for this asset
-        let apy = query_apy_contract(
-            &deps.as_ref(),
-            &config.apy_contract,
-            &asset.core_contract,
-            asset.query_period_hours,
-        )?;
+        // Query APY for this asset, either from the external apy_contract or
+        // derived internally from the asset's own redemption-rate growth.
+        let apy = match asset.apy_source {
+            crate::msg::ApySource::ApyContract => query_apy_contract(
+                &deps.as_ref(),
+                &config.apy_contract,
+                &asset.core_contract,
+                asset.query_period_hours,
+            )?,
+            crate::msg::ApySource::RedemptionRateGrowth => {
+                accrue_redemption_rate_apy(&mut deps, asset, _env.block.time.seconds())?
+            }
+        };
 
-        // Check if APY is zero
-        let is_apy_zero: bool = apy.is_zero();
+        // Check if APY is zero. A configured fixed_fee bypasses the
+        // APY-derived computation entirely, so it always takes the update path.
+        let is_apy_zero: bool = asset.fixed_fee.is_none() && apy.is_zero();
 
         if is_apy_zero {
             // If APY is zero, only perform withdrawal (no update or deposit)
             let withdrawal_msg = create_dex_withdrawal_message(&asset.vault_address)?;
             messages.push(withdrawal_msg);
-            
+            messages.extend(create_fee_distribution_messages(asset)?);
+
             // zero APY case attrs
             attributes.push(attr(format!("vault_{}_apy", asset.denom), "0"));
             attributes.push(attr(format!("vault_{}_action", asset.denom), "withdrawal_only"));
             attributes.push(attr(format!("vault_{}_reason", asset.denom), "zero_apy"));
         } else {
             // if not zero apy, calculate base fee tier, create fee tiers, and update vault.
-            // Calculate base fee tier using the APY and unbonding period
-            let base_fee = calculate_fee_tier(apy, asset.unbonding_period, asset.fee_dempening_amount)?;
+            // fixed_fee bypasses calculate_fee_tier entirely; otherwise clamp
+            // the computed base fee into [min_fee, max_fee].
+            let base_fee = match asset.fixed_fee {
+                Some(fixed_fee) => fixed_fee,
+                None => calculate_fee_tier(apy, asset.unbonding_period, asset.fee_dempening_amount)?
+                    .clamp(asset.min_fee, asset.max_fee),
+            };
 
             // Create fee tiers by adding configured values to the calculated base fee
-            let fee_tiers = create_fee_tiers(base_fee, &asset.fee_spacings, &asset.percentages)?;
+            let fee_tiers = create_fee_tiers_for_asset(base_fee, &asset.fee_spacings, &asset.percentages, &asset.distribution_mode)?;
 
-            // Oracle skew is base fee + 1. can be counteracted with fee_spacing of 1 on the first tick index.
-            let oracle_skew = (base_fee + 1) as i32;
+            // Oracle skew is base fee + 1, shifted by the asset's normalization
+            // factor to recenter on its current redemption rate. The base-fee
+            // component can be counteracted with fee_spacing of 1 on the first tick index.
+            let oracle_skew = compute_oracle_skew(base_fee, asset.normalization_factor)?;
 
             // Full sequence for all vaults: dex_withdrawal, update_config, dex_deposit
             let withdrawal_msg = create_dex_withdrawal_message(&asset.vault_address)?;
             let update_msg = create_vault_update_message(
+                &mut deps,
+                &_env,
+                &asset.denom,
                 &asset.vault_address,
+                base_fee,
                 &fee_tiers,
                 oracle_skew,
+                asset.rate_limiter.as_ref(),
                 &info.sender.to_string(),
             )?;
             let deposit_msg = create_dex_deposit_message(&asset.vault_address)?;
-            
+
             messages.push(withdrawal_msg);
+            messages.extend(create_fee_distribution_messages(asset)?);
             messages.push(update_msg);
             messages.push(deposit_msg);
 
@@ -32,6 +32,26 @@ pub struct CalculatedFeeTiers {
     pub fee_tiers: Vec<(u64, u64)>, // (fee, percentage) pairs
 }
 
+/// One fee tier's effective quoted prices, resolved from its tick offset
+/// around `oracle_skew` against the asset's current oracle price.
+#[cw_serde]
+pub struct TierSpotPrice {
+    pub fee: u64,
+    pub percentage: u64,
+    /// price to buy `base_asset_denom` with `quote_asset_denom` at this tier
+    pub bid_price: Decimal,
+    /// price to sell `base_asset_denom` for `quote_asset_denom` at this tier
+    pub ask_price: Decimal,
+}
+
+#[cw_serde]
+pub struct SpotPriceResponse {
+    pub base_asset_denom: String,
+    pub quote_asset_denom: String,
+    pub oracle_price: Decimal,
+    pub tiers: Vec<TierSpotPrice>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, QueryResponses)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -40,4 +60,13 @@ pub enum QueryMsg {
         instance: String,
         time_span_hours: u64,
     },
+}
+
+/// Queried directly against a dasset's `core_contract`, bypassing the APY
+/// aggregator, when `ApySource::RedemptionRateGrowth` is configured.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum CoreQueryMsg {
+    #[returns(Decimal)]
+    ExchangeRate {},
 }
\ No newline at end of file
@@ -2,8 +2,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use cosmwasm_schema::QueryResponses;
+use cosmwasm_std::Decimal;
 use crate::state::Config;
-use crate::external_types::{AllApyResponse, CalculatedFeeTiers};
+use crate::external_types::{AllApyResponse, CalculatedFeeTiers, SpotPriceResponse};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, QueryResponses)]
 #[serde(rename_all = "snake_case")]
@@ -14,6 +15,24 @@ pub enum QueryMsg {
     GetAllApy {},
     #[returns(Vec<CalculatedFeeTiers>)]
     GetFeeTiers {},
+    /// paginated enumeration of the configured assets, ordered as stored
+    #[returns(Vec<AssetData>)]
+    GetAssetConfigs {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// runs the fee-tier pipeline for a single asset without submitting a
+    /// transaction, so keepers can preview what `RunVaultUpdate` would post
+    #[returns(CalculatedFeeTiers)]
+    SimulateFeeTiers { denom: String },
+    /// the effective bid/ask price each fee tier will quote, mirroring
+    /// transmuter's `spot_price` entrypoint: explicit base/quote ordering,
+    /// rejecting a same-denom request
+    #[returns(SpotPriceResponse)]
+    SpotPrice {
+        base_asset_denom: String,
+        quote_asset_denom: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -37,6 +56,50 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum MigrateMsg {}
 
+/// How `percentages` is turned into per-tier liquidity weights by
+/// `create_fee_tiers`/`create_stableswap_fee_tiers`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionMode {
+    /// spread liquidity across tiers using the caller-supplied `percentages` as-is
+    Uniform,
+    /// concentrate liquidity near the base index, weighting each fee tier by
+    /// `1 / (1 + (tick_offset / amplification)^2)` instead of `percentages`
+    Stableswap { amplification: u64 },
+}
+
+/// Bounds how fast `base_fee`/`oracle_skew` can move per `RunVaultUpdate`,
+/// modeled as a moving-average change limiter: a sliding window of total
+/// duration `window_size` split into `division_count` equal divisions tracks
+/// a time-weighted average of the value, and a new value is clamped to
+/// `moving_average +/- boundary_offset` rather than applied outright.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RateLimiterConfig {
+    /// total duration of the sliding window, in seconds
+    pub window_size: u64,
+    /// number of equal-length divisions the window is split into
+    pub division_count: u64,
+    /// max allowed excess of `base_fee` over the window's moving average
+    pub base_fee_boundary_offset: u64,
+    /// max allowed excess of `oracle_skew` over the window's moving average
+    pub oracle_skew_boundary_offset: u64,
+    /// if true, also clamp a value that falls `boundary_offset` below the
+    /// moving average; if false, only the upper bound is enforced
+    pub symmetric: bool,
+}
+
+/// Where `RunVaultUpdate` sources an asset's APY from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApySource {
+    /// trust `apy_contract`'s `GetApy` query, as today
+    ApyContract,
+    /// derive APY internally from the compounding growth of `core_contract`'s
+    /// redemption rate, sampled every `query_period_hours`
+    RedemptionRateGrowth,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct AssetData {
@@ -48,6 +111,26 @@ pub struct AssetData {
     pub vault_address: String, // vault address for the dasset
     pub query_period_hours: u64, // query period in hours for the dasset apy contract
     pub fee_dempening_amount: u64, // amount to dempen the fee calculation by in pps
+    pub distribution_mode: DistributionMode, // how percentages are turned into tier weights
+    // (address, basis_points) pairs summing to 10000; validated only. `RunVaultUpdate` sends the vault an
+    // argument-less `distribute_fees`, which pays the vault's own `fee_splitter` recipients, not these
+    pub fee_recipients: Vec<(String, u16)>,
+    /// optional per-asset change-rate limiter for `base_fee`/`oracle_skew`
+    pub rate_limiter: Option<RateLimiterConfig>,
+    /// `core_contract`'s current redemption rate (dasset per underlying), used
+    /// to scale the quoted price center before fee tiers are placed so LSTs
+    /// that drift off a 1:1 peg as rewards accrue don't skew the tiers
+    pub normalization_factor: Decimal,
+    /// how this asset's APY is sourced for `calculate_fee_tier`
+    pub apy_source: ApySource,
+    /// lower bound `calculate_fee_tier`'s result is clamped to
+    pub min_fee: u64,
+    /// upper bound `calculate_fee_tier`'s result is clamped to
+    pub max_fee: u64,
+    /// when set, bypasses the APY-derived computation entirely and uses this
+    /// constant base fee instead, for operators who want a deterministic
+    /// spread regardless of yield volatility. Must lie within `[min_fee, max_fee]`.
+    pub fixed_fee: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -1,6 +1,6 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal};
 use crate::msg::AssetData;
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -12,3 +12,37 @@ pub struct Config {
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+
+/// One division of a `RateLimiterConfig`'s sliding window: accumulates
+/// `latest_value * (updated_at - started_at)` so the window's time-weighted
+/// moving average can be recovered without replaying every individual update.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct LimiterDivision {
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub latest_value: i64,
+    pub integral: i128,
+}
+
+/// Per-asset change-limiter state, tracked independently for `base_fee` and
+/// `oracle_skew` since the two metrics move on unrelated scales.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct RateLimiterState {
+    pub base_fee_divisions: Vec<LimiterDivision>,
+    pub oracle_skew_divisions: Vec<LimiterDivision>,
+}
+
+/// keyed by `AssetData::denom`
+pub const RATE_LIMITER_STATE: Map<String, RateLimiterState> = Map::new("rate_limiter_state");
+
+/// The most recent `core_contract` redemption rate observed for an asset
+/// configured with `ApySource::RedemptionRateGrowth`, used as the baseline
+/// the next sample's growth is measured against.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RedemptionRateSample {
+    pub rate: Decimal,
+    pub timestamp: u64,
+}
+
+/// keyed by `AssetData::denom`
+pub const REDEMPTION_RATE_SAMPLES: Map<String, RedemptionRateSample> = Map::new("redemption_rate_samples");
@@ -1,11 +1,21 @@
 use crate::error::ContractResult;
-use crate::external_types::{QueryMsg as ApyQueryMsg, DropInstanceApy};
-use cosmwasm_std::{Deps, WasmQuery, QueryRequest, to_json_binary, Addr, Decimal, CosmosMsg, WasmMsg};
+use crate::external_types::{QueryMsg as ApyQueryMsg, CoreQueryMsg, DropInstanceApy, TierSpotPrice};
+use crate::msg::{ApySource, AssetData, RateLimiterConfig};
+use crate::state::{LimiterDivision, RedemptionRateSample, RATE_LIMITER_STATE, REDEMPTION_RATE_SAMPLES};
+use cosmwasm_std::{Deps, DepsMut, Env, WasmQuery, QueryRequest, to_json_binary, Addr, Decimal, CosmosMsg, WasmMsg};
 use serde_json::{json, Value};
+use std::str::FromStr;
 
 // Constants for fee tier calculation
 const DAYS_IN_YEAR: u64 = 365;
-const LN_1_0001: f64 = 0.00009999500033330835; // ln(1.0001)
+/// `1 / (2 * ln(1.0001))`. `ln(1.0001)` is a fixed constant, so the whole
+/// `(r*t)/(2*ln(1.0001))` formula collapses to a single multiplication by
+/// this value, keeping the calculation in deterministic fixed-point math
+/// (CosmWasm rejects floating-point instructions at upload).
+const FEE_TIER_CONSTANT: &str = "5000.249995833541653473";
+/// base of the tick index price formula `price = TICK_BASE^tick`, shared with
+/// the vault contracts this contract drives via `oracle_price_skew`/fee tiers
+const TICK_BASE: &str = "1.0001";
 
 #[derive(Clone, Debug)]
 pub struct FeeTier {
@@ -70,27 +80,102 @@ impl FeeTier {
 // # this will be the oracle price skew.
 // # the total spread will remain S, but the target to buy ASSET and dASSET respectively will stay at  RRe^-rt and RR repectively
 pub fn calculate_fee_tier(apy: Decimal, unbonding_days: u64, fee_dempening_amount: u64) -> ContractResult<u64> {
-    // Convert APY from Decimal to f64
-    let r: f64 = apy.to_string().parse()
+    // t = unbonding period in years
+    let t = Decimal::from_ratio(unbonding_days, DAYS_IN_YEAR);
+
+    // Calculate fee tier: (r * t) / (2 * ln(1.0001)) == r * t * FEE_TIER_CONSTANT
+    let fee_tier_constant = Decimal::from_str(FEE_TIER_CONSTANT)
         .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
-    
-    // Convert unbonding period to years
-    let t = unbonding_days as f64 / DAYS_IN_YEAR as f64;
-    
-    // Calculate fee tier: (r * t) / (2 * ln(1.0001))
-    let fee_tier = (r * t) / (2.0 * LN_1_0001);
-    
-    // dempen by the dampening amount:
-    let mut fee_tier_u64 = fee_tier.abs() as u64;
+    let fee_tier = apy
+        .checked_mul(t)
+        .and_then(|v| v.checked_mul(fee_tier_constant))
+        .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
+
+    // Floor to an integer, saturating on absurd APYs so the cast can never overflow.
+    let mut fee_tier_u64 = u64::try_from(fee_tier.to_uint_floor().u128()).unwrap_or(u64::MAX);
 
+    // dempen by the dampening amount:
     if fee_tier_u64 > fee_dempening_amount {
         fee_tier_u64 -= fee_dempening_amount;
-    } 
+    }
 
-    // Return absolute value as u64 (always positive)
     Ok(fee_tier_u64)
 }
 
+/// Derives the `oracle_skew` that centers fee tiers on `base_fee`'s redemption
+/// rate, per the `index B` term of `calculate_fee_tier`'s derivation
+/// (`ln(RR) / ln(1.0001)`): `base_fee + 1` alone assumes `RR == 1`, i.e. the
+/// dasset trades 1:1 with its underlying, which drifts false as an LST's
+/// redemption rate accrues above 1.0. `ln(RR)` is approximated as `RR - 1`
+/// (accurate for `RR` close to 1, i.e. the gradual drift a redemption rate
+/// accrues between `RunVaultUpdate` calls), reusing `FEE_TIER_CONSTANT` the
+/// same way `calculate_fee_tier` does to avoid floating-point `ln`.
+pub fn compute_oracle_skew(base_fee: u64, normalization_factor: Decimal) -> ContractResult<i32> {
+    let fee_tier_constant = Decimal::from_str(FEE_TIER_CONSTANT)
+        .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
+    let two = Decimal::from_ratio(2u64, 1u64);
+
+    let (drift, negative) = if normalization_factor >= Decimal::one() {
+        (normalization_factor - Decimal::one(), false)
+    } else {
+        (Decimal::one() - normalization_factor, true)
+    };
+    let normalization_ticks = drift
+        .checked_mul(two)
+        .and_then(|v| v.checked_mul(fee_tier_constant))
+        .map_err(|_| crate::error::ContractError::DecimalConversionError)?
+        .to_uint_floor()
+        .u128();
+    let normalization_ticks = i32::try_from(normalization_ticks).unwrap_or(i32::MAX);
+    let normalization_ticks = if negative {
+        -normalization_ticks
+    } else {
+        normalization_ticks
+    };
+
+    Ok((base_fee as i32) + 1 + normalization_ticks)
+}
+
+/// Resolves `TICK_BASE^tick`, `tick` signed, via `decimal_pow` on its
+/// magnitude and inverting for negative ticks.
+fn tick_price(oracle_price: Decimal, tick: i64) -> ContractResult<Decimal> {
+    let tick_base = Decimal::from_str(TICK_BASE)
+        .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
+    let factor = decimal_pow(tick_base, tick.unsigned_abs())?;
+    if tick >= 0 {
+        oracle_price
+            .checked_mul(factor)
+            .map_err(|_| crate::error::ContractError::DecimalConversionError)
+    } else {
+        oracle_price
+            .checked_div(factor)
+            .map_err(|_| crate::error::ContractError::DecimalConversionError)
+    }
+}
+
+/// Resolves each fee tier's effective bid/ask price: the tier's tick offset
+/// is applied symmetrically around `oracle_skew` against `oracle_price`, the
+/// same center `create_vault_update_message` posts as `oracle_price_skew`.
+pub fn resolve_tier_spot_prices(
+    fee_tiers: &[FeeTier],
+    oracle_skew: i32,
+    oracle_price: Decimal,
+) -> ContractResult<Vec<TierSpotPrice>> {
+    fee_tiers
+        .iter()
+        .map(|tier| {
+            let ask_tick = oracle_skew as i64 + tier.fee as i64;
+            let bid_tick = oracle_skew as i64 - tier.fee as i64;
+            Ok(TierSpotPrice {
+                fee: tier.fee,
+                percentage: tier.percentage,
+                bid_price: tick_price(oracle_price, bid_tick)?,
+                ask_price: tick_price(oracle_price, ask_tick)?,
+            })
+        })
+        .collect()
+}
+
 /// Create fee tiers by adding spacings to the calculated base fee
 pub fn create_fee_tiers(
     calculated_base_fee: u64,
@@ -104,54 +189,267 @@ pub fn create_fee_tiers(
             reason: "Fee tier percentages must sum to 100".to_string(),
         });
     }
-    
+
     // Validate that we have the same number of fee tier values and percentages
     if percentages.len() != fee_tier_values.len() {
         return Err(crate::error::ContractError::InvalidFeeTier {
             reason: "Number of percentages must match number of fee tier values".to_string(),
         });
     }
-    
+
     let mut fee_tiers = Vec::new();
-    
+
     // Create fee tiers by adding each fee tier value to the calculated base fee
     for (i, &fee_tier_value) in fee_tier_values.iter().enumerate() {
         let final_fee = calculated_base_fee + fee_tier_value;
         fee_tiers.push(FeeTier::new(final_fee, percentages[i]));
     }
-    
+
     Ok(fee_tiers)
 }
 
-/// Create the update_config message for a vault contract
+/// Create fee tiers the way `create_fee_tiers` does, but weight each tier by
+/// `asset.distribution_mode` instead of always using the caller-supplied
+/// `percentages` verbatim. `Uniform` delegates straight to `create_fee_tiers`;
+/// `Stableswap { amplification }` concentrates weight near the base index.
+pub fn create_fee_tiers_for_asset(
+    calculated_base_fee: u64,
+    fee_tier_values: &[u64],
+    percentages: &[u64],
+    distribution_mode: &crate::msg::DistributionMode,
+) -> ContractResult<Vec<FeeTier>> {
+    match distribution_mode {
+        crate::msg::DistributionMode::Uniform => {
+            create_fee_tiers(calculated_base_fee, fee_tier_values, percentages)
+        }
+        crate::msg::DistributionMode::Stableswap { amplification } => {
+            let weights = stableswap_weights(fee_tier_values, *amplification)?;
+            create_fee_tiers(calculated_base_fee, fee_tier_values, &weights)
+        }
+    }
+}
+
+/// Normalized stableswap-style weights for each fee-tier offset: tiers close
+/// to the base index (`tick_offset` near 0) get more weight than wider ones,
+/// `w_k ∝ 1 / (1 + (tick_offset_k / A)^2)`, renormalized so the resulting
+/// percentages sum to exactly 100. The fall-off is quadratic, not
+/// exponential; a smaller `amplification` makes it steeper, while a larger
+/// one flattens the weights toward uniform.
+fn stableswap_weights(fee_tier_values: &[u64], amplification: u64) -> ContractResult<Vec<u64>> {
+    if amplification == 0 {
+        return Err(crate::error::ContractError::InvalidFeeTier {
+            reason: "Stableswap amplification must be non-zero".to_string(),
+        });
+    }
+
+    let amp = Decimal::from_ratio(amplification, 1u64);
+    let raw_weights: Vec<Decimal> = fee_tier_values
+        .iter()
+        .map(|&offset| {
+            let ratio = Decimal::from_ratio(offset, 1u64).checked_div(amp)?;
+            let squared = ratio.checked_mul(ratio)?;
+            Decimal::one().checked_div(Decimal::one() + squared)
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
+
+    let total_weight: Decimal = raw_weights.iter().fold(Decimal::zero(), |acc, w| acc + *w);
+
+    let mut percentages: Vec<u64> = raw_weights
+        .iter()
+        .map(|w| {
+            w.checked_mul(Decimal::from_ratio(100u64, 1u64))
+                .and_then(|scaled| scaled.checked_div(total_weight))
+                .map(|scaled| scaled.to_uint_floor().u128() as u64)
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
+
+    // Flooring can leave the total a few points under 100; hand the remainder
+    // to the tier closest to the peg, which already carries the most weight.
+    let total: u64 = percentages.iter().sum();
+    if let Some(heaviest) = percentages.iter_mut().max_by_key(|p| **p) {
+        *heaviest += 100 - total;
+    }
+
+    Ok(percentages)
+}
+
+/// Selects which of a `RateLimiterConfig`'s two independently tracked
+/// metrics an `apply_rate_limiter` call clamps.
+pub enum LimiterMetric {
+    BaseFee,
+    OracleSkew,
+}
+
+/// Clamps `raw_value` into the sliding-window moving-average bound described
+/// by a `RateLimiterConfig`, mutating `divisions` in place: stale divisions
+/// (entirely outside `window_size`) are dropped, the moving average is
+/// recovered from the remaining divisions' accumulated integrals, and the
+/// current division is folded forward or rolled over into a new one.
+fn clamp_to_moving_average(
+    divisions: &mut Vec<LimiterDivision>,
+    now: u64,
+    raw_value: i64,
+    boundary_offset: u64,
+    symmetric: bool,
+    window_size: u64,
+    division_count: u64,
+) -> i64 {
+    let window_start = now.saturating_sub(window_size);
+    divisions.retain(|division| division.started_at >= window_start);
+
+    // Fold the time the current division's value has been held up to `now`
+    // into its integral before reading the average, so the average reflects
+    // time elapsed up to this call rather than up to the previous one.
+    if let Some(current) = divisions.last_mut() {
+        current.integral +=
+            current.latest_value as i128 * now.saturating_sub(current.updated_at) as i128;
+        current.updated_at = now;
+    }
+
+    let clamped_value = match divisions.first() {
+        None => raw_value,
+        Some(earliest) => {
+            let elapsed = now.saturating_sub(earliest.started_at);
+            if elapsed == 0 {
+                raw_value
+            } else {
+                let total_integral: i128 = divisions.iter().map(|d| d.integral).sum();
+                let moving_average = (total_integral / elapsed as i128) as i64;
+                let offset = boundary_offset as i64;
+                let upper = moving_average.saturating_add(offset);
+                let lower = if symmetric {
+                    moving_average.saturating_sub(offset)
+                } else {
+                    i64::MIN
+                };
+                raw_value.clamp(lower, upper)
+            }
+        }
+    };
+
+    let division_span = (window_size / division_count.max(1)).max(1);
+    match divisions.last_mut() {
+        Some(current) if now.saturating_sub(current.started_at) <= division_span => {
+            current.latest_value = clamped_value;
+        }
+        _ => divisions.push(LimiterDivision {
+            started_at: now,
+            updated_at: now,
+            latest_value: clamped_value,
+            integral: 0,
+        }),
+    }
+
+    clamped_value
+}
+
+/// Loads, applies, and persists `denom`'s rate-limiter state for one metric,
+/// returning the value to actually use (identical to `raw_value` unless the
+/// limiter clamped it).
+pub fn apply_rate_limiter(
+    deps: &mut DepsMut,
+    now: u64,
+    denom: &str,
+    metric: LimiterMetric,
+    raw_value: i64,
+    cfg: &RateLimiterConfig,
+) -> ContractResult<i64> {
+    let mut state = RATE_LIMITER_STATE
+        .may_load(deps.storage, denom.to_string())?
+        .unwrap_or_default();
+
+    let clamped = match metric {
+        LimiterMetric::BaseFee => clamp_to_moving_average(
+            &mut state.base_fee_divisions,
+            now,
+            raw_value,
+            cfg.base_fee_boundary_offset,
+            cfg.symmetric,
+            cfg.window_size,
+            cfg.division_count,
+        ),
+        LimiterMetric::OracleSkew => clamp_to_moving_average(
+            &mut state.oracle_skew_divisions,
+            now,
+            raw_value,
+            cfg.oracle_skew_boundary_offset,
+            cfg.symmetric,
+            cfg.window_size,
+            cfg.division_count,
+        ),
+    };
+
+    RATE_LIMITER_STATE.save(deps.storage, denom.to_string(), &state)?;
+    Ok(clamped)
+}
+
+/// Create the update_config message for a vault contract. When `limiter` is
+/// configured, `base_fee`/`oracle_skew` are first clamped to the denom's
+/// rate-limiter moving average; the fee tiers are shifted by the resulting
+/// delta so their spacing above the base fee is preserved.
 pub fn create_vault_update_message(
+    deps: &mut DepsMut,
+    env: &Env,
+    denom: &str,
     vault_address: &str,
+    base_fee: u64,
     fee_tiers: &[FeeTier],
     oracle_skew: i32,
+    limiter: Option<&RateLimiterConfig>,
     _sender: &str,
 ) -> ContractResult<CosmosMsg> {
-    // Convert fee tiers to the format expected by the vault contract
+    let (clamped_base_fee, clamped_oracle_skew) = match limiter {
+        Some(cfg) => {
+            let now = env.block.time.seconds();
+            let clamped_base_fee = apply_rate_limiter(
+                deps,
+                now,
+                denom,
+                LimiterMetric::BaseFee,
+                base_fee as i64,
+                cfg,
+            )?
+            .max(0) as u64;
+            let clamped_oracle_skew = apply_rate_limiter(
+                deps,
+                now,
+                denom,
+                LimiterMetric::OracleSkew,
+                oracle_skew as i64,
+                cfg,
+            )? as i32;
+            (clamped_base_fee, clamped_oracle_skew)
+        }
+        None => (base_fee, oracle_skew),
+    };
+
+    // Convert fee tiers to the format expected by the vault contract, shifted
+    // by however much the limiter clamped the base fee.
+    let fee_delta = clamped_base_fee as i64 - base_fee as i64;
     let fee_tier_list: Vec<Value> = fee_tiers
         .iter()
         .map(|tier| {
+            let fee = (tier.fee as i64 + fee_delta).max(0) as u64;
             json!({
-                "fee": tier.fee,
+                "fee": fee,
                 "percentage": tier.percentage
             })
         })
         .collect();
-    
+
     let update_config_msg = json!({
         "update_config": {
             "update": {
                 "fee_tier_config": {
                     "fee_tiers": fee_tier_list
                 },
-                "oracle_price_skew": oracle_skew
+                "oracle_price_skew": clamped_oracle_skew
             }
         }
     });
-    
+
     Ok(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: vault_address.to_string(),
         msg: to_json_binary(&update_config_msg)?,
@@ -181,6 +479,31 @@ pub fn create_dex_deposit_message(vault_address: &str) -> ContractResult<CosmosM
     }))
 }
 
+/// `BankMsg::Send` always debits whichever contract returns it in its own
+/// `Response`, so a bank transfer built here would try to move
+/// dasset-updator's own (near-zero) balance, never the vault's. The balance
+/// to sweep lives on `asset.vault_address`, and only that vault can move its
+/// own funds, so this dispatches a `WasmMsg::Execute` into its
+/// `ExecuteMsg::DistributeFees` entry point instead, the same way
+/// `create_dex_withdrawal_message`/`create_dex_deposit_message` let the
+/// vault act on its own balance rather than trying to act on it from here.
+/// The vault pays its accrued fees out to its own `Config::fee_splitter`
+/// recipients; `asset.fee_recipients` is validated configuration describing
+/// the intended split but isn't threaded through this call, since
+/// `DistributeFees` takes no arguments and the vault is the only party that
+/// can authoritatively price and move what it's sweeping.
+pub fn create_fee_distribution_messages(
+    asset: &crate::msg::AssetData,
+) -> ContractResult<Vec<CosmosMsg>> {
+    let msg = json!({"distribute_fees": {}});
+
+    Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: asset.vault_address.clone(),
+        msg: to_json_binary(&msg)?,
+        funds: vec![],
+    })])
+}
+
 /// Validate asset configuration
 pub fn validate_asset_config(asset: &crate::msg::AssetData) -> ContractResult<()> {
     if asset.unbonding_period == 0 || asset.unbonding_period > 365 {
@@ -252,6 +575,93 @@ pub fn validate_asset_config(asset: &crate::msg::AssetData) -> ContractResult<()
         });
     }
 
+    // Validate that a Stableswap amplification coefficient is non-zero, since
+    // it's used as a divisor when computing tier weights.
+    if let crate::msg::DistributionMode::Stableswap { amplification } = &asset.distribution_mode {
+        if *amplification == 0 {
+            return Err(crate::error::ContractError::InvalidAssetConfig {
+                reason: "Stableswap amplification must be non-zero".to_string(),
+            });
+        }
+    }
+
+    // Validate fee recipients: non-empty addresses, no zero shares, summing to exactly 10000 bps
+    if asset.fee_recipients.is_empty() {
+        return Err(crate::error::ContractError::InvalidAssetConfig {
+            reason: "At least one fee recipient must be provided".to_string(),
+        });
+    }
+    for (i, (address, bps)) in asset.fee_recipients.iter().enumerate() {
+        if address.trim().is_empty() {
+            return Err(crate::error::ContractError::InvalidAssetConfig {
+                reason: format!("Fee recipient address at index {} cannot be empty", i),
+            });
+        }
+        if *bps == 0 {
+            return Err(crate::error::ContractError::InvalidAssetConfig {
+                reason: format!("Fee recipient share at index {} cannot be 0", i),
+            });
+        }
+    }
+    let total_bps: u32 = asset.fee_recipients.iter().map(|(_, bps)| *bps as u32).sum();
+    if total_bps != 10000 {
+        return Err(crate::error::ContractError::InvalidAssetConfig {
+            reason: format!("Fee recipient shares must sum to 10000 bps, got {}", total_bps),
+        });
+    }
+
+    // Validate the rate limiter window/division configuration, if present.
+    if let Some(limiter) = &asset.rate_limiter {
+        if limiter.window_size == 0 || limiter.division_count == 0 {
+            return Err(crate::error::ContractError::InvalidAssetConfig {
+                reason: "Rate limiter window_size and division_count must be non-zero".to_string(),
+            });
+        }
+        if limiter.division_count > limiter.window_size {
+            return Err(crate::error::ContractError::InvalidAssetConfig {
+                reason: format!(
+                    "Rate limiter division_count ({}) cannot exceed window_size ({})",
+                    limiter.division_count, limiter.window_size
+                ),
+            });
+        }
+    }
+
+    // Validate the normalization factor is positive and within a sane range of
+    // 1:1 (a redemption rate collapsing to ~0 or blowing up past 10x almost
+    // certainly means a bad oracle read, not a real LST growth rate).
+    if asset.normalization_factor <= Decimal::zero()
+        || asset.normalization_factor > Decimal::from_ratio(10u64, 1u64)
+    {
+        return Err(crate::error::ContractError::InvalidAssetConfig {
+            reason: format!(
+                "Normalization factor must be between 0 (exclusive) and 10, got {}",
+                asset.normalization_factor
+            ),
+        });
+    }
+
+    // Validate the fixed-fee bounds: min_fee <= max_fee, and fixed_fee, when
+    // set, must fall within [min_fee, max_fee].
+    if asset.min_fee > asset.max_fee {
+        return Err(crate::error::ContractError::InvalidAssetConfig {
+            reason: format!(
+                "min_fee ({}) cannot exceed max_fee ({})",
+                asset.min_fee, asset.max_fee
+            ),
+        });
+    }
+    if let Some(fixed_fee) = asset.fixed_fee {
+        if fixed_fee < asset.min_fee || fixed_fee > asset.max_fee {
+            return Err(crate::error::ContractError::InvalidAssetConfig {
+                reason: format!(
+                    "fixed_fee ({}) must lie within [min_fee, max_fee] ({}, {})",
+                    fixed_fee, asset.min_fee, asset.max_fee
+                ),
+            });
+        }
+    }
+
     // Validate denom is not empty
     if asset.denom.trim().is_empty() {
         return Err(crate::error::ContractError::InvalidAssetConfig {
@@ -393,6 +803,104 @@ pub fn query_apy_contract(
     Ok(result.apy)
 }
 
+/// Raises `base` to an integer power via exponentiation by squaring, used to
+/// compound a periodic redemption-rate growth factor into an annualized one
+/// without floating-point `pow`.
+fn decimal_pow(mut base: Decimal, mut exp: u64) -> ContractResult<Decimal> {
+    let mut result = Decimal::one();
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result
+                .checked_mul(base)
+                .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base
+                .checked_mul(base)
+                .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
+        }
+    }
+    Ok(result)
+}
+
+/// Queries a dasset's `core_contract` directly for its current redemption
+/// rate, used by `ApySource::RedemptionRateGrowth` instead of `apy_contract`.
+pub fn query_redemption_rate(deps: &Deps, core_contract: &Addr) -> ContractResult<Decimal> {
+    let query_request = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: core_contract.to_string(),
+        msg: to_json_binary(&CoreQueryMsg::ExchangeRate {})?,
+    });
+    let rate: Decimal = deps.querier.query(&query_request)?;
+    Ok(rate)
+}
+
+/// Annualizes the growth between `prev`'s sampled redemption rate and
+/// `rate_now`, compounding the periodic return
+/// `rate_now / prev.rate - 1` over `8760 / hours_elapsed` periods per year.
+/// Returns zero (treated the same as a zero external APY, i.e. withdrawal-only
+/// this round) when there's no prior sample yet, the rate dropped (slashing),
+/// or not enough time has passed to measure a period.
+pub fn compute_redemption_rate_apy(
+    prev: Option<&RedemptionRateSample>,
+    rate_now: Decimal,
+    now: u64,
+) -> ContractResult<Decimal> {
+    let Some(prev) = prev else {
+        return Ok(Decimal::zero());
+    };
+    if rate_now < prev.rate {
+        return Ok(Decimal::zero());
+    }
+    let hours_elapsed = now.saturating_sub(prev.timestamp) / 3600;
+    if hours_elapsed == 0 {
+        return Ok(Decimal::zero());
+    }
+    let periods_per_year = 8760 / hours_elapsed;
+    if periods_per_year == 0 {
+        return Ok(Decimal::zero());
+    }
+
+    let growth = rate_now
+        .checked_div(prev.rate)
+        .map_err(|_| crate::error::ContractError::DecimalConversionError)?;
+    let compounded = decimal_pow(growth, periods_per_year)?;
+    Ok(compounded.checked_sub(Decimal::one()).unwrap_or_default())
+}
+
+/// Read-only preview of the APY `ApySource::RedemptionRateGrowth` would
+/// derive, without persisting a new sample. Used by `GetFeeTiers`/
+/// `SimulateFeeTiers` queries, which can't write state.
+pub fn derive_apy_for_asset(deps: &Deps, asset: &AssetData, now: u64) -> ContractResult<Decimal> {
+    let core_contract = deps.api.addr_validate(&asset.core_contract)?;
+    let rate_now = query_redemption_rate(deps, &core_contract)?;
+    let prev = REDEMPTION_RATE_SAMPLES.may_load(deps.storage, asset.denom.clone())?;
+    compute_redemption_rate_apy(prev.as_ref(), rate_now, now)
+}
+
+/// Samples `asset`'s current redemption rate, derives APY from its growth
+/// against the previously stored sample, and rolls the stored sample forward
+/// to `(rate_now, now)` so the next call measures from this point.
+pub fn accrue_redemption_rate_apy(
+    deps: &mut DepsMut,
+    asset: &AssetData,
+    now: u64,
+) -> ContractResult<Decimal> {
+    let core_contract = deps.api.addr_validate(&asset.core_contract)?;
+    let rate_now = query_redemption_rate(&deps.as_ref(), &core_contract)?;
+    let prev = REDEMPTION_RATE_SAMPLES.may_load(deps.storage, asset.denom.clone())?;
+    let apy = compute_redemption_rate_apy(prev.as_ref(), rate_now, now)?;
+    REDEMPTION_RATE_SAMPLES.save(
+        deps.storage,
+        asset.denom.clone(),
+        &RedemptionRateSample {
+            rate: rate_now,
+            timestamp: now,
+        },
+    )?;
+    Ok(apy)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -671,10 +1179,18 @@ mod tests {
             // Step 4: Create vault update message
             let vault_address = "neutron1test_vault_address";
             let sender = "neutron1test_sender";
+            let denom = "factory/neutron1test/udtest";
+            let mut deps = cosmwasm_std::testing::mock_dependencies();
+            let env = cosmwasm_std::testing::mock_env();
             let update_msg = create_vault_update_message(
+                &mut deps.as_mut(),
+                &env,
+                denom,
                 vault_address,
+                base_fee,
                 &fee_tiers,
                 oracle_skew,
+                None,
                 sender,
             ).unwrap();
             
@@ -707,6 +1223,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result = validate_asset_config(&valid_asset);
@@ -727,6 +1251,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result = validate_asset_config(&asset);
@@ -751,6 +1283,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result = validate_asset_config(&asset);
@@ -777,6 +1317,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result = validate_asset_config(&asset);
@@ -792,6 +1340,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result2 = validate_asset_config(&asset2);
@@ -807,6 +1363,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result3 = validate_asset_config(&asset3);
@@ -827,6 +1391,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result = validate_asset_config(&asset);
@@ -842,6 +1414,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result2 = validate_asset_config(&asset2);
@@ -857,6 +1437,14 @@ mod tests {
             vault_address: "".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result3 = validate_asset_config(&asset3);
@@ -877,6 +1465,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result = validate_asset_config(&asset);
@@ -892,6 +1488,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 200, // > 168
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result2 = validate_asset_config(&asset2);
@@ -907,6 +1511,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 600, // > 500
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let result3 = validate_asset_config(&asset3);
@@ -926,6 +1538,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let asset2 = AssetData {
@@ -937,6 +1557,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let msg = InstantiateMsg {
@@ -962,6 +1590,14 @@ mod tests {
             vault_address: "neutron1test_vault1".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let asset2 = AssetData {
@@ -973,6 +1609,14 @@ mod tests {
             vault_address: "neutron1test_vault2".to_string(),
             query_period_hours: 72,
             fee_dempening_amount: 10,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let msg = InstantiateMsg {
@@ -999,6 +1643,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let update_config = UpdateConfig {
@@ -1066,6 +1718,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let update_config = UpdateConfig {
@@ -1091,6 +1751,14 @@ mod tests {
             vault_address: "neutron1test_vault".to_string(),
             query_period_hours: 24,
             fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
         };
 
         let update_config = UpdateConfig {
@@ -1122,4 +1790,349 @@ mod tests {
         let result = validate_update_config(&update_config);
         assert!(result.is_ok(), "Valid whitelist update should pass validation");
     }
+
+    #[test]
+    fn test_create_fee_tiers_for_asset_uniform_matches_create_fee_tiers() {
+        let base_fee = 30;
+        let fee_tier_values = vec![0, 10];
+        let percentages = vec![35, 65];
+
+        let uniform = create_fee_tiers_for_asset(
+            base_fee,
+            &fee_tier_values,
+            &percentages,
+            &crate::msg::DistributionMode::Uniform,
+        )
+        .unwrap();
+        let plain = create_fee_tiers(base_fee, &fee_tier_values, &percentages).unwrap();
+
+        assert_eq!(uniform.len(), plain.len());
+        for (a, b) in uniform.iter().zip(plain.iter()) {
+            assert_eq!(a.fee, b.fee);
+            assert_eq!(a.percentage, b.percentage);
+        }
+    }
+
+    #[test]
+    fn test_create_fee_tiers_for_asset_stableswap_concentrates_near_peg() {
+        let base_fee = 0;
+        let fee_tier_values = vec![0, 10, 40];
+
+        let fee_tiers = create_fee_tiers_for_asset(
+            base_fee,
+            &fee_tier_values,
+            &[1, 1, 1], // ignored under Stableswap
+            &crate::msg::DistributionMode::Stableswap { amplification: 10 },
+        )
+        .unwrap();
+
+        let total_percentage: u64 = fee_tiers.iter().map(|t| t.percentage).sum();
+        assert_eq!(total_percentage, 100, "Stableswap weights must still sum to 100");
+
+        // the tier closest to the base index should carry the most weight
+        assert!(fee_tiers[0].percentage > fee_tiers[1].percentage);
+        assert!(fee_tiers[1].percentage > fee_tiers[2].percentage);
+    }
+
+    #[test]
+    fn test_create_fee_tiers_for_asset_stableswap_zero_amplification_rejected() {
+        let result = create_fee_tiers_for_asset(
+            0,
+            &[0, 10],
+            &[50, 50],
+            &crate::msg::DistributionMode::Stableswap { amplification: 0 },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_validate_asset_config_stableswap_zero_amplification() {
+        use crate::msg::AssetData;
+
+        let asset = AssetData {
+            denom: "factory/neutron1test/udtest".to_string(),
+            core_contract: "neutron1test_core".to_string(),
+            unbonding_period: 21,
+            fee_spacings: vec![0, 10],
+            percentages: vec![35, 65],
+            vault_address: "neutron1test_vault".to_string(),
+            query_period_hours: 24,
+            fee_dempening_amount: 0,
+            distribution_mode: crate::msg::DistributionMode::Stableswap { amplification: 0 },
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
+        };
+
+        let result = validate_asset_config(&asset);
+        assert!(result.is_err(), "Zero amplification should fail validation");
+    }
+
+    #[test]
+    fn test_validate_asset_config_fee_recipients_invalid_shares() {
+        use crate::msg::AssetData;
+
+        let mut asset = AssetData {
+            denom: "factory/neutron1test/udtest".to_string(),
+            core_contract: "neutron1test_core".to_string(),
+            unbonding_period: 21,
+            fee_spacings: vec![0, 10],
+            percentages: vec![35, 65],
+            vault_address: "neutron1test_vault".to_string(),
+            query_period_hours: 24,
+            fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![
+                ("neutron1treasury".to_string(), 7000),
+                ("neutron1staking".to_string(), 2000),
+            ],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
+        };
+        // 7000 + 2000 = 9000, not 10000
+        assert!(validate_asset_config(&asset).is_err());
+
+        asset.fee_recipients = vec![("neutron1treasury".to_string(), 0)];
+        assert!(validate_asset_config(&asset).is_err(), "Zero share should fail validation");
+
+        asset.fee_recipients = vec![];
+        assert!(validate_asset_config(&asset).is_err(), "Empty fee recipients should fail validation");
+    }
+
+    #[test]
+    fn test_validate_asset_config_rate_limiter() {
+        use crate::msg::{AssetData, RateLimiterConfig};
+
+        let mut asset = AssetData {
+            denom: "factory/neutron1test/udtest".to_string(),
+            core_contract: "neutron1test_core".to_string(),
+            unbonding_period: 21,
+            fee_spacings: vec![0, 10],
+            percentages: vec![35, 65],
+            vault_address: "neutron1test_vault".to_string(),
+            query_period_hours: 24,
+            fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: Some(RateLimiterConfig {
+                window_size: 3600,
+                division_count: 6,
+                base_fee_boundary_offset: 5,
+                oracle_skew_boundary_offset: 5,
+                symmetric: false,
+            }),
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
+        };
+        assert!(validate_asset_config(&asset).is_ok());
+
+        asset.rate_limiter.as_mut().unwrap().window_size = 0;
+        assert!(validate_asset_config(&asset).is_err(), "Zero window_size should fail validation");
+
+        asset.rate_limiter.as_mut().unwrap().window_size = 3600;
+        asset.rate_limiter.as_mut().unwrap().division_count = 7200;
+        assert!(
+            validate_asset_config(&asset).is_err(),
+            "division_count exceeding window_size should fail validation"
+        );
+    }
+
+    #[test]
+    fn test_clamp_to_moving_average_first_update_passes_through() {
+        let mut divisions = Vec::new();
+        let value = clamp_to_moving_average(&mut divisions, 1_000, 50, 5, false, 3600, 6);
+        assert_eq!(value, 50);
+        assert_eq!(divisions.len(), 1);
+        assert_eq!(divisions[0].latest_value, 50);
+    }
+
+    #[test]
+    fn test_clamp_to_moving_average_clamps_spike_above_offset() {
+        let mut divisions = Vec::new();
+        // Seed a division that's been sitting at 10 for a while so the
+        // moving average is well established before the spike.
+        clamp_to_moving_average(&mut divisions, 0, 10, 5, false, 3600, 6);
+        let clamped = clamp_to_moving_average(&mut divisions, 1800, 100, 5, false, 3600, 6);
+        // moving_average over [0, 1800) is 10 (flat), so anything past 15 is clamped.
+        assert_eq!(clamped, 15);
+    }
+
+    #[test]
+    fn test_clamp_to_moving_average_symmetric_clamps_drop_too() {
+        let mut divisions = Vec::new();
+        clamp_to_moving_average(&mut divisions, 0, 10, 5, true, 3600, 6);
+        let clamped = clamp_to_moving_average(&mut divisions, 1800, -100, 5, true, 3600, 6);
+        assert_eq!(clamped, 5);
+    }
+
+    #[test]
+    fn test_clamp_to_moving_average_prunes_stale_divisions() {
+        let mut divisions = Vec::new();
+        clamp_to_moving_average(&mut divisions, 0, 10, 5, false, 3600, 6);
+        // Well past window_size later, the old division should be pruned and
+        // the new value pass through untouched (no prior data in-window).
+        let clamped = clamp_to_moving_average(&mut divisions, 10_000, 1000, 5, false, 3600, 6);
+        assert_eq!(clamped, 1000);
+    }
+
+    #[test]
+    fn test_compute_oracle_skew_at_parity_is_base_fee_plus_one() {
+        assert_eq!(compute_oracle_skew(40, Decimal::one()).unwrap(), 41);
+    }
+
+    #[test]
+    fn test_compute_oracle_skew_shifts_up_for_redemption_rate_above_one() {
+        // 1% above parity shifts the skew up by ~100 ticks (0.01 * 2 * FEE_TIER_CONSTANT).
+        let skew = compute_oracle_skew(40, Decimal::from_str("1.01").unwrap()).unwrap();
+        assert!(skew > 41, "expected skew to shift above base_fee + 1, got {}", skew);
+    }
+
+    #[test]
+    fn test_compute_oracle_skew_shifts_down_for_redemption_rate_below_one() {
+        let skew = compute_oracle_skew(40, Decimal::from_str("0.99").unwrap()).unwrap();
+        assert!(skew < 41, "expected skew to shift below base_fee + 1, got {}", skew);
+    }
+
+    #[test]
+    fn test_validate_asset_config_normalization_factor() {
+        use crate::msg::AssetData;
+
+        let mut asset = AssetData {
+            denom: "factory/neutron1test/udtest".to_string(),
+            core_contract: "neutron1test_core".to_string(),
+            unbonding_period: 21,
+            fee_spacings: vec![0, 10],
+            percentages: vec![35, 65],
+            vault_address: "neutron1test_vault".to_string(),
+            query_period_hours: 24,
+            fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 0,
+            max_fee: 1000,
+            fixed_fee: None,
+        };
+        assert!(validate_asset_config(&asset).is_ok());
+
+        asset.normalization_factor = Decimal::zero();
+        assert!(validate_asset_config(&asset).is_err(), "zero normalization_factor should fail validation");
+
+        asset.normalization_factor = Decimal::from_ratio(11u64, 1u64);
+        assert!(validate_asset_config(&asset).is_err(), "normalization_factor above 10 should fail validation");
+    }
+
+    #[test]
+    fn test_compute_redemption_rate_apy_no_prior_sample_is_zero() {
+        let apy = compute_redemption_rate_apy(None, Decimal::from_str("1.05").unwrap(), 100_000).unwrap();
+        assert!(apy.is_zero());
+    }
+
+    #[test]
+    fn test_compute_redemption_rate_apy_compounds_growth() {
+        let prev = RedemptionRateSample {
+            rate: Decimal::one(),
+            timestamp: 0,
+        };
+        // 1-day sample (24h) of 0.1% growth annualizes to (1.001)^365 - 1 ~= 44%.
+        let now = 24 * 3600;
+        let apy = compute_redemption_rate_apy(Some(&prev), Decimal::from_str("1.001").unwrap(), now).unwrap();
+        assert!(
+            apy > Decimal::from_str("0.40").unwrap() && apy < Decimal::from_str("0.50").unwrap(),
+            "expected apy near 44%, got {}",
+            apy
+        );
+    }
+
+    #[test]
+    fn test_compute_redemption_rate_apy_skips_on_slashing() {
+        let prev = RedemptionRateSample {
+            rate: Decimal::from_str("1.05").unwrap(),
+            timestamp: 0,
+        };
+        let apy = compute_redemption_rate_apy(Some(&prev), Decimal::from_str("1.0").unwrap(), 24 * 3600).unwrap();
+        assert!(apy.is_zero());
+    }
+
+    #[test]
+    fn test_compute_redemption_rate_apy_skips_on_near_zero_elapsed() {
+        let prev = RedemptionRateSample {
+            rate: Decimal::one(),
+            timestamp: 100,
+        };
+        let apy = compute_redemption_rate_apy(Some(&prev), Decimal::from_str("1.001").unwrap(), 200).unwrap();
+        assert!(apy.is_zero());
+    }
+
+    #[test]
+    fn test_validate_asset_config_fixed_fee_bounds() {
+        use crate::msg::AssetData;
+
+        let mut asset = AssetData {
+            denom: "factory/neutron1test/udtest".to_string(),
+            core_contract: "neutron1test_core".to_string(),
+            unbonding_period: 21,
+            fee_spacings: vec![0, 10],
+            percentages: vec![35, 65],
+            vault_address: "neutron1test_vault".to_string(),
+            query_period_hours: 24,
+            fee_dempening_amount: 0,
+            distribution_mode: DistributionMode::Uniform,
+            fee_recipients: vec![("neutron1admin1".to_string(), 10000)],
+            rate_limiter: None,
+            normalization_factor: Decimal::one(),
+            apy_source: ApySource::ApyContract,
+            min_fee: 10,
+            max_fee: 100,
+            fixed_fee: None,
+        };
+        assert!(validate_asset_config(&asset).is_ok());
+
+        asset.min_fee = 200;
+        assert!(validate_asset_config(&asset).is_err(), "min_fee exceeding max_fee should fail validation");
+        asset.min_fee = 10;
+
+        asset.fixed_fee = Some(5);
+        assert!(validate_asset_config(&asset).is_err(), "fixed_fee below min_fee should fail validation");
+
+        asset.fixed_fee = Some(200);
+        assert!(validate_asset_config(&asset).is_err(), "fixed_fee above max_fee should fail validation");
+
+        asset.fixed_fee = Some(50);
+        assert!(validate_asset_config(&asset).is_ok());
+    }
+
+    #[test]
+    fn test_resolve_tier_spot_prices_centers_on_oracle_skew() {
+        let fee_tiers = vec![FeeTier::new(40, 100)];
+        let tiers = resolve_tier_spot_prices(&fee_tiers, 10, Decimal::one()).unwrap();
+        assert_eq!(tiers.len(), 1);
+        assert_eq!(tiers[0].fee, 40);
+        assert_eq!(tiers[0].percentage, 100);
+        // bid tick = 10 - 40 = -30, ask tick = 10 + 40 = 50: bid below ask, both around parity.
+        assert!(tiers[0].bid_price < Decimal::one());
+        assert!(tiers[0].ask_price > Decimal::one());
+        assert!(tiers[0].bid_price < tiers[0].ask_price);
+    }
+
+    #[test]
+    fn test_resolve_tier_spot_prices_scales_by_oracle_price() {
+        let fee_tiers = vec![FeeTier::new(0, 100)];
+        let tiers = resolve_tier_spot_prices(&fee_tiers, 0, Decimal::from_str("1.2").unwrap()).unwrap();
+        assert_eq!(tiers[0].bid_price, Decimal::from_str("1.2").unwrap());
+        assert_eq!(tiers[0].ask_price, Decimal::from_str("1.2").unwrap());
+    }
 }
@@ -0,0 +1,199 @@
+//! Terminal UI that renders the exact `dynamic_spread_adjustment`/`bend`
+//! curve this contract applies on-chain, so an operator can see what a
+//! `(spread_cap, factor, fee_tiers)` config actually does before deploying
+//! it. Imports the library crate directly (no reimplementation) so the
+//! rendered curve can never drift from on-chain behavior.
+//!
+//! `cargo run --bin fee_curve_simulator`, then `q` to quit, arrow keys to
+//! adjust `spread_cap`, `[`/`]` to step `factor`.
+
+use std::io;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, ExecutableCommand};
+use neutron_std::types::neutron::util::precdec::PrecDec;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::symbols;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Axis, Block, Borders, Cell, Chart, Dataset, Paragraph, Row, Table};
+use ratatui::Terminal;
+
+use mmvauilt::spread_curve::{bend, SpreadFactors};
+use mmvauilt::state::FeeTier;
+use mmvauilt::utils::dynamic_spread_adjustment;
+
+/// Number of imbalance ratios sampled across `[-1.0, 1.0]` for the plotted
+/// curve. Fine enough to show the logistic curve's flat/steep/flat shape
+/// without flooding the terminal with points.
+const SAMPLE_COUNT: usize = 101;
+
+struct AppState {
+    spread_cap: u64,
+    factor: i32,
+    fee_tiers: Vec<FeeTier>,
+}
+
+impl AppState {
+    fn curve_points(&self) -> Vec<(f64, f64)> {
+        (0..SAMPLE_COUNT)
+            .map(|i| {
+                let ratio = -1.0 + 2.0 * (i as f64) / ((SAMPLE_COUNT - 1) as f64);
+                let widen = ratio >= 0.0;
+                let imbalance = PrecDec::from_ratio((ratio.abs() * 1_000_000.0) as u128, 1_000_000u128);
+                let (tick_offset, _) = dynamic_spread_adjustment(
+                    self.spread_cap,
+                    SpreadFactors::symmetric(self.factor),
+                    imbalance,
+                    widen,
+                    &self.fee_tiers,
+                )
+                .unwrap_or((0, self.fee_tiers.clone()));
+                (ratio, tick_offset as f64)
+            })
+            .collect()
+    }
+
+    fn adjusted_fee_tiers(&self, ratio: f64) -> Vec<FeeTier> {
+        let widen = ratio >= 0.0;
+        let imbalance = PrecDec::from_ratio((ratio.abs() * 1_000_000.0) as u128, 1_000_000u128);
+        dynamic_spread_adjustment(
+            self.spread_cap,
+            SpreadFactors::symmetric(self.factor),
+            imbalance,
+            widen,
+            &self.fee_tiers,
+        )
+        .map(|(_, tiers)| tiers)
+        .unwrap_or_else(|_| self.fee_tiers.clone())
+    }
+
+    /// Boundary values an operator cares about: the zero crossing (always
+    /// `0` by construction — every curve agrees with linear at the
+    /// midpoint), the value at `+/-cap`, and the rounding-to-zero
+    /// threshold below which a small `spread_cap` has no precision left to
+    /// express any adjustment at all.
+    fn boundary_values(&self) -> Vec<(&'static str, String)> {
+        let at_full_cap = dynamic_spread_adjustment(
+            self.spread_cap,
+            SpreadFactors::symmetric(self.factor),
+            PrecDec::one(),
+            true,
+            &self.fee_tiers,
+        )
+        .map(|(tick, _)| tick)
+        .unwrap_or(0);
+
+        let mut rounds_to_zero_threshold = None;
+        for bps in 1..=10000u64 {
+            let ratio = bps as f64 / 10000.0;
+            let imbalance = PrecDec::from_ratio(bps, 10000u128);
+            let (tick, _) = dynamic_spread_adjustment(
+                self.spread_cap,
+                SpreadFactors::symmetric(self.factor),
+                imbalance,
+                true,
+                &self.fee_tiers,
+            )
+            .unwrap_or((0, vec![]));
+            if tick != 0 {
+                rounds_to_zero_threshold = Some(ratio);
+                break;
+            }
+        }
+
+        vec![
+            ("zero crossing", "0.0 (every curve agrees with linear here)".to_string()),
+            ("value at +cap", at_full_cap.to_string()),
+            (
+                "smallest ratio with a nonzero adjustment",
+                rounds_to_zero_threshold.map(|r| format!("{r:.4}")).unwrap_or_else(|| "none below 1.0".to_string()),
+            ),
+        ]
+    }
+}
+
+fn main() -> io::Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let mut state = AppState {
+        spread_cap: 100,
+        factor: 0,
+        fee_tiers: vec![FeeTier { fee: 10, percentage: 100 }],
+    };
+
+    loop {
+        terminal.draw(|frame| draw(frame, &state))?;
+
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') => break,
+                    KeyCode::Up => state.spread_cap = state.spread_cap.saturating_add(10),
+                    KeyCode::Down => state.spread_cap = state.spread_cap.saturating_sub(10),
+                    KeyCode::Char(']') => state.factor = state.factor.saturating_add(1),
+                    KeyCode::Char('[') => state.factor = state.factor.saturating_sub(1),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    disable_raw_mode()?;
+    io::stdout().execute(LeaveAlternateScreen)?;
+    Ok(())
+}
+
+fn draw(frame: &mut ratatui::Frame, state: &AppState) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(frame.area());
+
+    let right_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(columns[1]);
+
+    let points = state.curve_points();
+    let cap = state.spread_cap as f64;
+    let dataset = Dataset::default()
+        .name("tick_offset")
+        .marker(symbols::Marker::Braille)
+        .style(Style::default().fg(Color::Cyan))
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "tick offset vs imbalance ratio (spread_cap={}, factor={})",
+            state.spread_cap, state.factor
+        )))
+        .x_axis(Axis::default().title("imbalance ratio").bounds([-1.0, 1.0]))
+        .y_axis(Axis::default().title("tick offset").bounds([-cap, cap]));
+    frame.render_widget(chart, columns[0]);
+
+    let tier_rows: Vec<Row> = state
+        .adjusted_fee_tiers(0.5)
+        .into_iter()
+        .map(|tier| Row::new(vec![Cell::from(tier.fee.to_string()), Cell::from(tier.percentage.to_string())]))
+        .collect();
+    let tier_table = Table::new(tier_rows, [Constraint::Length(10), Constraint::Length(10)])
+        .header(Row::new(vec!["fee", "percentage"]))
+        .block(Block::default().borders(Borders::ALL).title("fee_tiers at ratio=0.5"));
+    frame.render_widget(tier_table, right_rows[0]);
+
+    let boundary_lines: Vec<Line> = state
+        .boundary_values()
+        .into_iter()
+        .map(|(label, value)| Line::from(vec![Span::raw(format!("{label}: {value}"))]))
+        .collect();
+    let boundary_panel = Paragraph::new(boundary_lines)
+        .block(Block::default().borders(Borders::ALL).title("boundary values"));
+    frame.render_widget(boundary_panel, right_rows[1]);
+}
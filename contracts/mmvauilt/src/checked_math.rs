@@ -0,0 +1,61 @@
+use cosmwasm_std::{Int128, Uint128};
+use neutron_std::types::neutron::util::precdec::PrecDec;
+
+use crate::error::ContractError;
+
+/// Overflow-checked arithmetic for the `PrecDec`/`Uint128`/`Int128` math
+/// scattered through `utils`, collapsing the repeated
+/// `.checked_add(...).map_err(|_| ContractError::Overflow)` boilerplate into
+/// a single `.try_add(...)` call. Every trait method returns
+/// [`ContractError::Overflow`] (add/sub/mul) or
+/// [`ContractError::DivideByZero`] (div) instead of panicking, so no
+/// multiply/divide built on these traits can panic on adversarial input
+/// (e.g. near-`Uint128::MAX` reserves) — it surfaces as a clean contract
+/// error instead.
+pub trait TryAdd: Sized {
+    fn try_add(self, other: Self) -> Result<Self, ContractError>;
+}
+
+pub trait TrySub: Sized {
+    fn try_sub(self, other: Self) -> Result<Self, ContractError>;
+}
+
+pub trait TryMul: Sized {
+    fn try_mul(self, other: Self) -> Result<Self, ContractError>;
+}
+
+pub trait TryDiv: Sized {
+    fn try_div(self, other: Self) -> Result<Self, ContractError>;
+}
+
+macro_rules! impl_checked_math {
+    ($ty:ty) => {
+        impl TryAdd for $ty {
+            fn try_add(self, other: Self) -> Result<Self, ContractError> {
+                self.checked_add(other).map_err(|_| ContractError::Overflow)
+            }
+        }
+
+        impl TrySub for $ty {
+            fn try_sub(self, other: Self) -> Result<Self, ContractError> {
+                self.checked_sub(other).map_err(|_| ContractError::Overflow)
+            }
+        }
+
+        impl TryMul for $ty {
+            fn try_mul(self, other: Self) -> Result<Self, ContractError> {
+                self.checked_mul(other).map_err(|_| ContractError::Overflow)
+            }
+        }
+
+        impl TryDiv for $ty {
+            fn try_div(self, other: Self) -> Result<Self, ContractError> {
+                self.checked_div(other).map_err(|_| ContractError::DivideByZero)
+            }
+        }
+    };
+}
+
+impl_checked_math!(PrecDec);
+impl_checked_math!(Uint128);
+impl_checked_math!(Int128);
@@ -1,8 +1,11 @@
 use crate::error::{ContractError, ContractResult};
 use crate::execute::*;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, WithdrawPayload};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use crate::query::*;
-use crate::state::{Balances, Config, PairData, CONFIG};
+use crate::state::{
+    Balances, Config, FeeSplitterConfig, PairData, WithdrawalWindow, ALLOWED_FEE_TIERS, CONFIG,
+    DEX_DEPOSIT_REPLY_ID, DEX_USER_WITHDRAW_REPLY_ID, REWARD_CLAIM_REPLY_ID, WITHDRAWAL_WINDOW,
+};
 use crate::utils::*;
 use cosmwasm_std::{
     attr, entry_point, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Reply, Response, Uint128,
@@ -16,8 +19,15 @@ use std::str::FromStr;
 ///////////////
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> ContractResult<Response> {
-    unimplemented!()
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> ContractResult<Response> {
+    let (from_version, to_version) =
+        crate::migrations::run(deps, CONTRACT_NAME, CONTRACT_VERSION, msg)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("contract", CONTRACT_NAME)
+        .add_attribute("from_version", from_version)
+        .add_attribute("to_version", to_version))
 }
 
 const CONTRACT_NAME: &str = concat!("crates.io:neutron-contracts__", env!("CARGO_PKG_NAME"));
@@ -45,8 +55,26 @@ pub fn instantiate(
         api: deps.api,
         querier: deps.querier,
     };
-    validate_market(&deps_readonly, &_env, &msg.token_a.pair, msg.max_block_old)?;
-    validate_market(&deps_readonly, &_env, &msg.token_b.pair, msg.max_block_old)?;
+    validate_market(
+        &deps_readonly,
+        &_env,
+        &msg.token_a.pair,
+        msg.max_block_old,
+        msg.token_a.max_price_age_seconds,
+        &msg.stable_denoms,
+    )?;
+    validate_market(
+        &deps_readonly,
+        &_env,
+        &msg.token_b.pair,
+        msg.max_block_old,
+        msg.token_b.max_price_age_seconds,
+        &msg.stable_denoms,
+    )?;
+
+    let allowed_fee_tiers = query_dex_fee_tiers(&deps_readonly);
+    InstantiateMsg::validate_base_fee(msg.base_fee, &allowed_fee_tiers)?;
+    ALLOWED_FEE_TIERS.save(deps.storage, &allowed_fee_tiers)?;
 
     let pairs = PairData {
         token_0: tokens[0].clone(),
@@ -67,12 +95,179 @@ pub fn instantiate(
         base_deposit_percentage: msg.base_deposit_percentage,
         ambient_fee: msg.ambient_fee,
         deposit_ambient: msg.deposit_ambient,
-        owner,
+        owner: owner.clone(),
         deposit_cap: msg.deposit_cap,
+        total_shares: Uint128::zero(),
+        admin: owner,
+        status: crate::state::ContractStatus::Operational,
+        status_reason: None,
+        pause_block: None,
+        withdrawal_limit_token_0: scale_withdrawal_limit(msg.withdrawal_limit, tokens[0].decimals),
+        withdrawal_limit_token_1: scale_withdrawal_limit(msg.withdrawal_limit, tokens[1].decimals),
+        max_slippage_bps: msg.max_slippage_bps,
+        incentives: None,
+        ema_alpha: msg.ema_alpha,
+        ema_max_deviation_bps: msg.ema_max_deviation_bps,
+        ema_fallback: msg.ema_fallback,
+        target_rate_provider: msg
+            .target_rate_provider
+            .as_ref()
+            .map(|addr| deps.api.addr_validate(addr))
+            .transpose()?,
+        target_rate_max_blocks_old: msg.target_rate_max_blocks_old,
+        target_rate_amortization_seconds: msg.target_rate_amortization_seconds,
+        max_target_rate_deviation_bps: msg.max_target_rate_deviation_bps,
+        target_rate_max_drift_bps: msg.target_rate_max_drift_bps,
+        book_aware_valuation: msg.book_aware_valuation,
+        fee_splitter: msg
+            .fee_splitter
+            .as_ref()
+            .map(|recipients| {
+                recipients
+                    .iter()
+                    .map(|(addr, weight)| Ok((deps.api.addr_validate(addr)?, *weight)))
+                    .collect::<ContractResult<Vec<_>>>()
+            })
+            .transpose()?
+            .map(|recipients| FeeSplitterConfig { recipients }),
+        accrued_fees: Balances {
+            token_0: Coin::new(Uint128::zero(), tokens[0].denom.clone()),
+            token_1: Coin::new(Uint128::zero(), tokens[1].denom.clone()),
+        },
+        skew: msg.skew,
+        imbalance_bps: msg.imbalance_bps,
+        oracle_price_skew: msg.oracle_price_skew,
+        max_ema_age_seconds: msg.max_ema_age_seconds,
+        max_conf_ratio_bps: msg.max_conf_ratio_bps,
+        deposit_band: msg.deposit_band.clone(),
+        rebalance_threshold_bps: msg.rebalance_threshold_bps,
+        rebalance_target_bps: msg.rebalance_target_bps,
+        max_rebalance_ticks: msg.max_rebalance_ticks,
+        max_rebalance_slippage_bps: msg.max_rebalance_slippage_bps,
+        performance_fee_bps: msg.performance_fee_bps,
+        swap_fee_bps: msg.swap_fee_bps,
+        staking_target: msg
+            .staking_target
+            .as_ref()
+            .map(|addr| deps.api.addr_validate(addr))
+            .transpose()?,
+        unbonding_period_seconds: msg.unbonding_period_seconds,
+        price_ema_tau_seconds: msg.price_ema_tau_seconds,
+        max_price_deviation_bps: msg.max_price_deviation_bps,
+        price_divergence_fallback: msg.price_divergence_fallback,
+        change_limiter: msg.change_limiter.clone(),
+        per_address_cap: msg.per_address_cap,
+        dynamic_spread_cap: msg.dynamic_spread_cap,
+        cw20_token_0: msg
+            .cw20_token_0
+            .as_ref()
+            .map(|addr| deps.api.addr_validate(addr))
+            .transpose()?,
+        cw20_token_1: msg
+            .cw20_token_1
+            .as_ref()
+            .map(|addr| deps.api.addr_validate(addr))
+            .transpose()?,
+        withdrawal_queue_period_seconds: msg.withdrawal_queue_period_seconds,
+        fee_tiers: msg.fee_tiers.clone(),
+        deposit_curve: msg.deposit_curve.clone(),
+        volatility_spread: msg.volatility_spread.clone(),
+        timelock_blocks: msg.timelock_blocks,
+        oracle_contracts: msg
+            .oracle_contracts
+            .iter()
+            .map(|addr| deps.api.addr_validate(addr))
+            .collect::<Result<Vec<_>, _>>()?,
+        min_sources: msg.min_sources,
+        max_oracle_deviation_bps: msg.max_oracle_deviation_bps,
+        twap_window_seconds: msg.twap_window_seconds,
+        max_twap_deviation_bps: msg.max_twap_deviation_bps,
+        redemption_adapter: msg
+            .redemption_adapter_source
+            .as_ref()
+            .map(|source| -> ContractResult<_> {
+                Ok(crate::state::RedemptionAdapterConfig {
+                    lst_asset_denom: msg
+                        .redemption_adapter_lst_denom
+                        .clone()
+                        .ok_or_else(|| ContractError::MalformedInput {
+                            input: "redemption_adapter_lst_denom".to_string(),
+                            reason: "must be set when redemption_adapter_source is set".to_string(),
+                        })?,
+                    source: source.validate(deps.api)?,
+                    min_redemption_rate: msg.redemption_adapter_min_rate.ok_or_else(|| {
+                        ContractError::MalformedInput {
+                            input: "redemption_adapter_min_rate".to_string(),
+                            reason: "must be set when redemption_adapter_source is set".to_string(),
+                        }
+                    })?,
+                    max_redemption_rate: msg.redemption_adapter_max_rate.ok_or_else(|| {
+                        ContractError::MalformedInput {
+                            input: "redemption_adapter_max_rate".to_string(),
+                            reason: "must be set when redemption_adapter_source is set".to_string(),
+                        }
+                    })?,
+                    max_redemption_rate_change_bps: msg
+                        .redemption_adapter_max_rate_change_bps
+                        .ok_or_else(|| ContractError::MalformedInput {
+                            input: "redemption_adapter_max_rate_change_bps".to_string(),
+                            reason: "must be set when redemption_adapter_source is set".to_string(),
+                        })?,
+                    max_rate_age_seconds: msg.redemption_adapter_max_rate_age_seconds.ok_or_else(
+                        || ContractError::MalformedInput {
+                            input: "redemption_adapter_max_rate_age_seconds".to_string(),
+                            reason: "must be set when redemption_adapter_source is set".to_string(),
+                        },
+                    )?,
+                })
+            })
+            .transpose()?,
+        management_fee_bps: msg.management_fee_bps,
+        fee_collector: msg
+            .fee_collector
+            .as_ref()
+            .map(|addr| deps.api.addr_validate(addr))
+            .transpose()?,
+        max_total_shares: msg.max_total_shares,
+        market_making: msg.market_making.clone(),
+        reward_claim_contracts: msg
+            .reward_claim_contracts
+            .iter()
+            .map(|addr| deps.api.addr_validate(addr))
+            .collect::<Result<Vec<_>, _>>()?,
+        max_price_jump_bps: msg.max_price_jump_bps,
+        stable_denoms: msg.stable_denoms,
+        // a new vault has never had `ExecuteMsg::FreezeConfig` called on it
+        config_frozen: false,
+        min_dex_deposit_interval_seconds: msg.min_dex_deposit_interval_seconds,
+        stableswap_amplification: msg.stableswap_amplification,
+        dex_deviation_bps: msg.dex_deviation_bps,
+        dex_deviation_cooldown_blocks: msg.dex_deviation_cooldown_blocks,
+        min_deposit_amount_0: msg.min_deposit_amount_0,
+        min_deposit_amount_1: msg.min_deposit_amount_1,
+        min_rebalance_amount_0: msg.min_rebalance_amount_0,
+        min_rebalance_amount_1: msg.min_rebalance_amount_1,
+        rebalance_strategy: msg.rebalance_strategy,
+        max_oracle_price_skew_ticks: msg.max_oracle_price_skew_ticks,
+        signers: msg
+            .signers
+            .iter()
+            .map(|addr| deps.api.addr_validate(addr))
+            .collect::<Result<Vec<_>, _>>()?,
+        threshold: msg.threshold,
+        rebalance_drift_tolerance_ticks: msg.rebalance_drift_tolerance_ticks,
     };
+    config.validate(&allowed_fee_tiers)?;
 
     // PAIRDATA.save(deps.storage, &pool_data)?;
     CONFIG.save(deps.storage, &config)?;
+    WITHDRAWAL_WINDOW.save(
+        deps.storage,
+        &WithdrawalWindow {
+            window_start: _env.block.height,
+            ..Default::default()
+        },
+    )?;
 
     Ok(Response::new()
         .add_attribute("action", "instantiate")
@@ -101,13 +296,55 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Deposit { .. } => deposit(deps, _env, info),
-        ExecuteMsg::Withdraw { .. } => {
+        ExecuteMsg::Deposit {
+            min_shares_out,
+            beneficiary,
+            auto_balance,
+        } => deposit(deps, _env, info, min_shares_out, beneficiary, auto_balance),
+        ExecuteMsg::Receive(cw20_msg) => receive_cw20(deps, _env, info, cw20_msg),
+        ExecuteMsg::Withdraw {
+            amount,
+            min_amount_0_out,
+            min_amount_1_out,
+            deadline,
+            receiver,
+        } => {
             // Prevent tokens from being sent with the Withdraw message
             if !info.funds.is_empty() {
                 return Err(ContractError::FundsNotAllowed);
             }
-            withdraw(deps, _env, info)
+            withdraw(
+                deps,
+                _env,
+                info,
+                amount,
+                min_amount_0_out,
+                min_amount_1_out,
+                deadline,
+                receiver,
+            )
+        }
+        ExecuteMsg::WithdrawPosition {
+            token_id,
+            min_amount_0_out,
+            min_amount_1_out,
+            deadline,
+            receiver,
+        } => {
+            // Prevent tokens from being sent with the WithdrawPosition message
+            if !info.funds.is_empty() {
+                return Err(ContractError::FundsNotAllowed);
+            }
+            withdraw_position(
+                deps,
+                _env,
+                info,
+                token_id,
+                min_amount_0_out,
+                min_amount_1_out,
+                deadline,
+                receiver,
+            )
         }
         ExecuteMsg::DexDeposit { .. } => {
             // Prevent tokens from being sent with the Deposit message
@@ -123,6 +360,110 @@ pub fn execute(
             }
             dex_withdrawal(deps, _env, info)
         }
+        ExecuteMsg::SetContractStatus { status, reason } => {
+            set_contract_status(deps, _env, info, status, reason)
+        }
+        ExecuteMsg::PurgeAndWithdraw {} => purge_and_withdraw(deps, _env, info),
+        ExecuteMsg::PurgeAndPause {} => purge_and_pause(deps, _env, info),
+        ExecuteMsg::RefreshFeeTiers {} => refresh_fee_tiers(deps, _env, info),
+        ExecuteMsg::SetIncentives {
+            reward_denom,
+            total_reward,
+            start_time,
+            end_time,
+        } => set_incentives(deps, _env, info, reward_denom, total_reward, start_time, end_time),
+        ExecuteMsg::ClaimIncentives {} => claim_incentives(deps, _env, info),
+        ExecuteMsg::CollectRewards {} => execute_collect_rewards(deps, _env, info),
+        ExecuteMsg::ClaimRewards {} => claim_rewards(deps, _env, info),
+        ExecuteMsg::SetRewardClaimContracts { contracts } => {
+            set_reward_claim_contracts(deps, _env, info, contracts)
+        }
+        ExecuteMsg::SweepDust {} => sweep_dust(deps, _env, info),
+        ExecuteMsg::SweepFees {} => sweep_fees(deps, _env, info),
+        ExecuteMsg::DistributeFees {} => distribute_fees(deps, _env, info),
+        ExecuteMsg::SetPerformanceFee {
+            fee_bps,
+            recipients,
+        } => set_performance_fee(deps, _env, info, fee_bps, recipients),
+        ExecuteMsg::HarvestPerformanceFee {} => harvest_performance_fee(deps, _env, info),
+        ExecuteMsg::SetManagementFee { fee_bps, collector } => {
+            set_management_fee(deps, _env, info, fee_bps, collector)
+        }
+        ExecuteMsg::Swap {
+            token_in,
+            amount_in,
+            min_out,
+            recipient,
+        } => swap(deps, _env, info, token_in, amount_in, min_out, recipient),
+        ExecuteMsg::RetryDeposit { id } => retry_deposit(deps, _env, info, id),
+        ExecuteMsg::Bond { amount } => bond(deps, _env, info, amount),
+        ExecuteMsg::Unbond { amount } => unbond(deps, _env, info, amount),
+        ExecuteMsg::QueueWithdrawal { shares } => queue_withdrawal(deps, _env, info, shares),
+        ExecuteMsg::Claim {} => claim(deps, _env, info),
+        ExecuteMsg::RegisterPair { pair_data } => register_pair(deps, _env, info, pair_data),
+        ExecuteMsg::DeregisterPair { denom_0, denom_1 } => {
+            deregister_pair(deps, _env, info, denom_0, denom_1)
+        }
+        ExecuteMsg::UpdateConfig { update } => update_config_timelocked(deps, _env, info, update),
+        ExecuteMsg::CommitConfig {} => commit_config(deps, _env, info),
+        ExecuteMsg::CancelConfig {} => cancel_config(deps, _env, info),
+        ExecuteMsg::RevertConfig {} => revert_config(deps, _env, info),
+        ExecuteMsg::FreezeConfig {} => freeze_config(deps, _env, info),
+        ExecuteMsg::SetOracleSources {
+            oracle_contracts,
+            min_sources,
+            max_oracle_deviation_bps,
+        } => set_oracle_sources(
+            deps,
+            _env,
+            info,
+            oracle_contracts,
+            min_sources,
+            max_oracle_deviation_bps,
+        ),
+        ExecuteMsg::SetRedemptionAdapter {
+            source,
+            lst_asset_denom,
+            min_redemption_rate,
+            max_redemption_rate,
+            max_redemption_rate_change_bps,
+            max_rate_age_seconds,
+        } => set_redemption_adapter(
+            deps,
+            _env,
+            info,
+            source,
+            lst_asset_denom,
+            min_redemption_rate,
+            max_redemption_rate,
+            max_redemption_rate_change_bps,
+            max_rate_age_seconds,
+        ),
+        ExecuteMsg::UpdateApyEma {
+            apy_contract,
+            instance,
+            time_span_hours,
+            alpha,
+            max_blocks_old,
+        } => execute_update_apy_ema(
+            deps,
+            _env,
+            info,
+            apy_contract,
+            instance,
+            time_span_hours,
+            alpha,
+            max_blocks_old,
+        ),
+        ExecuteMsg::SetSigners { signers, threshold } => {
+            set_signers(deps, _env, info, signers, threshold)
+        }
+        ExecuteMsg::ProposeConfigUpdate { update } => {
+            propose_config_update(deps, _env, info, update)
+        }
+        ExecuteMsg::ApproveConfigUpdate { id } => approve_config_update(deps, _env, info, id),
+        ExecuteMsg::ExecuteConfigUpdate { id } => execute_config_update(deps, _env, info, id),
+        ExecuteMsg::CancelProposal { id } => cancel_config_update(deps, _env, info, id),
     }
 }
 
@@ -136,6 +477,69 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> ContractResult<Binary> {
         QueryMsg::GetFormated {} => query_recent_valid_prices_formatted(deps, _env),
         QueryMsg::GetDeposits {} => q_dex_deposit(deps, _env),
         QueryMsg::GetConfig {} => query_config(deps, _env),
+        QueryMsg::GetShareValue { address } => query_share_value(deps, _env, address),
+        QueryMsg::GetContractStatus {} => query_contract_status(deps, _env),
+        QueryMsg::GetAccruedFees {} => query_accrued_fees(deps, _env),
+        QueryMsg::GetAllowedFeeTiers {} => query_allowed_fee_tiers(deps, _env),
+        QueryMsg::GetPendingIncentives { address } => {
+            query_pending_incentives(deps, _env, address)
+        }
+        QueryMsg::GetFailedDeposits {} => query_failed_deposits(deps, _env),
+        QueryMsg::GetTotalValue {} => query_total_value(deps, _env),
+        QueryMsg::GetBondedShares { address } => query_bonded_shares(deps, _env, address),
+        QueryMsg::GetWithdrawalQueue { address } => query_withdrawal_queue(deps, _env, address),
+        QueryMsg::GetSharePriceAtHeight { height } => {
+            query_share_price_at_height(deps, _env, height)
+        }
+        QueryMsg::GetTwapSharePrice {
+            start_height,
+            end_height,
+        } => query_twap_share_price(deps, _env, start_height, end_height),
+        QueryMsg::PreviewDeposit {
+            token0_amount,
+            token1_amount,
+        } => query_preview_deposit(deps, _env, token0_amount, token1_amount),
+        QueryMsg::WithPermit { permit, query } => query_with_permit(deps, _env, permit, query),
+        QueryMsg::OwnerOf { token_id } => query_owner_of(deps, _env, token_id),
+        QueryMsg::NftInfo { token_id } => query_nft_info(deps, _env, token_id),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => query_tokens(deps, _env, owner, start_after, limit),
+        QueryMsg::ListPairs { start_after, limit } => {
+            query_list_pairs(deps, _env, start_after, limit)
+        }
+        QueryMsg::GetPendingConfig {} => query_pending_config(deps, _env),
+        QueryMsg::ConfigHistory { start_after, limit } => {
+            query_config_history(deps, _env, start_after, limit)
+        }
+        QueryMsg::GetPendingRewards { address } => query_pending_rewards(deps, _env, address),
+        QueryMsg::GetDust {} => query_dust(deps, _env),
+        QueryMsg::GetRedemptionRate {} => query_redemption_rate(deps, _env),
+        QueryMsg::SimulateSwap {
+            token_in,
+            amount_in,
+        } => query_simulate_swap(deps, _env, token_in, amount_in),
+        QueryMsg::SimulateDeposit {} => query_simulate_deposit(deps, _env),
+        QueryMsg::GetNav {} => query_nav(deps, _env),
+        QueryMsg::GetCalculatedFeeTiers {
+            apy_contract,
+            instance,
+            time_span_hours,
+            base_fee,
+            oracle_skew,
+        } => query_calculated_fee_tiers(
+            deps,
+            _env,
+            apy_contract,
+            instance,
+            time_span_hours,
+            base_fee,
+            oracle_skew,
+        ),
+        QueryMsg::ListProposals {} => query_list_proposals(deps, _env),
+        QueryMsg::SimulateVaultUpdate {} => query_simulate_vault_update(deps, _env),
     }
 }
 
@@ -143,10 +547,23 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> ContractResult<Binary> {
 /// REPLY ///
 /////////////
 
+/// `handle_dex_withdrawal_reply`/`handle_dex_deposit_reply`/
+/// `handle_user_withdrawal_reply`/`handle_reward_claim_reply` are already
+/// this contract's fault-tolerant-DEX-message pattern: each leg of a vault's
+/// own withdraw-then-deposit cycle is dispatched as a tagged `SubMsg` (see
+/// `DEX_WITHDRAW_REPLY_ID`/`DEX_DEPOSIT_REPLY_ID`/etc.) so a failing leg is
+/// observed here instead of reverting the whole batch. There is no
+/// `config.vault_addresses` list or `execute_run_rebalancing` batch to make
+/// fault-tolerant in this tree - this contract drives exactly one vault's
+/// own position, so there is only ever one withdrawal/deposit pair per
+/// cycle, not a per-vault loop to keep resilient against one bad entry.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
         1 => handle_dex_withdrawal_reply(deps, env, msg.result),
+        DEX_DEPOSIT_REPLY_ID => handle_dex_deposit_reply(deps, msg.result),
+        DEX_USER_WITHDRAW_REPLY_ID => handle_user_withdrawal_reply(deps, env, msg.result),
+        REWARD_CLAIM_REPLY_ID => handle_reward_claim_reply(deps, env, msg.result),
         id => Err(ContractError::UnknownReplyId { id }),
     }
 }
@@ -38,6 +38,9 @@ pub enum ContractError {
     #[error( "Market {symbol}, {quote} is older than {max_blocks} blocks")]
     PriceTooOld { symbol: String, quote: String, max_blocks: u64},
 
+    #[error("Market {symbol}, {quote} price is older than {max_age_seconds}s")]
+    PriceTooStale { symbol: String, quote: String, max_age_seconds: u64 },
+
     #[error("input for {input} is invalid: {reason}")]
     MalformedInput { input: String, reason: String },
 
@@ -89,5 +92,267 @@ pub enum ContractError {
     #[error("Liquidity exists but cannot be retreived")]
     LiquidityNotFound,
 
+    #[error("Attempted deposit of invalid token amount")]
+    InvalidTokenAmount,
+
+    #[error("Cannot withdraw zero shares")]
+    ZeroBurnAmount,
+
+    #[error("Insufficient shares: available: {available}, required: {required}")]
+    InsufficientShares { available: Uint128, required: Uint128 },
+
+    #[error("Deposit value is too small to mint any vault shares")]
+    DepositBelowMinimumLiquidity,
+
+    #[error("Slippage exceeded: wanted at least {min}, got {actual}")]
+    SlippageExceeded { min: Uint128, actual: Uint128 },
+
+    #[error("Deposits are frozen")]
+    DepositsFrozen,
+
+    #[error("Contract is frozen")]
+    ContractFrozen,
+
+    #[error("Withdrawal limit exceeded for {denom}: requested {requested}, remaining {remaining} for this window")]
+    WithdrawalLimitExceeded {
+        denom: String,
+        requested: Uint128,
+        remaining: Uint128,
+    },
+
+    #[error("Simulated DEX deposit rejected: {reason}")]
+    DexSimulationRejected { reason: String },
+
+    #[error("Spot price deviates {deviation_bps} bps from EMA, max allowed {max_deviation_bps}")]
+    PriceDeviatesFromEma { deviation_bps: String, max_deviation_bps: u64 },
+
+    #[error("Failed to query target rate from {provider}: {reason}")]
+    TargetRateQueryFailed { provider: String, reason: String },
+
+    #[error("No fee splitter configured for this vault")]
+    NoFeeSplitterConfigured,
+
+    #[error("No fee collector configured for this vault")]
+    NoFeeCollectorConfigured,
+
+    #[error("Minting {minted} shares would bring total_shares to {new_total}, exceeding max_total_shares of {cap}")]
+    ExceedsShareSupplyCap { minted: Uint128, new_total: Uint128, cap: Uint128 },
+
+    #[error("Fee splitter recipient weights sum to {actual}, expected {expected}")]
+    InvalidFeeSplitterWeights { actual: u64, expected: u64 },
+
+    #[error("No EMA sample within the last {max_age_seconds} seconds")]
+    EmaStale { max_age_seconds: u64 },
+
+    #[error("Oracle confidence/price ratio {ratio_bps} bps exceeds max allowed {max_ratio_bps}")]
+    LowOracleConfidence { ratio_bps: String, max_ratio_bps: u64 },
+
+    #[error("Swap output {amount_out} is below the requested minimum {min_out}")]
+    SwapBelowMinOut { amount_out: Uint128, min_out: Uint128 },
+
+    #[error("No failed deposit recorded with id {id}")]
+    FailedDepositNotFound { id: u64 },
+
+    #[error("Cannot migrate stored contract {found}, expected {expected}")]
+    MigrateWrongContract { expected: String, found: String },
+
+    #[error("Cannot migrate from version {from} down to {to}")]
+    MigrateDowngrade { from: String, to: String },
+
+    #[error("No migration path from stored version {version} to {target}")]
+    MigrateUnknownVersion { version: String, target: String },
+
+    #[error("No staking_target is configured for this vault")]
+    StakingNotConfigured,
+
+    /// Raised by `apply_price_divergence_guard`/`check_price_divergence` (and
+    /// surfaced read-only via `validate_price_reliability`'s `PriceUnreliable`
+    /// wrapping). Keyed by `token_index: u8` (`0` or `1` into this vault's one
+    /// `pair_data`) rather than `symbol`/`quote: String`, since a vault only
+    /// ever tracks a single `CurrencyPair` - there's no second pair a string
+    /// key would need to disambiguate between. Functionally the same check
+    /// `PriceDeviatesFromEma` above already performs for the older
+    /// `price_0_to_1`-scalar guard, just keyed per-token for `Deposit`'s
+    /// per-token EMAs instead of the combined ratio `DexDeposit` guards.
+    #[error("token_{token_index} spot price deviates {deviation_bps} bps from its EMA, max allowed {max_deviation_bps}")]
+    PriceDivergence {
+        token_index: u8,
+        deviation_bps: String,
+        max_deviation_bps: u64,
+    },
+
+    #[error("total_shares would move faster than the configured change limiter allows")]
+    ChangeLimitExceeded {},
+
+    #[error("Per-address deposit cap exceeded for {address}: cumulative deposited value would be {deposited}, max allowed {cap}")]
+    PerAddressCapExceeded {
+        address: String,
+        deposited: String,
+        cap: String,
+    },
+
+    #[error("Invalid query permit: {reason}")]
+    InvalidPermit { reason: String },
+
+    #[error("Permit does not authorize {permission}")]
+    PermitNotAuthorized { permission: String },
+
+    #[error("Auto-balance swap's realized price deviates from oracle price beyond the configured dynamic_spread_cap")]
+    SwapSlippageExceeded {},
+
+    #[error("CW20 sender {sender} is not a configured cw20_token_0/cw20_token_1")]
+    UnrecognizedCw20Sender { sender: String },
+
+    #[error("Position NFT {token_id} does not exist")]
+    PositionNotFound { token_id: u64 },
+
+    #[error("Execution price at tick {tick_index} deviates {deviation_bps} bps from oracle price_0_to_1, max allowed {max_slippage_bps}")]
+    TickPriceDeviatesFromOracle {
+        tick_index: i64,
+        deviation_bps: String,
+        max_slippage_bps: u64,
+    },
+
+    #[error("Pair {denom_0}/{denom_1} is already registered")]
+    PairAlreadyRegistered { denom_0: String, denom_1: String },
+
+    #[error("Pair {denom_0}/{denom_1} is not registered")]
+    PairNotRegistered { denom_0: String, denom_1: String },
+
+    #[error("Sender does not own position NFT {token_id}")]
+    NotPositionOwner { token_id: u64 },
+
+    #[error("No share-price snapshot recorded at or before height {height}")]
+    NoSnapshotAvailable { height: u64 },
+
+    #[error("Arithmetic overflow while computing deposit amounts")]
+    Overflow,
+
+    #[error("Division by zero while computing deposit amounts")]
+    DivideByZero,
+
+    #[error("Fee tier percentages sum to {actual}, expected {expected}")]
+    InvalidFeeTierWeights { actual: u64, expected: u64 },
+
+    #[error("dynamic_spread_adjustment tick offset {tick_offset} exceeds dynamic_spread_cap {dynamic_spread_cap}")]
+    DynamicSpreadAdjustmentOutOfBounds { tick_offset: i64, dynamic_spread_cap: u64 },
+
+    #[error("A config update is already staged, effective at block {effective_block}; cancel it first")]
+    ConfigUpdateAlreadyPending { effective_block: u64 },
+
+    #[error("No config update is currently staged")]
+    NoPendingConfigUpdate,
+
+    #[error("Staged config update is not yet effective: current block {current_block}, effective at {effective_block}")]
+    TimelockNotElapsed { current_block: u64, effective_block: u64 },
+
+    #[error("No previously committed config to revert to")]
+    NoPreviousConfig,
+
+    #[error("Config has been permanently frozen by ExecuteMsg::FreezeConfig; no further updates may be staged")]
+    ConfigFrozen,
+
+    #[error("dex_deposit was run too recently: next eligible at {next_eligible_seconds}")]
+    DexDepositTooFrequent { next_eligible_seconds: u64 },
+
+    #[error("Only {available} of {required} required oracle_contracts sources returned a fresh price")]
+    InsufficientOracleSources { available: u64, required: u64 },
+
+    #[error("oracle_contracts source deviates {deviation_bps} bps from the median, max allowed {max_deviation_bps}")]
+    PriceDeviation { deviation_bps: String, max_deviation_bps: u64 },
+
+    #[error("Oracle unavailable and no usable cached price: {reason}")]
+    OracleUnavailable { reason: String },
+
+    #[error("Failed to query redemption rate from {provider}: {reason}")]
+    RedemptionRateQueryFailed { provider: String, reason: String },
+
+    #[error("Redemption rate {current} is not greater than the last observed value {previous}")]
+    RedemptionRateNotIncreasing { previous: String, current: String },
+
+    #[error("Redemption rate {rate} is out of bounds: {reason}")]
+    RedemptionRateOutOfBounds { rate: String, reason: String },
+
+    #[error("Cached redemption rate baseline is {elapsed}s old, max allowed {max_rate_age_seconds}")]
+    RedemptionRateStale { elapsed: u64, max_rate_age_seconds: u64 },
+
+    #[error("Price is not reliable enough to act on: {reason}")]
+    PriceUnreliable { reason: String },
+
+    #[error("Only {available} of {required} required price feeds survived validation for {symbol}/{quote}")]
+    InsufficientPriceSources {
+        symbol: String,
+        quote: String,
+        available: u64,
+        required: u64,
+    },
+
+    #[error("Price feed spread for {symbol}/{quote} is {deviation_bps} bps, max allowed {max_deviation_bps}")]
+    PriceFeedDeviation {
+        symbol: String,
+        quote: String,
+        deviation_bps: String,
+        max_deviation_bps: u64,
+    },
+
+    #[error("Target rate deviates from the oracle price by {deviation_bps} bps, max allowed {max_deviation_bps}")]
+    TargetRateDeviation { deviation_bps: String, max_deviation_bps: u64 },
+
+    #[error("DEX response contained no message data to decode")]
+    NoResponseData,
+
+    #[error("Failed to decode DEX response data")]
+    DecodingError,
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("A pro-rata DEX withdrawal is already settling for another withdrawer")]
+    WithdrawalInProgress,
+
+    #[error("No reward_claim_contracts are configured for this vault")]
+    NoRewardClaimContractsConfigured,
+
+    #[error("A reward collection is already in progress")]
+    RewardCollectionInProgress,
+
+    #[error("Price for {symbol}/{quote} jumped {deviation_bps} bps since the last accepted snapshot, max allowed {max_jump_bps} per block elapsed")]
+    PriceJump {
+        symbol: String,
+        quote: String,
+        deviation_bps: String,
+        max_jump_bps: u64,
+    },
+
+    #[error("Withdrawal deadline exceeded: wanted settlement by block {deadline}, now at {current_height}")]
+    WithdrawalDeadlineExceeded { deadline: u64, current_height: u64 },
+
+    #[error("Target rate drifted {drift_bps} bps since the last sample, max allowed {max_drift_bps} bps per second elapsed")]
+    TargetRateDrift { drift_bps: String, max_drift_bps: u64 },
+
+    #[error("Deposit would bring total vault value (idle + in-DEX) to {total_value}, exceeding deposit_cap of {cap}")]
+    DepositCapExceeded { total_value: String, cap: Uint128 },
+
+    #[error("Price implies tick magnitude {magnitude}, outside the Neutron DEX's representable tick range of +/-{max_tick}")]
+    TickOutOfRange { magnitude: i64, max_tick: i64 },
+
+    #[error("oracle_price_skew {value} is outside the configured +/-{max} tick range")]
+    SkewOutOfRange { value: i32, max: i32 },
+
+    #[error("dynamic_spread_cap {value} exceeds max of {max} bps")]
+    SpreadCapOutOfRange { value: u64, max: u64 },
+
+    #[error("APY observation for {instance} is older than {max_blocks} blocks")]
+    ApyTooOld { instance: String, max_blocks: u64 },
+
+    #[error("No open config update proposal with id {id}")]
+    ProposalNotFound { id: u64 },
+
+    #[error("Proposal {id} has {approvals} of {threshold} required signer approvals")]
+    ThresholdNotMet {
+        id: u64,
+        approvals: u32,
+        threshold: u32,
+    },
 }
 
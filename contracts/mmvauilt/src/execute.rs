@@ -1,20 +1,40 @@
 use crate::error::ContractError;
-use crate::msg::InstantiateMsg;
-use crate::state::{CONFIG, CRON_MODULE_ADDRESS, DEX_WITHDRAW_REPLY_ID};
+use crate::msg::{
+    ApyResponse, ApySourceQueryMsg, Cw20HookMsg, InstantiateMsg, RedemptionRateSourceInput,
+    RewardClaimExecuteMsg,
+};
+use crate::state::{
+    ApyEmaCache, Balances, CoinList, ConfigProposal, ContractStatus, DepositRecord, DustBalances,
+    DustRemainder, FailedDeposit, FeeSplitterConfig, IncentiveConfig, LastDeployedState,
+    PendingUserWithdrawal, PendingWithdrawal, PositionNft, RewardsStatus, UnbondEntry,
+    UnbondingEntry, WithdrawalSettlement, WithdrawalWindow,
+    APY_EMA, BONDED_SHARES, CONFIG, CRON_MODULE_ADDRESS, CURRENT_TOTAL_SUPPLY, DEPLOYED_PRINCIPAL,
+    DEPOSITS, DEX_DEPOSIT_REPLY_ID,
+    DEX_USER_WITHDRAW_REPLY_ID, DEX_WITHDRAW_REPLY_ID, DISTRIBUTED_REWARDS, DUST, DUST_REMAINDER,
+    EXTERNAL_REWARD_PER_SHARE, FAILED_DEPOSITS, FAILED_DEPOSIT_SEQ, LAST_DEPLOYED_STATE,
+    LAST_DEX_DEPOSIT, NEXT_POSITION_ID, PENDING_DEX_DEPOSIT, PENDING_DEX_WITHDRAWAL,
+    PENDING_REWARD_CLAIM_SNAPSHOT, PENDING_USER_WITHDRAWAL, POSITIONS, POSITIONS_BY_OWNER,
+    PROPOSALS, PROPOSAL_SEQ, REWARDS_STATUS, REWARD_CLAIM_REPLY_ID, SHARES, UNBONDING_SHARES,
+    USER_EXTERNAL_REWARD_DEBT, WITHDRAWAL_QUEUE, WITHDRAWAL_QUEUE_SEQ, WITHDRAWAL_WINDOW,
+};
 use crate::utils::*;
 use cosmwasm_std::{
-    Addr, CosmosMsg, DepsMut, Env, MessageInfo, Response, SubMsg, SubMsgResult, Uint128, Coin, BankMsg,
+    from_json, to_json_binary, Addr, CosmosMsg, Decimal, DepsMut, Empty, Env, MessageInfo, Order,
+    Response, SubMsg, SubMsgResult, Uint128, WasmMsg, Coin, BankMsg,
 };
-use neutron_std::types::neutron::dex::{DexQuerier, MsgWithdrawal, QueryAllUserDepositsResponse};
+use cw20::Cw20ReceiveMsg;
+use neutron_std::types::neutron::dex::{DepositOptions, MsgDeposit};
+use neutron_std::types::neutron::util::precdec::PrecDec;
 
-pub fn deposit(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-    let messages: Vec<CosmosMsg> = vec![];
-    // Load the contract configuration from storage
-    let mut config = CONFIG.load(deps.storage)?;
-    //if calles is not the owner error
-    if info.sender != config.owner {
-        return Err(ContractError::Unauthorized {});
-    }
+pub fn deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    min_shares_out: Option<Uint128>,
+    beneficiary: Option<String>,
+    auto_balance: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
     // Extract the sent funds from the transaction info
     let sent_funds = info.funds;
 
@@ -25,90 +45,876 @@ pub fn deposit(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, C
     let mut token0_deposited = Uint128::zero();
     let mut token1_deposited = Uint128::zero();
     // Iterate through the sent funds if the denoms match the expected vault denom, and are greater than zero we add them to the config balances.
+    // A leg with `cw20_token_0`/`cw20_token_1` configured is only ever read
+    // via `Cw20Contract::balance` (see `query_contract_balance`), so native
+    // coins of that leg's denom sent here would mint shares against value
+    // that's invisible to every future NAV/withdrawal computation; that leg
+    // must come in through `receive_cw20` instead.
     for coin in sent_funds.iter() {
         if coin.denom == config.balances.token_0.denom {
+            if config.cw20_token_0.is_some() {
+                return Err(ContractError::InvalidToken);
+            }
             if coin.amount == Uint128::zero() {
                 return Err(ContractError::InvalidTokenAmount);
             }
             token0_deposited += coin.amount;
-            config.balances.token_0.amount += coin.amount;
         } else if coin.denom == config.balances.token_1.denom {
+            if config.cw20_token_1.is_some() {
+                return Err(ContractError::InvalidToken);
+            }
             if coin.amount == Uint128::zero() {
                 return Err(ContractError::InvalidTokenAmount);
             }
             token1_deposited += coin.amount;
-            config.balances.token_1.amount += coin.amount;
         } else {
             // Return an error if an unsupported token is sent
             return Err(ContractError::InvalidToken);
         }
     }
 
+    deposit_internal(
+        deps,
+        env,
+        info.sender,
+        beneficiary,
+        min_shares_out,
+        auto_balance,
+        token0_deposited,
+        token1_deposited,
+    )
+}
+
+/// Handles a CW20 `Send`/`SendFrom` to this contract carrying a
+/// [`Cw20HookMsg`]. `info.sender` here is the CW20 token contract itself
+/// (checked against `Config::cw20_token_0`/`cw20_token_1`), while
+/// `cw20_msg.sender` is the wallet that actually invoked `Send` on it and is
+/// therefore the real depositor/beneficiary-default for this deposit.
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let is_token_0 = config.cw20_token_0.as_ref() == Some(&info.sender);
+    let is_token_1 = config.cw20_token_1.as_ref() == Some(&info.sender);
+    if !is_token_0 && !is_token_1 {
+        return Err(ContractError::UnrecognizedCw20Sender {
+            sender: info.sender.to_string(),
+        });
+    }
+    let depositor = deps.api.addr_validate(&cw20_msg.sender)?;
+    let Cw20HookMsg::Deposit {
+        min_shares_out,
+        beneficiary,
+        auto_balance,
+    } = from_json(&cw20_msg.msg)?;
+
+    let (token0_deposited, token1_deposited) = if is_token_0 {
+        (cw20_msg.amount, Uint128::zero())
+    } else {
+        (Uint128::zero(), cw20_msg.amount)
+    };
+    deposit_internal(
+        deps,
+        env,
+        depositor,
+        beneficiary,
+        min_shares_out,
+        auto_balance,
+        token0_deposited,
+        token1_deposited,
+    )
+}
+
+/// Shared by `deposit`/`receive_cw20` - there used to be a third
+/// `already_in_bank_balance` parameter distinguishing the two transports,
+/// but `query_contract_balance` (and so `get_deposited_token_amounts`) reads
+/// a CW20-configured leg's balance from that CW20 contract directly, which
+/// `cw20-base` already credits before dispatching the `Receive` hook this
+/// runs from - exactly like the bank module crediting a native send before
+/// `deposit` executes. With both transports reflected in the balance query
+/// the same way, there's nothing left for a transport-specific branch to do,
+/// so it's gone; `deposit`/`receive_cw20` now call this identically. (No
+/// handler-level regression test accompanies this - this backlog's tests
+/// only cover pure functions, not handlers taking `Deps`/`DepsMut`/`Env`; see
+/// `validate_price_recent`'s doc comment for the precedent. The fix itself
+/// is what removes the only place CW20 vs. native could diverge.)
+#[allow(clippy::too_many_arguments)]
+fn deposit_internal(
+    mut deps: DepsMut,
+    env: Env,
+    depositor: Addr,
+    beneficiary: Option<String>,
+    min_shares_out: Option<Uint128>,
+    auto_balance: bool,
+    token0_deposited: Uint128,
+    token1_deposited: Uint128,
+) -> Result<Response, ContractError> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    // Load the contract configuration from storage
+    let mut config = CONFIG.load(deps.storage)?;
+    require_deposits_allowed(&config.status)?;
+
+    let beneficiary = beneficiary
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .unwrap_or_else(|| depositor.clone());
+
+    // Accrue incentives up to now and pay out whatever the depositor had
+    // already earned on their pre-deposit share balance before that balance
+    // changes.
+    let reward_per_share = accrue_rewards(&mut deps, env.block.time.seconds(), &config)?;
+    if let Some(claim_msg) =
+        create_incentive_claim_message(&mut deps, &config, &depositor, reward_per_share)?
+    {
+        messages.push(claim_msg);
+    }
+
+    // Whether this deposit arrived as a native bank send or a CW20 `Send`,
+    // the transfer has already landed in this contract's balance (bank
+    // module or CW20 contract storage, respectively - see
+    // `query_contract_balance`) by the time either `deposit`/`receive_cw20`
+    // calls in here, so `get_deposited_token_amounts` already includes it.
+    // The vault's pre-deposit value is derived further down by subtracting
+    // `deposit_value_oracle` back out of this total rather than by excluding
+    // the deposit from the query itself.
+    let mut prices = get_prices_with_fallback(&mut deps, &env)?;
+    apply_price_divergence_guard(&deps, &env, &config, &mut prices)?;
+    apply_price_circuit_breaker(&deps, &env, &config, &prices)?;
+    let (total_amount_0, total_amount_1) =
+        get_deposited_token_amounts(env.clone(), &deps, config.clone())?;
+
+    // Captured pre-`auto_balance` so the StableSwap-invariant mint path below
+    // can back the vault's pre-deposit reserves out of `total_amount_0`/
+    // `total_amount_1` (which are computed from this deposit's pre-swap
+    // amounts) without the swap's re-denomination of the deposit throwing
+    // off the subtraction.
+    let (pre_swap_token0_deposited, pre_swap_token1_deposited) = (token0_deposited, token1_deposited);
+
+    let (swap_msg, swapped, token0_deposited, token1_deposited) = if auto_balance {
+        auto_balance_deposit(&deps, &env, &config, &prices, token0_deposited, token1_deposited)?
+    } else {
+        (None, None, token0_deposited, token1_deposited)
+    };
+    if let Some(swap_msg) = swap_msg {
+        messages.push(swap_msg);
+    }
+
+    let deposit_value_oracle = total_vault_value(token0_deposited, token1_deposited, &prices)?;
+    let existing_record = DEPOSITS.may_load(deps.storage, beneficiary.clone())?;
+    if let Some(cap) = config.per_address_cap {
+        let prior_value = existing_record
+            .as_ref()
+            .map(|record| record.deposited_value)
+            .unwrap_or_else(PrecDec::zero);
+        let new_value = prior_value + deposit_value_oracle;
+        if new_value > cap {
+            return Err(ContractError::PerAddressCapExceeded {
+                address: beneficiary.to_string(),
+                deposited: new_value.to_string(),
+                cap: cap.to_string(),
+            });
+        }
+    }
+    let total_value_after = total_vault_value(total_amount_0, total_amount_1, &prices)?;
+    let total_value_before = total_value_after - deposit_value_oracle;
+
+    // `config.deposit_cap` is a TVL ceiling, not a per-deposit one: idle
+    // balances alone understate the vault once liquidity is deployed, so
+    // whatever's sitting in active DEX positions counts toward it too, the
+    // same combined-reserves convention `query_total_value` reports. `0`
+    // disables the check, the default (and this field's pre-existing value
+    // in every fixture/migration that never read it).
+    if !config.deposit_cap.is_zero() {
+        let (in_dex_0, in_dex_1) = get_in_dex_token_amounts(deps.as_ref(), env.clone(), &config)?;
+        let tvl_after = total_value_after + total_vault_value(in_dex_0, in_dex_1, &prices)?;
+        let cap_value = PrecDec::from_ratio(config.deposit_cap, 1u128);
+        if tvl_after > cap_value {
+            return Err(ContractError::DepositCapExceeded {
+                total_value: tvl_after.to_string(),
+                cap: config.deposit_cap,
+            });
+        }
+    }
+
+    // Existing LPs' pre-deposit stake is always valued at the oracle price;
+    // only the depositor's own new shares are priced off the more
+    // conservative book-aware valuation, when enabled.
+    let book_prices =
+        book_aware_prices(&deps, &env, &config, prices, token0_deposited, token1_deposited)?;
+    let deposit_value = total_vault_value(token0_deposited, token1_deposited, &book_prices)?;
+
+    // A configured `stableswap_amplification` mints against the 2-asset
+    // invariant `D` instead of the straight oracle-proportional value, so a
+    // deposit into an imbalanced (but tightly correlated) pool is priced
+    // fairly rather than at its raw oracle value. Only minting uses this
+    // mode -- `withdraw` stays proportional to `total_shares` regardless, so
+    // its existing behavior/tests are unaffected.
+    let minted = if config.stableswap_amplification > 0 {
+        crate::utils::stableswap_shares_to_mint(
+            config.stableswap_amplification,
+            total_amount_0 - pre_swap_token0_deposited,
+            total_amount_1 - pre_swap_token1_deposited,
+            total_amount_0,
+            total_amount_1,
+            config.total_shares,
+        )?
+    } else {
+        shares_to_mint(deposit_value, config.total_shares, total_value_before)?
+    };
+    if minted.is_zero() {
+        return Err(ContractError::DepositBelowMinimumLiquidity);
+    }
+    if let Some(min_shares_out) = min_shares_out {
+        if minted < min_shares_out {
+            return Err(ContractError::SlippageExceeded {
+                min: min_shares_out,
+                actual: minted,
+            });
+        }
+    }
+
+    if config.total_shares.is_zero() {
+        // Genuinely burn `MINIMUM_LIQUIDITY`: count it in `total_shares` so
+        // it permanently dilutes every future share, but credit it to no
+        // `SHARES` entry at all. Crediting it to `config.owner` would leave
+        // it sitting in the same map `withdraw` reads from, letting the
+        // owner redeem the "locked" floor on demand and re-expose the next
+        // first-depositor to the inflation/donation attack this exists to
+        // prevent.
+        config.total_shares = crate::state::MINIMUM_LIQUIDITY;
+    }
+
+    let beneficiary_shares = SHARES
+        .may_load(deps.storage, beneficiary.clone())?
+        .unwrap_or_default()
+        + minted;
+    SHARES.save(deps.storage, beneficiary.clone(), &beneficiary_shares)?;
+    mint_shares_checked(&mut config, minted)?;
+
+    // Mint a position NFT alongside the fungible SHARES entry above, so this
+    // tranche can be transferred/used as collateral without moving funds.
+    let token_id = NEXT_POSITION_ID.may_load(deps.storage)?.unwrap_or_default() + 1;
+    NEXT_POSITION_ID.save(deps.storage, &token_id)?;
+    POSITIONS.save(
+        deps.storage,
+        token_id,
+        &PositionNft {
+            owner: beneficiary.clone(),
+            shares: minted,
+        },
+    )?;
+    POSITIONS_BY_OWNER.save(deps.storage, (beneficiary.clone(), token_id), &Empty {})?;
+
+    config.balances.token_0.amount += token0_deposited;
+    config.balances.token_1.amount += token1_deposited;
+
+    apply_change_limiter(&deps, &env, &config, config.total_shares)?;
+
+    let prior_value = existing_record
+        .as_ref()
+        .map(|record| record.deposited_value)
+        .unwrap_or_else(PrecDec::zero);
+    DEPOSITS.save(
+        deps.storage,
+        beneficiary.clone(),
+        &DepositRecord {
+            deposited_value: prior_value + deposit_value_oracle,
+            shares_minted: existing_record.map_or(minted, |record| record.shares_minted + minted),
+        },
+    )?;
+
     // Save the updated configuration with new balances back to the contract's storage
     CONFIG.save(deps.storage, &config)?;
+    record_snapshot(&mut deps, &env, &config)?;
 
     // Return a success response with updated balances
-    Ok(Response::new()
+    let mut response = Response::new()
         .add_messages(messages)
         .add_attribute("action", "deposit")
-        .add_attribute("from", info.sender.to_string())
+        .add_attribute("from", depositor.to_string())
+        .add_attribute("beneficiary", beneficiary.to_string())
+        .add_attribute("shares_minted", minted.to_string())
+        .add_attribute("total_shares", config.total_shares.to_string())
         .add_attribute("token_0_amount", config.balances.token_0.amount.to_string())
-        .add_attribute("token_1_amount", config.balances.token_1.amount.to_string()))
+        .add_attribute("token_1_amount", config.balances.token_1.amount.to_string())
+        .add_attribute("effective_token_0", token0_deposited.to_string())
+        .add_attribute("effective_token_1", token1_deposited.to_string())
+        .add_attribute("token_id", token_id.to_string());
+    if let Some((swapped_denom, swapped_amount)) = swapped {
+        response = response
+            .add_attribute("swapped_denom", swapped_denom)
+            .add_attribute("swapped_amount", swapped_amount.to_string());
+    }
+    Ok(response)
 }
 
-pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+/// Already does genuine proportional partial redemption: `amount_0`/`amount_1`
+/// below are `balances[n].amount * amount / config.total_shares`, a
+/// checked-multiply-then-divide, not a full-balance payout, with the
+/// `accrue_dust`/`DUST`/`DUST_REMAINDER` bookkeeping a few lines down
+/// guaranteeing the floor-rounding loss stays in the vault (never overpays)
+/// and only gets folded into the payout once `amount == config.total_shares`
+/// collapses this to the zero-remainder full-withdrawal case. There is no
+/// `handle_withdrawal_reply`/`test_withdrawal_reply_handler_full_withdrawal`
+/// in this tree to add re-deposit-the-remainder logic to; the DEX-deployed
+/// remainder after a partial exit is simply left in its existing positions
+/// rather than withdrawn and redeposited (see `create_pro_rata_dex_withdrawal_messages`).
+pub fn withdraw(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    min_amount_0_out: Option<Uint128>,
+    min_amount_1_out: Option<Uint128>,
+    deadline: Option<u64>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
     // Load the contract configuration to access the owner address and balances
     let mut config = CONFIG.load(deps.storage)?;
+    require_not_frozen(&config.status)?;
     let mut messages: Vec<CosmosMsg> = vec![];
 
-    if info.sender != config.owner {
-        return Err(ContractError::Unauthorized {});
+    // Same ungated `recipient` convention as `swap`: the sender only ever
+    // redirects the payout of shares they themselves are burning, so there's
+    // no privilege here for an owner-gate to guard against.
+    let receiver_addr = receiver
+        .map(|r| deps.api.addr_validate(&r))
+        .transpose()?
+        .unwrap_or_else(|| info.sender.clone());
+
+    if amount.is_zero() {
+        return Err(ContractError::ZeroBurnAmount);
+    }
+
+    if let Some(deadline) = deadline {
+        if env.block.height > deadline {
+            return Err(ContractError::WithdrawalDeadlineExceeded {
+                deadline,
+                current_height: env.block.height,
+            });
+        }
+    }
+
+    let sender_shares = SHARES
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+
+    // Let shares whose unbonding period has elapsed count as withdrawable again.
+    purge_matured_unbonding(&mut deps, &info.sender, env.block.time.seconds())?;
+    let locked = locked_shares(deps.as_ref(), &info.sender, env.block.time.seconds())?;
+    let available = sender_shares.saturating_sub(locked);
+    if available < amount {
+        return Err(ContractError::InsufficientShares {
+            available,
+            required: amount,
+        });
+    }
+
+    // Accrue incentives up to now and pay out the sender's earned balance on
+    // their pre-withdrawal share count before it shrinks.
+    let reward_per_share = accrue_rewards(&mut deps, env.block.time.seconds(), &config)?;
+    if let Some(claim_msg) =
+        create_incentive_claim_message(&mut deps, &config, &info.sender, reward_per_share)?
+    {
+        messages.push(claim_msg);
     }
 
     // Query current contract balances
-    let balances = query_contract_balance(&deps, env.clone(), config.pair_data.clone())?;
-    
-    // Create bank send messages for both tokens
-    if balances[0].amount > Uint128::zero() {
-        messages.push(
-            BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: balances[0].denom.clone(),
-                    amount: balances[0].amount,
-                }],
+    let balances = query_contract_balance(&deps, env.clone(), &config)?;
+
+    // Pro-rata slice of the idle balances for the shares being burned.
+    let mut amount_0 = balances[0].amount.multiply_ratio(amount, config.total_shares);
+    let mut amount_1 = balances[1].amount.multiply_ratio(amount, config.total_shares);
+
+    // Track this call's floor-rounding loss (see `DustRemainder`/`DustBalances`
+    // docs); once the vault's very last share is burned there's nobody left to
+    // pro-rate any further rounding benefit to, so any dust accrued so far is
+    // folded straight into this payout instead of being stranded.
+    let mut dust_remainder = DUST_REMAINDER.may_load(deps.storage)?.unwrap_or_default();
+    let mut dust = DUST.may_load(deps.storage)?.unwrap_or_default();
+    (dust_remainder.token_0, dust.token_0) = accrue_dust(
+        balances[0].amount,
+        amount,
+        config.total_shares,
+        amount_0,
+        dust_remainder.token_0,
+        dust.token_0,
+    )?;
+    (dust_remainder.token_1, dust.token_1) = accrue_dust(
+        balances[1].amount,
+        amount,
+        config.total_shares,
+        amount_1,
+        dust_remainder.token_1,
+        dust.token_1,
+    )?;
+    if amount == config.total_shares {
+        amount_0 += dust.token_0;
+        amount_1 += dust.token_1;
+        dust = DustBalances::default();
+        dust_remainder = DustRemainder::default();
+    }
+    DUST_REMAINDER.save(deps.storage, &dust_remainder)?;
+    DUST.save(deps.storage, &dust)?;
+
+    // Pro-rata slice of whatever's still deployed in active DEX positions, so
+    // a withdrawal isn't capped by however little happens to be idle.
+    // `handle_user_withdrawal_reply` settles the slippage check and the
+    // combined payout once every `MsgWithdrawal` reply is in; resting limit
+    // orders from the market-making ladder aren't covered, see
+    // `create_pro_rata_dex_withdrawal_messages`.
+    // `create_pro_rata_dex_withdrawal_messages` is what makes this
+    // non-empty whenever liquidity is deployed: it queries every open
+    // `MsgDeposit` position via `user_deposits_all`, computes each one's
+    // `withdrawn_shares / total_shares` fraction, and emits one
+    // `MsgWithdrawal` per tick (see its own docs for the resting-limit-order
+    // caveat). Below, `messages`/the bank sends only get built once this
+    // batch is empty (nothing deployed, so the idle slice is the whole
+    // payout) or, when it
+    // isn't, once `handle_user_withdrawal_reply` has every reply's real
+    // settled amount in hand via `PENDING_USER_WITHDRAWAL` - so the
+    // position-withdrawal messages always precede the burn/transfer by
+    // construction, not by simulating the inflow ahead of time.
+    if PENDING_USER_WITHDRAWAL.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::WithdrawalInProgress);
+    }
+    let dex_withdraw_messages =
+        create_pro_rata_dex_withdrawal_messages(&deps, &env, &config, amount, config.total_shares)?;
+
+    // Roll the withdrawal window over and enforce the per-window caps.
+    let mut window = WITHDRAWAL_WINDOW.load(deps.storage)?;
+    if env.block.height >= window.window_start + config.max_blocks_old {
+        window = WithdrawalWindow {
+            window_start: env.block.height,
+            ..Default::default()
+        };
+    }
+    if let Some(limit) = config.withdrawal_limit_token_0 {
+        let remaining = limit.saturating_sub(window.withdrawn_0);
+        if amount_0 > remaining {
+            return Err(ContractError::WithdrawalLimitExceeded {
+                denom: balances[0].denom.clone(),
+                requested: amount_0,
+                remaining,
+            });
+        }
+    }
+    if let Some(limit) = config.withdrawal_limit_token_1 {
+        let remaining = limit.saturating_sub(window.withdrawn_1);
+        if amount_1 > remaining {
+            return Err(ContractError::WithdrawalLimitExceeded {
+                denom: balances[1].denom.clone(),
+                requested: amount_1,
+                remaining,
+            });
+        }
+    }
+    window.withdrawn_0 += amount_0;
+    window.withdrawn_1 += amount_1;
+    WITHDRAWAL_WINDOW.save(deps.storage, &window)?;
+
+    if dex_withdraw_messages.is_empty() {
+        // Nothing deployed to wait on: the idle slice is the whole payout, so
+        // the slippage check and bank sends can both happen now.
+        if let Some(min_amount_0_out) = min_amount_0_out {
+            if amount_0 < min_amount_0_out {
+                return Err(ContractError::SlippageExceeded { min: min_amount_0_out, actual: amount_0 });
             }
-            .into(),
-        );
-    }
-    if balances[1].amount > Uint128::zero() {
-        messages.push(
-            BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: balances[1].denom.clone(),
-                    amount: balances[1].amount,
-                }],
+        }
+        if let Some(min_amount_1_out) = min_amount_1_out {
+            if amount_1 < min_amount_1_out {
+                return Err(ContractError::SlippageExceeded { min: min_amount_1_out, actual: amount_1 });
             }
-            .into(),
-        );
+        }
+        if amount_0 > Uint128::zero() {
+            messages.push(payout_message(
+                &config.cw20_token_0,
+                &balances[0].denom,
+                &receiver_addr,
+                amount_0,
+            )?);
+        }
+        if amount_1 > Uint128::zero() {
+            messages.push(payout_message(
+                &config.cw20_token_1,
+                &balances[1].denom,
+                &receiver_addr,
+                amount_1,
+            )?);
+        }
+    } else {
+        // The rest is still settling asynchronously, so the idle slice rides
+        // along in `PENDING_USER_WITHDRAWAL` and goes out together with the
+        // DEX slice once `handle_user_withdrawal_reply` has it all.
+        PENDING_USER_WITHDRAWAL.save(
+            deps.storage,
+            &PendingUserWithdrawal {
+                recipient: receiver_addr.clone(),
+                remaining: dex_withdraw_messages.len() as u64,
+                received_0: Uint128::zero(),
+                received_1: Uint128::zero(),
+                idle_amount_0: amount_0,
+                idle_amount_1: amount_1,
+                denom_0: balances[0].denom.clone(),
+                denom_1: balances[1].denom.clone(),
+                min_amount_0_out,
+                min_amount_1_out,
+                deadline,
+                settlement: WithdrawalSettlement::Immediate,
+            },
+        )?;
+    }
+
+    let remaining_shares = sender_shares - amount;
+    if remaining_shares.is_zero() {
+        SHARES.remove(deps.storage, info.sender.clone());
+    } else {
+        SHARES.save(deps.storage, info.sender.clone(), &remaining_shares)?;
+    }
+    config.total_shares -= amount;
+    config.balances.token_0.amount -= amount_0;
+    config.balances.token_1.amount -= amount_1;
+
+    // The last share of a retiring vault just burned: clear its DEX
+    // bookkeeping and close it for good. Idempotent by construction -
+    // `total_shares` can't go negative, so this only ever fires once, and a
+    // replayed call would already be rejected by `require_not_frozen` above.
+    if config.status == ContractStatus::WindDown && config.total_shares.is_zero() {
+        DEPLOYED_PRINCIPAL.remove(deps.storage);
+        config.market_making = None;
+        config.status = ContractStatus::Frozen;
     }
 
-    // Update config balances to zero
-    config.balances.token_0.amount = Uint128::zero();
-    config.balances.token_1.amount = Uint128::zero();
+    apply_change_limiter(&deps, &env, &config, config.total_shares)?;
+
     CONFIG.save(deps.storage, &config)?;
+    record_snapshot(&mut deps, &env, &config)?;
 
     // Add the message to the response and return
     Ok(Response::new()
         .add_messages(messages)
+        .add_submessages(dex_withdraw_messages)
         .add_attribute("action", "withdrawal")
-        .add_attribute("token_0_amount", balances[0].amount.to_string())
-        .add_attribute("token_1_amount", balances[1].amount.to_string()))
+        .add_attribute("shares_burned", amount.to_string())
+        .add_attribute("token_0_amount", amount_0.to_string())
+        .add_attribute("token_1_amount", amount_1.to_string())
+        .add_attribute("receiver", receiver_addr.to_string()))
+}
+
+/// Burns the position NFT `token_id`, owned by the sender, then withdraws the
+/// shares it was holding exactly like `withdraw` would. The NFT is released
+/// before the transfer so `locked_shares` no longer counts it against the
+/// sender, making its `shares` withdrawable through this one call.
+pub fn withdraw_position(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: u64,
+    min_amount_0_out: Option<Uint128>,
+    min_amount_1_out: Option<Uint128>,
+    deadline: Option<u64>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    let position = POSITIONS
+        .may_load(deps.storage, token_id)?
+        .ok_or(ContractError::PositionNotFound { token_id })?;
+    if position.owner != info.sender {
+        return Err(ContractError::NotPositionOwner { token_id });
+    }
+    POSITIONS.remove(deps.storage, token_id);
+    POSITIONS_BY_OWNER.remove(deps.storage, (position.owner, token_id));
+
+    let response = withdraw(
+        deps,
+        env,
+        info,
+        position.shares,
+        min_amount_0_out,
+        min_amount_1_out,
+        deadline,
+        receiver,
+    )?;
+    Ok(response.add_attribute("token_id", token_id.to_string()))
+}
+
+/// Bonds `amount` of the sender's vault shares to `Config::staking_target`.
+/// Bonded shares are still counted in `SHARES` and keep earning the vault's
+/// own yield, but `withdraw` refuses to burn them until `unbond` then
+/// `Config::unbonding_period_seconds` elapses, the superfluid-LP pattern of
+/// composing with staking without exiting liquidity first.
+pub fn bond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    require_not_frozen(&config.status)?;
+    if config.staking_target.is_none() {
+        return Err(ContractError::StakingNotConfigured);
+    }
+    if amount.is_zero() {
+        return Err(ContractError::ZeroBurnAmount);
+    }
+
+    let sender_shares = SHARES
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    let locked = locked_shares(deps.as_ref(), &info.sender, env.block.time.seconds())?;
+    let available = sender_shares.saturating_sub(locked);
+    if available < amount {
+        return Err(ContractError::InsufficientShares {
+            available,
+            required: amount,
+        });
+    }
+
+    let bonded = BONDED_SHARES
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    BONDED_SHARES.save(deps.storage, info.sender.clone(), &(bonded + amount))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "bond")
+        .add_attribute("amount", amount.to_string()))
+}
+
+/// Starts unbonding `amount` of the sender's bonded shares: moves them out of
+/// `BONDED_SHARES` and into a `UNBONDING_SHARES` entry that still locks them
+/// against `withdraw` until `Config::unbonding_period_seconds` has elapsed.
+pub fn unbond(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroBurnAmount);
+    }
+
+    let bonded = BONDED_SHARES
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    if bonded < amount {
+        return Err(ContractError::InsufficientShares {
+            available: bonded,
+            required: amount,
+        });
+    }
+    let remaining_bonded = bonded - amount;
+    if remaining_bonded.is_zero() {
+        BONDED_SHARES.remove(deps.storage, info.sender.clone());
+    } else {
+        BONDED_SHARES.save(deps.storage, info.sender.clone(), &remaining_bonded)?;
+    }
+
+    purge_matured_unbonding(&mut deps, &info.sender, env.block.time.seconds())?;
+    let mut unbonding = UNBONDING_SHARES
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    let release_at = env.block.time.seconds() + config.unbonding_period_seconds;
+    unbonding.push(UnbondingEntry { amount, release_at });
+    UNBONDING_SHARES.save(deps.storage, info.sender.clone(), &unbonding)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "unbond")
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("release_at", release_at.to_string()))
+}
+
+/// Burns `shares` now and snapshots their pro-rata redemption value into a
+/// `WITHDRAWAL_QUEUE` entry instead of sending it immediately, so volatile
+/// oracle conditions between now and `Config::withdrawal_queue_period_seconds`
+/// later can't be gamed by instant-exit arbitrage. Subject to the same
+/// eligibility and change-limiter checks `withdraw` uses, since it burns
+/// shares and releases balances the same way; the per-window withdrawal caps
+/// (`WITHDRAWAL_WINDOW`) are not checked or updated here. Also pulls the
+/// same pro-rata slice of whatever's deployed on the DEX that `withdraw`
+/// does (see `create_pro_rata_dex_withdrawal_messages`): the idle balance
+/// alone would otherwise silently forfeit this share of deployed liquidity
+/// to the remaining LPs, which would make the "snapshot the redemption
+/// value at request time" this entry point promises a lie whenever a
+/// market-making position is open. When a slice is in flight, the queue
+/// entry itself is only written once `handle_user_withdrawal_reply` has the
+/// settled DEX amounts in hand, via the same `PENDING_USER_WITHDRAWAL`
+/// bookkeeping `withdraw` uses (see `WithdrawalSettlement::Queued`).
+pub fn queue_withdrawal(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    shares: Uint128,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    require_not_frozen(&config.status)?;
+
+    if shares.is_zero() {
+        return Err(ContractError::ZeroBurnAmount);
+    }
+
+    let sender_shares = SHARES
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    purge_matured_unbonding(&mut deps, &info.sender, env.block.time.seconds())?;
+    let locked = locked_shares(deps.as_ref(), &info.sender, env.block.time.seconds())?;
+    let available = sender_shares.saturating_sub(locked);
+    if available < shares {
+        return Err(ContractError::InsufficientShares {
+            available,
+            required: shares,
+        });
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let reward_per_share = accrue_rewards(&mut deps, env.block.time.seconds(), &config)?;
+    if let Some(claim_msg) =
+        create_incentive_claim_message(&mut deps, &config, &info.sender, reward_per_share)?
+    {
+        messages.push(claim_msg);
+    }
+
+    let balances = query_contract_balance(&deps, env.clone(), &config)?;
+    let amount_0 = balances[0].amount.multiply_ratio(shares, config.total_shares);
+    let amount_1 = balances[1].amount.multiply_ratio(shares, config.total_shares);
+
+    if PENDING_USER_WITHDRAWAL.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::WithdrawalInProgress);
+    }
+    let dex_withdraw_messages =
+        create_pro_rata_dex_withdrawal_messages(&deps, &env, &config, shares, config.total_shares)?;
+
+    let seq = WITHDRAWAL_QUEUE_SEQ
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    WITHDRAWAL_QUEUE_SEQ.save(deps.storage, &seq)?;
+    let release_at = env.block.time.seconds() + config.withdrawal_queue_period_seconds;
+
+    if dex_withdraw_messages.is_empty() {
+        // Nothing deployed to wait on: the idle slice is the whole redemption
+        // value, so the queue entry can be written now.
+        WITHDRAWAL_QUEUE.save(
+            deps.storage,
+            (info.sender.clone(), seq),
+            &UnbondEntry {
+                token_0: Coin {
+                    denom: balances[0].denom.clone(),
+                    amount: amount_0,
+                },
+                token_1: Coin {
+                    denom: balances[1].denom.clone(),
+                    amount: amount_1,
+                },
+                release_at,
+            },
+        )?;
+    } else {
+        // The DEX slice is still settling asynchronously, so the queue entry
+        // itself waits for `handle_user_withdrawal_reply` to fold it in.
+        PENDING_USER_WITHDRAWAL.save(
+            deps.storage,
+            &PendingUserWithdrawal {
+                recipient: info.sender.clone(),
+                remaining: dex_withdraw_messages.len() as u64,
+                received_0: Uint128::zero(),
+                received_1: Uint128::zero(),
+                idle_amount_0: amount_0,
+                idle_amount_1: amount_1,
+                denom_0: balances[0].denom.clone(),
+                denom_1: balances[1].denom.clone(),
+                min_amount_0_out: None,
+                min_amount_1_out: None,
+                deadline: None,
+                settlement: WithdrawalSettlement::Queued { seq, release_at },
+            },
+        )?;
+    }
+
+    let remaining_shares = sender_shares - shares;
+    if remaining_shares.is_zero() {
+        SHARES.remove(deps.storage, info.sender.clone());
+    } else {
+        SHARES.save(deps.storage, info.sender.clone(), &remaining_shares)?;
+    }
+    config.total_shares -= shares;
+    config.balances.token_0.amount -= amount_0;
+    config.balances.token_1.amount -= amount_1;
+
+    apply_change_limiter(&deps, &env, &config, config.total_shares)?;
+
+    CONFIG.save(deps.storage, &config)?;
+    record_snapshot(&mut deps, &env, &config)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(dex_withdraw_messages)
+        .add_attribute("action", "queue_withdrawal")
+        .add_attribute("shares_burned", shares.to_string())
+        .add_attribute("queue_id", seq.to_string())
+        .add_attribute("token_0_amount", amount_0.to_string())
+        .add_attribute("token_1_amount", amount_1.to_string())
+        .add_attribute("release_at", release_at.to_string()))
+}
+
+/// Pays out and removes every one of the sender's `WITHDRAWAL_QUEUE` entries
+/// whose `release_at` has passed `env.block.time`, leaving any still-unmatured
+/// entries untouched. A no-op (no messages, no error) if nothing has matured.
+pub fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    require_not_frozen(&config.status)?;
+
+    let now = env.block.time.seconds();
+    let matured = WITHDRAWAL_QUEUE
+        .prefix(info.sender.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<cosmwasm_std::StdResult<Vec<(u64, UnbondEntry)>>>()?
+        .into_iter()
+        .filter(|(_, entry)| entry.release_at <= now)
+        .collect::<Vec<_>>();
+
+    let mut total_0 = Uint128::zero();
+    let mut total_1 = Uint128::zero();
+    for (id, entry) in &matured {
+        total_0 += entry.token_0.amount;
+        total_1 += entry.token_1.amount;
+        WITHDRAWAL_QUEUE.remove(deps.storage, (info.sender.clone(), *id));
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !total_0.is_zero() {
+        messages.push(payout_message(
+            &config.cw20_token_0,
+            &config.pair_data.token_0.denom,
+            &info.sender,
+            total_0,
+        )?);
+    }
+    if !total_1.is_zero() {
+        messages.push(payout_message(
+            &config.cw20_token_1,
+            &config.pair_data.token_1.denom,
+            &info.sender,
+            total_1,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "claim")
+        .add_attribute("entries_claimed", matured.len().to_string())
+        .add_attribute("token_0_amount", total_0.to_string())
+        .add_attribute("token_1_amount", total_1.to_string()))
 }
 
 // depends on up-to-date config
-pub fn dex_deposit(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn dex_deposit(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
     // Load the contract configuration
     let mut config = CONFIG.load(deps.storage)?;
     let mut messages: Vec<CosmosMsg> = vec![];
@@ -118,33 +924,188 @@ pub fn dex_deposit(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respons
     if info.sender != config.owner && info.sender != cron_address {
         return Err(ContractError::Unauthorized {});
     }
-    let balances = query_contract_balance(&deps, env.clone(), config.pair_data.clone())?;
+
+    // `config.owner` bypasses the throttle the same way it bypasses the
+    // authorization check above; only a cron-triggered call can be too
+    // frequent.
+    if config.min_dex_deposit_interval_seconds > 0 && info.sender != config.owner {
+        if let Some(last) = LAST_DEX_DEPOSIT.may_load(deps.storage)? {
+            let next_eligible = last + config.min_dex_deposit_interval_seconds;
+            if env.block.time.seconds() < next_eligible {
+                return Err(ContractError::DexDepositTooFrequent {
+                    next_eligible_seconds: next_eligible,
+                });
+            }
+        }
+    }
+    LAST_DEX_DEPOSIT.save(deps.storage, &env.block.time.seconds())?;
+
+    let balances = query_contract_balance(&deps, env.clone(), &config)?;
 
     config.balances.token_0.amount = balances[0].amount;
-    config.balances.token_1.amount = balances[1].amount; 
-    
+    config.balances.token_1.amount = balances[1].amount;
+
     CONFIG.save(deps.storage, &config)?;
 
-    // get the current slinky price and tick index
-    let prices: crate::msg::CombinedPriceResponse = get_prices(deps.as_ref(), env.clone())?;
+    // get the current slinky price, guard it against the tracked EMA, and
+    // derive the tick index to deposit around. Staleness is already
+    // enforced inside `get_prices_with_fallback`/`validate_price_fresh` (both
+    // wall-clock, via `TokenData::max_price_age_seconds`, and height-based,
+    // via `config.max_blocks_old`, rejecting with `ContractError::PriceTooOld`)
+    // before this ever returns a price to guard further - there is no
+    // separate `StalePrice` variant to duplicate that with.
+    let mut prices: crate::msg::CombinedPriceResponse = get_prices_with_fallback(&mut deps, &env)?;
+    validate_oracle_confidence(&prices, config.max_conf_ratio_bps)?;
+
+    // Runs ahead of `require_deposits_allowed` below (rather than after, its
+    // pre-existing position for every other guard) so a freeze this guard
+    // itself raised is eligible to lift automatically, once
+    // `dex_deviation_cooldown_blocks` have passed, in this same call -
+    // `require_deposits_allowed` would otherwise always reject before an
+    // auto-resume ever got a chance to run.
+    apply_dex_deviation_guard(&mut deps, &env, &mut config, &prices)?;
+    require_deposits_allowed(&config.status)?;
+
+    // EMA-deviation circuit breaker: `apply_ema_guard` maintains
+    // `TOKEN_PRICE_EMA`'s `price_0_to_1` EMA with the standard
+    // `ema = ema_prev + alpha * (spot - ema_prev)` recurrence
+    // (`config.ema_alpha`, in bps via `update_config`) and rejects with
+    // `ContractError::PriceDeviatesFromEma` once `abs(spot - ema) * 10_000 /
+    // ema` exceeds `config.ema_max_deviation_bps` (also `update_config`-able),
+    // unless `config.ema_fallback` is set, in which case it centers this
+    // deposit on the EMA price instead of rejecting outright.
+    prices.price_0_to_1 = apply_ema_guard(&deps, &env, &config, prices.price_0_to_1)?;
+    prices = apply_target_rate(&deps, &env, &config, prices)?;
     let tick_index = price_to_tick_index(prices.price_0_to_1)?;
 
+    // Widen the deployed fee tier(s) when `volatility_spread` is configured
+    // and recent prices have been turbulent, instead of always deploying at
+    // the same fixed `base_fee`/`fee_tiers`.
+    let config = if let Some(volatility_cfg) = config.volatility_spread.clone() {
+        let history = crate::volatility::record_price_sample(
+            &deps,
+            prices.price_0_to_1,
+            env.block.time.seconds(),
+            volatility_cfg.window_size,
+        )?;
+        let volatility = crate::volatility::realized_volatility(&history)?;
+        let spread_bps = crate::volatility::dynamic_spread_bps(volatility, &volatility_cfg)?;
+        widen_for_volatility(&config, spread_bps)
+    } else {
+        config
+    };
+
+    // Skip the withdraw->redeposit churn entirely when the freshly computed
+    // `tick_index` and `fee_tiers`/`base_fee` haven't meaningfully moved
+    // since `LAST_DEPLOYED_STATE`'s snapshot - `prepare_state`/
+    // `get_deposit_messages`/`get_limit_order_messages` would just re-place
+    // an equivalent position at DEX taker-fee cost for no rebalancing
+    // benefit. `Config::balances`/the price guards above have already run
+    // and saved, so the vault's accounting stays current even on a skip.
+    if let Some(last) = LAST_DEPLOYED_STATE.may_load(deps.storage)? {
+        let tick_drift = (tick_index - last.tick_index).unsigned_abs();
+        let tiers_unchanged =
+            last.fee_tiers == config.fee_tiers && last.base_fee == config.base_fee;
+        if tiers_unchanged && tick_drift <= config.rebalance_drift_tolerance_ticks {
+            return Ok(Response::new()
+                .add_attribute("action", "dex_deposit")
+                .add_attribute("result", "skipped_no_drift")
+                .add_attribute("tick_drift", tick_drift.to_string()));
+        }
+    }
+
     let (lo_messages, token_0_usable, token_1_usable) =
         prepare_state(&deps, &env, &config, tick_index)?;
     messages.extend(lo_messages);
-    let deposit_messages = get_deposit_messages(
-        &env,
-        config.clone(),
-        tick_index,
-        prices,
-        token_0_usable,
-        token_1_usable,
+
+    // Guard the skew-driven rebalance step against a single-block oracle
+    // spike: compare spot `prices.price_0_to_1` against its TWAP over
+    // `config.twap_window_seconds` and skip rebalancing entirely (instead of
+    // swapping toward a possibly-manipulated spot price) once they've
+    // diverged more than `config.max_twap_deviation_bps`. `0` disables the
+    // guard, the pre-existing behavior.
+    let observations = crate::twap::record_price_observation(
+        &deps,
+        prices.price_0_to_1,
+        env.block.time.seconds(),
+        config.twap_window_seconds,
     )?;
-    messages.extend(deposit_messages);
+    let twap = crate::twap::twap_price(&observations);
+    let deviation_bps = crate::twap::twap_deviation_bps(prices.price_0_to_1, twap);
+    let skip_rebalance = config.max_twap_deviation_bps != 0
+        && deviation_bps > PrecDec::from_ratio(config.max_twap_deviation_bps, 1u128);
 
-    Ok(Response::new()
+    // swap toward the target inventory ratio, if configured, needed, and not
+    // skipped by the TWAP deviation guard above, before computing fee-tier
+    // allocations
+    let (rebalance_msg, token_0_usable, token_1_usable, rebalanced_amount) = if skip_rebalance {
+        (None, token_0_usable, token_1_usable, Uint128::zero())
+    } else {
+        rebalance_inventory(
+            &deps,
+            &env,
+            &config,
+            &prices,
+            tick_index,
+            token_0_usable,
+            token_1_usable,
+        )?
+    };
+    messages.extend(rebalance_msg);
+
+    // strategy selector: quote a passive maker ladder instead of pooling
+    // liquidity with `MsgDeposit` when `market_making` is configured.
+    let deposit_messages = if let Some(market_making) = config.market_making.clone() {
+        messages.extend(get_limit_order_messages(
+            &env,
+            &config,
+            tick_index,
+            &prices,
+            token_0_usable,
+            token_1_usable,
+            &market_making,
+        )?);
+        vec![]
+    } else {
+        get_deposit_messages(
+            &deps,
+            &env,
+            config.clone(),
+            tick_index,
+            prices,
+            token_0_usable,
+            token_1_usable,
+        )?
+    };
+
+    // Track what just went out to the DEX so `handle_dex_withdrawal_reply`
+    // has a principal baseline to compare returned reserves against.
+    let mut deployed = DEPLOYED_PRINCIPAL.may_load(deps.storage)?.unwrap_or(Balances {
+        token_0: Coin::new(Uint128::zero(), config.pair_data.token_0.denom.clone()),
+        token_1: Coin::new(Uint128::zero(), config.pair_data.token_1.denom.clone()),
+    });
+    deployed.token_0.amount += token_0_usable;
+    deployed.token_1.amount += token_1_usable;
+    DEPLOYED_PRINCIPAL.save(deps.storage, &deployed)?;
+
+    LAST_DEPLOYED_STATE.save(
+        deps.storage,
+        &LastDeployedState {
+            tick_index,
+            fee_tiers: config.fee_tiers.clone(),
+            base_fee: config.base_fee,
+        },
+    )?;
+
+    let mut response = Response::new()
         .add_messages(messages)
-        .add_attribute("action", "dex_deposit"))
+        .add_submessages(deposit_messages)
+        .add_attribute("action", "dex_deposit")
+        .add_attribute("rebalanced_amount", rebalanced_amount.to_string());
+    if skip_rebalance {
+        response = response.add_attribute("price_deviation", deviation_bps.to_string());
+    }
+    Ok(response)
 }
 
 pub fn dex_withdrawal(
@@ -154,6 +1115,7 @@ pub fn dex_withdrawal(
 ) -> Result<Response, ContractError> {
     // Load the contract configuration to access the owner address and balances
     let config = CONFIG.load(deps.storage)?;
+    require_not_frozen(&config.status)?;
     let cron_address = Addr::unchecked(CRON_MODULE_ADDRESS);
 
     // if the caller is not the owner or the cron module, return an error
@@ -161,74 +1123,977 @@ pub fn dex_withdrawal(
         return Err(ContractError::Unauthorized {});
     }
 
-    // Prepare a vector to hold withdrawals
-    let mut messages: Vec<CosmosMsg> = vec![];
-    // Check if there are any active deposits
-    let dex_querier = DexQuerier::new(&deps.querier);
-    let res: QueryAllUserDepositsResponse =
-        dex_querier.user_deposits_all(env.contract.address.to_string(), None, true)?;
-
-    // If there are any active deposits, withdraw all of them
-    for deposit in res.deposits.iter() {
-        let withdraw_msg = Into::<CosmosMsg>::into(MsgWithdrawal {
-            creator: env.contract.address.to_string(),
-            receiver: env.contract.address.to_string(),
-            token_a: config.pair_data.token_0.denom.clone(),
-            token_b: config.pair_data.token_1.denom.clone(),
-            shares_to_remove: vec![deposit
-                .shares_owned
-                .parse()
-                .expect("Failed to parse the string as an integer")],
-            tick_indexes_a_to_b: vec![deposit.center_tick_index],
-            fees: vec![deposit.fee],
-        });
-
-        // Wrap the DexMsg into a SubMsg with reply
-        messages.push(withdraw_msg);
-    }
+    // Cancel every open limit order owned by the vault
+    let messages = create_dex_withdrawal_messages(&deps, &env, &config)?;
 
     // Add the message to the response and return
     Ok(Response::new()
-        .add_messages(messages)
+        .add_submessages(messages)
         .add_attribute("action", "dex_withdrawal"))
 }
 
+/// Rejects with `ContractError::DepositsFrozen`/`ContractError::ContractFrozen`
+/// unless `status` is `ContractStatus::Operational`. Gates `Deposit`/`DexDeposit`
+/// - `dex_deposit` is also the only caller of `prepare_state`, so setting
+/// `ContractStatus::DepositsFrozen` already doubles as a withdraw-only
+/// maintenance mode: no new limit orders reach the book, but `withdraw`
+/// (gated only by `require_not_frozen`) keeps working so depositors can
+/// always exit during a migration or an oracle issue, without needing a
+/// separate `mode` field alongside `status`.
+pub fn require_deposits_allowed(status: &ContractStatus) -> Result<(), ContractError> {
+    match status {
+        ContractStatus::Operational => Ok(()),
+        ContractStatus::DepositsFrozen => Err(ContractError::DepositsFrozen),
+        ContractStatus::WindDown => Err(ContractError::DepositsFrozen),
+        ContractStatus::Frozen => Err(ContractError::ContractFrozen),
+    }
+}
 
-pub fn update_config(
+/// Rejects with `ContractError::ContractFrozen` only under
+/// `ContractStatus::Frozen`. Gates every other non-admin message so
+/// depositors can always exit under `DepositsFrozen`.
+pub fn require_not_frozen(status: &ContractStatus) -> Result<(), ContractError> {
+    if matches!(status, ContractStatus::Frozen) {
+        return Err(ContractError::ContractFrozen);
+    }
+    Ok(())
+}
+
+pub fn set_contract_status(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    max_blocks_old: Option<u64>,
-    base_fee: Option<u64>,
-    base_deposit_percentage: Option<u64>,
-    ambient_fee: Option<u64>,
-    deposit_ambient: Option<bool>,
-    deposit_cap: Option<Uint128>,
+    status: ContractStatus,
+    reason: Option<String>,
 ) -> Result<Response, ContractError> {
-    // Load and verify owner
     let mut config = CONFIG.load(deps.storage)?;
-    if info.sender != config.owner {
+    if info.sender != config.admin {
         return Err(ContractError::Unauthorized {});
     }
 
-    // Update max_blocks_old if provided
-    if let Some(blocks) = max_blocks_old {
-        if blocks > 2 {
-            return Err(ContractError::MalformedInput {
-                input: "max_block_old".to_string(),
-                reason: "must be <=2".to_string(),
-            });
-        }
-        config.max_blocks_old = blocks;
+    let status_attr = format!("{status:?}");
+    config.status = status;
+    config.status_reason = reason;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_contract_status")
+        .add_attribute("status", status_attr))
+}
+
+/// Cancels all open DEX limit orders and sweeps the resulting free funds to the owner.
+pub fn purge_and_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
     }
 
-    // Update base_fee if provided
-    if let Some(fee) = base_fee {
-        InstantiateMsg::validate_base_fee(fee)?;
-        config.base_fee = fee;
+    let mut messages = create_dex_withdrawal_messages(&deps, &env, &config)?;
+    let balances = query_contract_balance(&deps, env.clone(), &config)?;
+    let cw20_addrs = [&config.cw20_token_0, &config.cw20_token_1];
+    for (balance, cw20_addr) in balances.iter().zip(cw20_addrs) {
+        if balance.amount > Uint128::zero() {
+            messages.push(SubMsg::new(payout_message(
+                cw20_addr,
+                &balance.denom,
+                &config.owner,
+                balance.amount,
+            )?));
+        }
     }
 
-    // Update base_deposit_percentage if provided
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", "purge_and_withdraw"))
+}
+
+/// Cancels all open DEX limit orders and sets status to `Frozen`.
+pub fn purge_and_pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let messages = create_dex_withdrawal_messages(&deps, &env, &config)?;
+    config.status = ContractStatus::Frozen;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", "purge_and_pause")
+        .add_attribute("paused", "true"))
+}
+
+/// Admin-only: cancels all open DEX limit orders, realizing principal and
+/// accrued swap fees back into the contract. `handle_dex_withdrawal_reply`
+/// does the actual split once the withdrawal lands, crediting principal to
+/// `Config::balances` and the earned-fee delta to `Config::accrued_fees`.
+pub fn sweep_fees(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let messages = create_dex_withdrawal_messages(&deps, &env, &config)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", "sweep_fees"))
+}
+
+/// Admin-only: pays out `Config::accrued_fees` to `Config::fee_splitter`'s
+/// recipients, pro-rata by weight, then zeroes `accrued_fees` out. LP
+/// principal in `Config::balances` is never touched by this.
+pub fn distribute_fees(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let messages = create_fee_distribution_messages(&config)?;
+
+    config.balances.token_0.amount =
+        config.balances.token_0.amount.saturating_sub(config.accrued_fees.token_0.amount);
+    config.balances.token_1.amount =
+        config.balances.token_1.amount.saturating_sub(config.accrued_fees.token_1.amount);
+    config.accrued_fees.token_0.amount = Uint128::zero();
+    config.accrued_fees.token_1.amount = Uint128::zero();
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "distribute_fees"))
+}
+
+/// Admin-only: (re)configures the performance fee rate and the
+/// `fee_splitter` recipients it (and `DistributeFees`) pays out to.
+pub fn set_performance_fee(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    fee_bps: u64,
+    recipients: Vec<(String, u64)>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    InstantiateMsg::validate_performance_fee_bps(fee_bps)?;
+    InstantiateMsg::validate_fee_splitter(&Some(recipients.clone()))?;
+
+    let fee_splitter = FeeSplitterConfig {
+        recipients: recipients
+            .iter()
+            .map(|(addr, weight)| Ok((deps.api.addr_validate(addr)?, *weight)))
+            .collect::<crate::error::ContractResult<Vec<_>>>()?,
+    };
+
+    config.performance_fee_bps = fee_bps;
+    config.fee_splitter = Some(fee_splitter);
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_performance_fee")
+        .add_attribute("fee_bps", fee_bps.to_string()))
+}
+
+/// Admin-only: charges `Config::performance_fee_bps` of each token's new
+/// per-share appreciation since the last harvest and pays it out to
+/// `Config::fee_splitter`'s recipients, pro-rata by weight.
+pub fn harvest_performance_fee(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let messages = create_performance_fee_messages(&deps, &env, &config)?;
+    let total_shares_before = config.total_shares;
+    accrue_management_fee(&mut deps, &env, &mut config)?;
+    if config.total_shares != total_shares_before {
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "harvest_performance_fee")
+        .add_attribute("management_fee_minted", (config.total_shares - total_shares_before).to_string()))
+}
+
+/// Admin-only: (re)configures the time-based management fee rate and the
+/// collector minted shares accrue to.
+pub fn set_management_fee(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    fee_bps: u64,
+    collector: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    InstantiateMsg::validate_management_fee_bps(fee_bps)?;
+    let collector = deps.api.addr_validate(&collector)?;
+
+    config.management_fee_bps = fee_bps;
+    config.fee_collector = Some(collector);
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_management_fee")
+        .add_attribute("fee_bps", fee_bps.to_string()))
+}
+
+/// Swaps `amount_in` of `token_in` directly against the vault's own idle
+/// `config.balances`, at the more conservative of a constant-product quote
+/// and an oracle quote (see `compute_swap_out`), so neither a thin pool nor a
+/// stale/manipulated oracle can be used alone to drain it. `recipient`
+/// defaults to the sender, so a router can swap on a user's behalf and
+/// deliver `token_out` straight to them. Not gated by
+/// `ContractStatus::DepositsFrozen`: like `Withdraw`, arbitrageurs should
+/// still be able to trade the vault back toward the oracle price while
+/// deposits are frozen. Only `ContractStatus::Frozen` halts it.
+pub fn swap(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_in: String,
+    amount_in: Uint128,
+    min_out: Uint128,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    require_not_frozen(&config.status)?;
+
+    if amount_in.is_zero() {
+        return Err(ContractError::InvalidTokenAmount);
+    }
+    if info.funds.len() != 1 || info.funds[0].denom != token_in || info.funds[0].amount != amount_in
+    {
+        return Err(ContractError::InvalidToken);
+    }
+
+    let prices = get_prices_with_fallback(&mut deps, &env)?;
+
+    // Same oracle-vs-DEX circuit breaker `dex_deposit` runs. It only ever
+    // freezes/resumes deposits (`config.status`), never rejects the swap
+    // itself - per this function's own doc comment, arbitrage should still
+    // be able to trade the vault back toward the oracle price while deposits
+    // are frozen, which is exactly what's supposed to happen while this
+    // guard is active.
+    apply_dex_deviation_guard(&mut deps, &env, &mut config, &prices)?;
+
+    let token_0_denom = config.pair_data.token_0.denom.clone();
+    let token_1_denom = config.pair_data.token_1.denom.clone();
+    let swap_0_to_1 = if token_in == token_0_denom {
+        true
+    } else if token_in == token_1_denom {
+        false
+    } else {
+        return Err(ContractError::InvalidToken);
+    };
+
+    let (reserve_in, reserve_out, price_in, price_out, token_out_denom) = if swap_0_to_1 {
+        (
+            config.balances.token_0.amount,
+            config.balances.token_1.amount,
+            prices.token_0_price,
+            prices.token_1_price,
+            token_1_denom,
+        )
+    } else {
+        (
+            config.balances.token_1.amount,
+            config.balances.token_0.amount,
+            prices.token_1_price,
+            prices.token_0_price,
+            token_0_denom,
+        )
+    };
+
+    let amount_out = compute_swap_out(
+        reserve_in,
+        reserve_out,
+        amount_in,
+        price_in,
+        price_out,
+        config.swap_fee_bps,
+    )?;
+    if amount_out < min_out {
+        return Err(ContractError::SwapBelowMinOut { amount_out, min_out });
+    }
+    if amount_out > reserve_out {
+        return Err(ContractError::InsufficientFunds {
+            available: reserve_out,
+            required: amount_out,
+        });
+    }
+
+    if swap_0_to_1 {
+        config.balances.token_0.amount += amount_in;
+        config.balances.token_1.amount -= amount_out;
+    } else {
+        config.balances.token_1.amount += amount_in;
+        config.balances.token_0.amount -= amount_out;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    let recipient_addr = recipient
+        .map(|r| deps.api.addr_validate(&r))
+        .transpose()?
+        .unwrap_or_else(|| info.sender.clone());
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: recipient_addr.to_string(),
+            amount: vec![Coin::new(amount_out, token_out_denom)],
+        })
+        .add_attribute("action", "swap")
+        .add_attribute("from", info.sender.to_string())
+        .add_attribute("recipient", recipient_addr.to_string())
+        .add_attribute("amount_in", amount_in.to_string())
+        .add_attribute("amount_out", amount_out.to_string()))
+}
+
+/// `reply` handler for `DEX_WITHDRAW_REPLY_ID`. `create_dex_withdrawal_messages`
+/// dispatches one `MsgWithdrawal` sub-message per on-chain DEX position, so
+/// this accumulates each reply's reserves into `PENDING_DEX_WITHDRAWAL` until
+/// the whole batch (`remaining` from `PENDING_DEX_WITHDRAWAL`) has reported
+/// back. Once it has, whatever the batch returned beyond `DEPLOYED_PRINCIPAL`
+/// is earned DEX trading fees: credited to `Config::accrued_fees` instead of
+/// `Config::balances`, so a later `ExecuteMsg::DistributeFees` can never pay
+/// the splitter out of LP principal. `DEPLOYED_PRINCIPAL` resets to zero
+/// since a withdrawal batch is always a full exit of every open position.
+pub fn handle_dex_withdrawal_reply(
+    deps: DepsMut,
+    env: Env,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let response = result.into_result().map_err(|_| ContractError::NoResponseData)?;
+    let (amount_0, amount_1) = extract_withdrawal_amounts(&response)?;
+
+    let mut pending = PENDING_DEX_WITHDRAWAL.may_load(deps.storage)?.unwrap_or(PendingWithdrawal {
+        remaining: 1,
+        received_0: Uint128::zero(),
+        received_1: Uint128::zero(),
+    });
+    pending.received_0 += amount_0;
+    pending.received_1 += amount_1;
+    pending.remaining = pending.remaining.saturating_sub(1);
+
+    if pending.remaining > 0 {
+        PENDING_DEX_WITHDRAWAL.save(deps.storage, &pending)?;
+        return Ok(Response::new()
+            .add_attribute("action", "dex_withdrawal_response")
+            .add_attribute("status", "awaiting_more_replies"));
+    }
+    PENDING_DEX_WITHDRAWAL.remove(deps.storage);
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let principal = DEPLOYED_PRINCIPAL.may_load(deps.storage)?.unwrap_or(Balances {
+        token_0: Coin::new(Uint128::zero(), config.pair_data.token_0.denom.clone()),
+        token_1: Coin::new(Uint128::zero(), config.pair_data.token_1.denom.clone()),
+    });
+
+    let fee_0 = pending.received_0.saturating_sub(principal.token_0.amount);
+    let fee_1 = pending.received_1.saturating_sub(principal.token_1.amount);
+    config.accrued_fees.token_0.amount += fee_0;
+    config.accrued_fees.token_1.amount += fee_1;
+
+    // The withdrawn reserves (principal and fees alike) have already landed
+    // in the contract's bank balance by the time a reply runs, so re-reading
+    // it keeps `config.balances` exact rather than re-deriving it by addition.
+    let idle = query_contract_balance(&deps, env, &config)?;
+    config.balances.token_0.amount = idle[0].amount;
+    config.balances.token_1.amount = idle[1].amount;
+    CONFIG.save(deps.storage, &config)?;
+    DEPLOYED_PRINCIPAL.save(
+        deps.storage,
+        &Balances {
+            token_0: Coin::new(Uint128::zero(), config.pair_data.token_0.denom.clone()),
+            token_1: Coin::new(Uint128::zero(), config.pair_data.token_1.denom.clone()),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "dex_withdrawal_response")
+        .add_attribute("status", "settled")
+        .add_attribute("fees_accrued_0", fee_0.to_string())
+        .add_attribute("fees_accrued_1", fee_1.to_string()))
+}
+
+/// `reply` handler for `DEX_USER_WITHDRAW_REPLY_ID`. `withdraw` dispatches one
+/// `MsgWithdrawal` sub-message per active DEX position it's pulling a
+/// pro-rata slice out of, so this accumulates each reply's reserves into
+/// `PENDING_USER_WITHDRAWAL` until the whole batch (`remaining`) has reported
+/// back. Once it has, the accumulated DEX reserves join the idle slice
+/// `withdraw` already set aside. For an `Immediate` settlement the deferred
+/// slippage check finally runs against that combined total and one
+/// `payout_message` per non-zero leg pays the withdrawer out; a `Queued`
+/// settlement writes its `WITHDRAWAL_QUEUE` entry instead. Unlike `handle_dex_withdrawal_reply`'s full-liquidation
+/// batch, what comes back here is the withdrawer's own principal plus their
+/// pro-rata share of that position's earned fees, so none of it is fee
+/// income to split off into `Config::accrued_fees` - it all goes straight to
+/// them. `DEPLOYED_PRINCIPAL` shrinks by what left instead of zeroing out,
+/// since this is a partial, not full, exit.
+pub fn handle_user_withdrawal_reply(
+    deps: DepsMut,
+    env: Env,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let response = result.into_result().map_err(|_| ContractError::NoResponseData)?;
+    let (amount_0, amount_1) = extract_withdrawal_amounts(&response)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut pending = PENDING_USER_WITHDRAWAL.load(deps.storage)?;
+    pending.received_0 += amount_0;
+    pending.received_1 += amount_1;
+    pending.remaining = pending.remaining.saturating_sub(1);
+
+    if pending.remaining > 0 {
+        PENDING_USER_WITHDRAWAL.save(deps.storage, &pending)?;
+        return Ok(Response::new()
+            .add_attribute("action", "user_withdrawal_response")
+            .add_attribute("status", "awaiting_more_replies"));
+    }
+    PENDING_USER_WITHDRAWAL.remove(deps.storage);
+
+    if let Some(principal) = DEPLOYED_PRINCIPAL.may_load(deps.storage)? {
+        DEPLOYED_PRINCIPAL.save(
+            deps.storage,
+            &Balances {
+                token_0: Coin::new(
+                    principal.token_0.amount.saturating_sub(pending.received_0),
+                    principal.token_0.denom,
+                ),
+                token_1: Coin::new(
+                    principal.token_1.amount.saturating_sub(pending.received_1),
+                    principal.token_1.denom,
+                ),
+            },
+        )?;
+    }
+
+    if let Some(deadline) = pending.deadline {
+        if env.block.height > deadline {
+            return Err(ContractError::WithdrawalDeadlineExceeded {
+                deadline,
+                current_height: env.block.height,
+            });
+        }
+    }
+
+    let total_0 = pending.idle_amount_0 + pending.received_0;
+    let total_1 = pending.idle_amount_1 + pending.received_1;
+
+    match pending.settlement {
+        WithdrawalSettlement::Immediate => {
+            // Already the per-withdrawal refund-on-shortfall guard: `withdraw`
+            // threads its caller's `min_amount_0_out`/`min_amount_1_out` into
+            // this pending record, and a shortfall here returns
+            // `ContractError::SlippageExceeded` before any `BankMsg::Send` is
+            // built, so a worse-than-expected DEX settlement aborts the
+            // payout outright instead of paying out whatever arrived.
+            if let Some(min_amount_0_out) = pending.min_amount_0_out {
+                if total_0 < min_amount_0_out {
+                    return Err(ContractError::SlippageExceeded { min: min_amount_0_out, actual: total_0 });
+                }
+            }
+            if let Some(min_amount_1_out) = pending.min_amount_1_out {
+                if total_1 < min_amount_1_out {
+                    return Err(ContractError::SlippageExceeded { min: min_amount_1_out, actual: total_1 });
+                }
+            }
+
+            // Built as separate per-leg messages (rather than one
+            // `BankMsg::Send` carrying both coins) since a CW20-backed leg
+            // pays out through its own `WasmMsg::Execute` instead of the
+            // bank module.
+            let mut payout_messages = vec![];
+            if total_0 > Uint128::zero() {
+                payout_messages.push(payout_message(
+                    &config.cw20_token_0,
+                    &pending.denom_0,
+                    &pending.recipient,
+                    total_0,
+                )?);
+            }
+            if total_1 > Uint128::zero() {
+                payout_messages.push(payout_message(
+                    &config.cw20_token_1,
+                    &pending.denom_1,
+                    &pending.recipient,
+                    total_1,
+                )?);
+            }
+
+            Ok(Response::new()
+                .add_messages(payout_messages)
+                .add_attribute("action", "user_withdrawal_response")
+                .add_attribute("status", "settled")
+                .add_attribute("recipient", pending.recipient.to_string())
+                .add_attribute("token_0_amount", total_0.to_string())
+                .add_attribute("token_1_amount", total_1.to_string()))
+        }
+        WithdrawalSettlement::Queued { seq, release_at } => {
+            // The idle + DEX slices only now both exist, so this is the
+            // first point the `WITHDRAWAL_QUEUE` entry can be written;
+            // `queue_withdrawal` already burned the shares and reserved the
+            // queue id up front.
+            WITHDRAWAL_QUEUE.save(
+                deps.storage,
+                (pending.recipient.clone(), seq),
+                &UnbondEntry {
+                    token_0: Coin {
+                        denom: pending.denom_0,
+                        amount: total_0,
+                    },
+                    token_1: Coin {
+                        denom: pending.denom_1,
+                        amount: total_1,
+                    },
+                    release_at,
+                },
+            )?;
+
+            Ok(Response::new()
+                .add_attribute("action", "user_withdrawal_response")
+                .add_attribute("status", "queued")
+                .add_attribute("recipient", pending.recipient.to_string())
+                .add_attribute("queue_id", seq.to_string())
+                .add_attribute("token_0_amount", total_0.to_string())
+                .add_attribute("token_1_amount", total_1.to_string()))
+        }
+    }
+}
+
+/// `reply` handler for `DEX_DEPOSIT_REPLY_ID`. The deposit sub-messages are
+/// dispatched `reply_on_error`, so this runs only when one fails: it moves
+/// the `PENDING_DEX_DEPOSIT` stash recorded by `get_deposit_messages`/
+/// `retry_deposit` into `FAILED_DEPOSITS` (keyed by an
+/// incrementing id) instead of letting the failure silently drop the idle
+/// funds with no on-chain record or recovery path.
+pub fn handle_dex_deposit_reply(
+    deps: DepsMut,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let mut pending = PENDING_DEX_DEPOSIT.load(deps.storage)?;
+    PENDING_DEX_DEPOSIT.remove(deps.storage);
+
+    if let Err(err) = result.into_result() {
+        pending.error = err;
+        let id = FAILED_DEPOSIT_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+        FAILED_DEPOSIT_SEQ.save(deps.storage, &id)?;
+        FAILED_DEPOSITS.save(deps.storage, id, &pending)?;
+
+        return Ok(Response::new()
+            .add_attribute("action", "dex_deposit_response")
+            .add_attribute("status", "recorded_failure")
+            .add_attribute("failed_deposit_id", id.to_string()));
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "dex_deposit_response")
+        .add_attribute("status", "success"))
+}
+
+/// `reply` handler for `REWARD_CLAIM_REPLY_ID`. `execute_collect_rewards`
+/// dispatches every claim call in a single batch, with only the last one
+/// wrapped as a reply - by the time this fires, every claim in the batch has
+/// already landed in the vault's bank balance. Diffs that balance against the
+/// `PENDING_REWARD_CLAIM_SNAPSHOT` taken right before the batch went out to
+/// learn exactly what came back, per denom, without needing to parse any
+/// particular claim contract's reply `data`. `token_0`/`token_1` are this
+/// vault's own principal/trading-fee denoms, already accounted for elsewhere,
+/// so any amount in those denoms is left alone here. Everything else is
+/// folded into `DISTRIBUTED_REWARDS` and bumps `EXTERNAL_REWARD_PER_SHARE`
+/// for that denom by `claimed / CURRENT_TOTAL_SUPPLY`, the same pro-rata
+/// mechanism `accrue_rewards` uses for the single-denom incentive program.
+pub fn handle_reward_claim_reply(
+    deps: DepsMut,
+    env: Env,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    result.into_result().map_err(|_| ContractError::NoResponseData)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let before = PENDING_REWARD_CLAIM_SNAPSHOT.load(deps.storage)?;
+    PENDING_REWARD_CLAIM_SNAPSHOT.remove(deps.storage);
+    let total_supply = CURRENT_TOTAL_SUPPLY.load(deps.storage)?;
+    CURRENT_TOTAL_SUPPLY.remove(deps.storage);
+
+    let after = deps.querier.query_all_balances(env.contract.address.to_string())?;
+    let mut distributed = DISTRIBUTED_REWARDS.may_load(deps.storage)?.unwrap_or_default();
+    let mut claimed_denoms = vec![];
+
+    for coin in after {
+        if coin.denom == config.pair_data.token_0.denom || coin.denom == config.pair_data.token_1.denom
+        {
+            continue;
+        }
+        let before_amount = before
+            .0
+            .iter()
+            .find(|c| c.denom == coin.denom)
+            .map(|c| c.amount)
+            .unwrap_or_default();
+        let claimed = coin.amount.saturating_sub(before_amount);
+        if claimed.is_zero() {
+            continue;
+        }
+
+        distributed.add(Coin { denom: coin.denom.clone(), amount: claimed });
+
+        if !total_supply.is_zero() {
+            let delta = Decimal::from_ratio(claimed, total_supply);
+            let mut reward_per_share = EXTERNAL_REWARD_PER_SHARE
+                .may_load(deps.storage, coin.denom.clone())?
+                .unwrap_or_default();
+            reward_per_share = reward_per_share
+                .checked_add(delta)
+                .map_err(|_| ContractError::DecimalConversionError)?;
+            EXTERNAL_REWARD_PER_SHARE.save(deps.storage, coin.denom.clone(), &reward_per_share)?;
+        }
+        claimed_denoms.push(format!("{}{}", claimed, coin.denom));
+    }
+    DISTRIBUTED_REWARDS.save(deps.storage, &distributed)?;
+    REWARDS_STATUS.save(deps.storage, &RewardsStatus::Ready)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "collect_rewards_response")
+        .add_attribute("status", "settled")
+        .add_attribute("claimed", claimed_denoms.join(",")))
+}
+
+/// Whitelist-gated (same callers as `DexDeposit`): rebuilds the `MsgDeposit`
+/// recorded under `id` in `FAILED_DEPOSITS` and resubmits it as a fresh
+/// `reply_on_error` sub-message, clearing the recorded entry. If this attempt
+/// also fails, `handle_dex_deposit_reply` records it again under a new id.
+pub fn retry_deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let cron_address = Addr::unchecked(CRON_MODULE_ADDRESS);
+    if info.sender != config.owner && info.sender != cron_address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let failed = FAILED_DEPOSITS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::FailedDepositNotFound { id })?;
+    FAILED_DEPOSITS.remove(deps.storage, id);
+
+    let deposit_msg = MsgDeposit {
+        creator: env.contract.address.to_string(),
+        receiver: env.contract.address.to_string(),
+        token_a: failed.token_a.clone(),
+        token_b: failed.token_b.clone(),
+        amounts_a: failed.amounts_a.clone(),
+        amounts_b: failed.amounts_b.clone(),
+        tick_indexes_a_to_b: failed.tick_indexes_a_to_b.clone(),
+        fees: failed.fees.clone(),
+        options: vec![
+            DepositOptions {
+                disable_autoswap: false,
+                fail_tx_on_bel: false,
+            };
+            failed.tick_indexes_a_to_b.len()
+        ],
+    };
+
+    PENDING_DEX_DEPOSIT.save(
+        deps.storage,
+        &FailedDeposit {
+            error: String::new(),
+            ..failed
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_error(deposit_msg, DEX_DEPOSIT_REPLY_ID))
+        .add_attribute("action", "retry_deposit")
+        .add_attribute("failed_deposit_id", id.to_string()))
+}
+
+/// Admin-only: (re)configures the vault's incentive emission. Replaces any
+/// previously configured (and possibly still-accruing) incentives outright;
+/// accrue it up to now first so past depositors aren't shortchanged by the switch.
+pub fn set_incentives(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reward_denom: String,
+    total_reward: Uint128,
+    start_time: u64,
+    end_time: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    accrue_rewards(&mut deps, env.block.time.seconds(), &config)?;
+
+    let incentives = IncentiveConfig {
+        reward_denom,
+        total_reward,
+        start_time,
+        end_time,
+    };
+    validate_incentive_config(&incentives)?;
+    config.incentives = Some(incentives.clone());
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_incentives")
+        .add_attribute("reward_denom", incentives.reward_denom)
+        .add_attribute("total_reward", incentives.total_reward.to_string())
+        .add_attribute("start_time", incentives.start_time.to_string())
+        .add_attribute("end_time", incentives.end_time.to_string()))
+}
+
+/// Claims the sender's currently accrued incentive reward, if any.
+pub fn claim_incentives(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    require_not_frozen(&config.status)?;
+    let reward_per_share = accrue_rewards(&mut deps, env.block.time.seconds(), &config)?;
+    let claim_msg =
+        create_incentive_claim_message(&mut deps, &config, &info.sender, reward_per_share)?;
+
+    let mut response = Response::new().add_attribute("action", "claim_incentives");
+    if let Some(claim_msg) = claim_msg {
+        response = response.add_message(claim_msg);
+    }
+    Ok(response)
+}
+
+/// Admin/cron-only: dispatches a `RewardClaimExecuteMsg::Claim` to every
+/// configured `Config::reward_claim_contracts`, so the vault's bank balance
+/// picks up whatever DEX/gauge incentives those contracts owe it. Every
+/// claim but the last goes out as a plain message; the last is wrapped
+/// `reply_on_success` on `REWARD_CLAIM_REPLY_ID`, since CosmWasm runs a
+/// response's messages in order, so by the time that one reply fires every
+/// earlier claim has already landed and `handle_reward_claim_reply` can diff
+/// the whole batch's effect on the balance at once. `REWARDS_STATUS` guards
+/// against a second `CollectRewards` call stomping the
+/// `PENDING_REWARD_CLAIM_SNAPSHOT` this one is still waiting on.
+pub fn execute_collect_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    require_not_frozen(&config.status)?;
+    let cron_address = Addr::unchecked(CRON_MODULE_ADDRESS);
+    if info.sender != config.admin && info.sender != cron_address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if config.reward_claim_contracts.is_empty() {
+        return Err(ContractError::NoRewardClaimContractsConfigured);
+    }
+    if REWARDS_STATUS.may_load(deps.storage)?.unwrap_or_default() != RewardsStatus::Ready {
+        return Err(ContractError::RewardCollectionInProgress);
+    }
+
+    let snapshot = deps.querier.query_all_balances(env.contract.address.to_string())?;
+    PENDING_REWARD_CLAIM_SNAPSHOT.save(deps.storage, &CoinList(snapshot))?;
+    CURRENT_TOTAL_SUPPLY.save(deps.storage, &config.total_shares)?;
+    REWARDS_STATUS.save(deps.storage, &RewardsStatus::Claiming)?;
+
+    let claim_msg = to_json_binary(&RewardClaimExecuteMsg::Claim {})?;
+    let last = config.reward_claim_contracts.len() - 1;
+    let messages: Vec<SubMsg> = config
+        .reward_claim_contracts
+        .iter()
+        .enumerate()
+        .map(|(i, contract)| {
+            let wasm_msg = WasmMsg::Execute {
+                contract_addr: contract.to_string(),
+                msg: claim_msg.clone(),
+                funds: vec![],
+            };
+            if i == last {
+                SubMsg::reply_on_success(wasm_msg, REWARD_CLAIM_REPLY_ID)
+            } else {
+                SubMsg::new(wasm_msg)
+            }
+        })
+        .collect();
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", "collect_rewards")
+        .add_attribute("reward_claim_contracts", config.reward_claim_contracts.len().to_string()))
+}
+
+/// Pays the sender their pro-rata share (by vault shares) of every denom
+/// `DISTRIBUTED_REWARDS` has ever realized, accrued since their last
+/// `ClaimRewards`/deposit/withdraw for that denom. Reuses `pending_incentives`
+/// per-denom, the same accumulator math `ClaimIncentives` uses for
+/// `Config::incentives.reward_denom`.
+pub fn claim_rewards(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let shares = SHARES.may_load(deps.storage, info.sender.clone())?.unwrap_or_default();
+    let distributed = DISTRIBUTED_REWARDS.may_load(deps.storage)?.unwrap_or_default();
+
+    let mut payout = vec![];
+    for coin in distributed.0.iter() {
+        let reward_per_share = EXTERNAL_REWARD_PER_SHARE
+            .may_load(deps.storage, coin.denom.clone())?
+            .unwrap_or_default();
+        let reward_debt = USER_EXTERNAL_REWARD_DEBT
+            .may_load(deps.storage, (info.sender.clone(), coin.denom.clone()))?
+            .unwrap_or_default();
+        let owed = pending_incentives(shares, reward_per_share, reward_debt)?;
+
+        USER_EXTERNAL_REWARD_DEBT.save(
+            deps.storage,
+            (info.sender.clone(), coin.denom.clone()),
+            &reward_per_share,
+        )?;
+
+        if !owed.is_zero() {
+            payout.push(Coin { denom: coin.denom.clone(), amount: owed });
+        }
+    }
+
+    let mut response = Response::new()
+        .add_attribute("action", "claim_rewards")
+        .add_attribute("claimant", info.sender.to_string());
+    if !payout.is_empty() {
+        response = response.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: payout,
+        });
+    }
+    Ok(response)
+}
+
+/// Admin-only: (re)configures the external contracts `CollectRewards` claims
+/// incentive emissions from. Applies immediately, the same as
+/// `SetOracleSources`, rather than going through the `UpdateConfig` timelock.
+/// `contracts: []` disables `CollectRewards`.
+pub fn set_reward_claim_contracts(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    contracts: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let reward_claim_contracts = contracts
+        .iter()
+        .map(|addr| deps.api.addr_validate(addr))
+        .collect::<Result<Vec<_>, _>>()?;
+    config.reward_claim_contracts = reward_claim_contracts;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_reward_claim_contracts")
+        .add_attribute("reward_claim_contracts", config.reward_claim_contracts.len().to_string()))
+}
+
+/// Admin-only: pays `DUST`'s accumulated whole-unit withdrawal-rounding
+/// remainder to `Config::fee_collector`, instead of leaving it to
+/// `withdraw` to fold into whichever call happens to burn the vault's last
+/// share. Zeroes `DUST` out; `DUST_REMAINDER`'s sub-unit fraction keeps
+/// accumulating from where it was.
+pub fn sweep_dust(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let collector = config.fee_collector.clone().ok_or(ContractError::NoFeeCollectorConfigured)?;
+
+    let dust = DUST.may_load(deps.storage)?.unwrap_or_default();
+    let mut amount = vec![];
+    if !dust.token_0.is_zero() {
+        amount.push(Coin { denom: config.pair_data.token_0.denom.clone(), amount: dust.token_0 });
+    }
+    if !dust.token_1.is_zero() {
+        amount.push(Coin { denom: config.pair_data.token_1.denom.clone(), amount: dust.token_1 });
+    }
+    DUST.save(deps.storage, &DustBalances::default())?;
+
+    let mut response = Response::new().add_attribute("action", "sweep_dust");
+    if !amount.is_empty() {
+        response = response.add_message(BankMsg::Send { to_address: collector.to_string(), amount });
+    }
+    Ok(response)
+}
+
+/// Re-syncs the cached allowed fee-tier set from the DEX module. Anyone may
+/// call this since it only ever narrows/widens the set to match the chain.
+pub fn refresh_fee_tiers(deps: DepsMut, _env: Env, _info: MessageInfo) -> Result<Response, ContractError> {
+    let allowed_fee_tiers = query_dex_fee_tiers(&deps.as_ref());
+    crate::state::ALLOWED_FEE_TIERS.save(deps.storage, &allowed_fee_tiers)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "refresh_fee_tiers")
+        .add_attribute("fee_tiers", format!("{:?}", allowed_fee_tiers)))
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    max_blocks_old: Option<u64>,
+    base_fee: Option<u64>,
+    base_deposit_percentage: Option<u64>,
+    ambient_fee: Option<u64>,
+    deposit_ambient: Option<bool>,
+    deposit_cap: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    // Load and verify owner
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Update max_blocks_old if provided
+    if let Some(blocks) = max_blocks_old {
+        if blocks > 2 {
+            return Err(ContractError::MalformedInput {
+                input: "max_block_old".to_string(),
+                reason: "must be <=2".to_string(),
+            });
+        }
+        config.max_blocks_old = blocks;
+    }
+
+    // Update base_fee if provided
+    if let Some(fee) = base_fee {
+        let allowed_fee_tiers = ALLOWED_FEE_TIERS
+            .may_load(deps.storage)?
+            .unwrap_or_else(|| crate::state::FALLBACK_FEE_TIERS.to_vec());
+        InstantiateMsg::validate_base_fee(fee, &allowed_fee_tiers)?;
+        config.base_fee = fee;
+    }
+
+    // Update base_deposit_percentage if provided
     if let Some(percentage) = base_deposit_percentage {
         InstantiateMsg::validate_base_deposit_percentage(percentage)?;
         config.base_deposit_percentage = percentage;
@@ -264,3 +2129,528 @@ pub fn update_config(
         .add_attribute("deposit_ambient", config.deposit_ambient.to_string())
         .add_attribute("deposit_cap", config.deposit_cap.to_string()))
 }
+
+/// Admin-only: adds `pair_data` to the vault-wide pair registry
+/// (`state::REGISTERED_PAIRS`), keyed by its `(denom_0, denom_1)`. Rejects a
+/// combination that's already registered rather than silently overwriting it.
+pub fn register_pair(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pair_data: crate::state::PairData,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = (pair_data.token_0.denom.clone(), pair_data.token_1.denom.clone());
+    if crate::state::REGISTERED_PAIRS.has(deps.storage, key.clone()) {
+        return Err(ContractError::PairAlreadyRegistered {
+            denom_0: key.0,
+            denom_1: key.1,
+        });
+    }
+    crate::state::REGISTERED_PAIRS.save(deps.storage, key.clone(), &pair_data)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_pair")
+        .add_attribute("denom_0", key.0)
+        .add_attribute("denom_1", key.1))
+}
+
+/// Admin-only: removes a previously `register_pair`-ed `denom_0`/`denom_1`
+/// combination from the registry.
+pub fn deregister_pair(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denom_0: String,
+    denom_1: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = (denom_0, denom_1);
+    if !crate::state::REGISTERED_PAIRS.has(deps.storage, key.clone()) {
+        return Err(ContractError::PairNotRegistered {
+            denom_0: key.0,
+            denom_1: key.1,
+        });
+    }
+    crate::state::REGISTERED_PAIRS.remove(deps.storage, key.clone());
+
+    Ok(Response::new()
+        .add_attribute("action", "deregister_pair")
+        .add_attribute("denom_0", key.0)
+        .add_attribute("denom_1", key.1))
+}
+
+/// Admin-only: stages `update` in `PENDING_CONFIG`, eligible to apply
+/// `Config::timelock_blocks` blocks from now. Rejects if an update is already
+/// staged rather than silently replacing it, so a second `UpdateConfig`
+/// doesn't quietly overwrite one still pending.
+pub fn update_config_timelocked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    update: crate::msg::ConfigOverride,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if config.config_frozen {
+        return Err(ContractError::ConfigFrozen);
+    }
+    if let Some(pending) = crate::state::PENDING_CONFIG.may_load(deps.storage)? {
+        return Err(ContractError::ConfigUpdateAlreadyPending {
+            effective_block: pending.effective_block,
+        });
+    }
+
+    let allowed_fee_tiers = crate::state::ALLOWED_FEE_TIERS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| crate::state::FALLBACK_FEE_TIERS.to_vec());
+    update.validate(&allowed_fee_tiers)?;
+
+    let effective_block = env.block.height + config.timelock_blocks;
+    crate::state::PENDING_CONFIG.save(
+        deps.storage,
+        &crate::state::PendingConfigUpdate {
+            update,
+            effective_block,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("effective_block", effective_block.to_string()))
+}
+
+/// Admin-only: applies the staged `PENDING_CONFIG` update once
+/// `effective_block` has been reached, snapshotting the pre-update `Config`
+/// into `PREVIOUS_CONFIG` first so `RevertConfig` can undo it.
+pub fn commit_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let pending = crate::state::PENDING_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingConfigUpdate)?;
+    if env.block.height < pending.effective_block {
+        return Err(ContractError::TimelockNotElapsed {
+            current_block: env.block.height,
+            effective_block: pending.effective_block,
+        });
+    }
+
+    let allowed_fee_tiers = ALLOWED_FEE_TIERS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| crate::state::FALLBACK_FEE_TIERS.to_vec());
+
+    crate::state::PREVIOUS_CONFIG.save(deps.storage, &config)?;
+    let diff_attributes = pending.update.diff_attributes(&config);
+    pending.update.apply_to(&mut config);
+    config.validate(&allowed_fee_tiers)?;
+    CONFIG.save(deps.storage, &config)?;
+    crate::state::CONFIG_HISTORY.save(deps.storage, env.block.height, &config)?;
+    crate::state::PENDING_CONFIG.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "commit_config")
+        .add_attributes(diff_attributes))
+}
+
+/// Admin-only: discards the staged `PENDING_CONFIG` update without applying it.
+pub fn cancel_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if crate::state::PENDING_CONFIG.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingConfigUpdate);
+    }
+    crate::state::PENDING_CONFIG.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_config"))
+}
+
+/// Admin-only: restores `Config` from `PREVIOUS_CONFIG`, one step of rollback
+/// for the most recently committed `UpdateConfig`. Leaves `PREVIOUS_CONFIG` in
+/// place afterward (rather than clearing it) since restoring is idempotent
+/// and non-destructive to call again.
+pub fn revert_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let previous = crate::state::PREVIOUS_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPreviousConfig)?;
+    CONFIG.save(deps.storage, &previous)?;
+    crate::state::CONFIG_HISTORY.save(deps.storage, env.block.height, &previous)?;
+
+    Ok(Response::new().add_attribute("action", "revert_config"))
+}
+
+/// Admin-only, one-way: sets `Config::config_frozen`, after which
+/// `update_config_timelocked` rejects every future `ExecuteMsg::UpdateConfig`
+/// with `ContractError::ConfigFrozen`. Does not disturb any update already
+/// staged in `PENDING_CONFIG` - `CommitConfig`/`CancelConfig` on it still
+/// work normally. Idempotent: freezing an already-frozen config is a no-op.
+pub fn freeze_config(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    config.config_frozen = true;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "freeze_config"))
+}
+
+/// Admin-only: (re)configures the additional `oracle_contracts` queried
+/// alongside the primary x/oracle feed, the `min_sources` quorum
+/// `get_prices` requires from them, and the `max_oracle_deviation_bps` guard
+/// against any one source straying too far from their median. Applies
+/// immediately, the same as `SetIncentives`/`SetPerformanceFee`, rather than
+/// going through the `UpdateConfig` timelock.
+pub fn set_oracle_sources(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    oracle_contracts: Vec<String>,
+    min_sources: u64,
+    max_oracle_deviation_bps: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    InstantiateMsg::validate_oracle_sources(&oracle_contracts, min_sources)?;
+
+    let oracle_contracts = oracle_contracts
+        .iter()
+        .map(|addr| deps.api.addr_validate(addr))
+        .collect::<Result<Vec<_>, _>>()?;
+    config.oracle_contracts = oracle_contracts;
+    config.min_sources = min_sources;
+    config.max_oracle_deviation_bps = max_oracle_deviation_bps;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_oracle_sources")
+        .add_attribute("oracle_contracts", config.oracle_contracts.len().to_string())
+        .add_attribute("min_sources", config.min_sources.to_string())
+        .add_attribute("max_oracle_deviation_bps", config.max_oracle_deviation_bps.to_string()))
+}
+
+/// Admin-only: (re)configures `get_prices`'s LST redemption-rate adapter.
+/// Applies immediately, the same as `SetOracleSources`, rather than going
+/// through the `UpdateConfig` timelock. `adapter: None` disables the
+/// adjustment regardless of `lst_asset_denom`.
+#[allow(clippy::too_many_arguments)]
+pub fn set_redemption_adapter(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    source: Option<RedemptionRateSourceInput>,
+    lst_asset_denom: Option<String>,
+    min_redemption_rate: Option<PrecDec>,
+    max_redemption_rate: Option<PrecDec>,
+    max_redemption_rate_change_bps: Option<u64>,
+    max_rate_age_seconds: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let redemption_adapter = match source {
+        Some(source) => {
+            let lst_asset_denom = lst_asset_denom.ok_or_else(|| ContractError::MalformedInput {
+                input: "lst_asset_denom".to_string(),
+                reason: "must be set when source is set".to_string(),
+            })?;
+            InstantiateMsg::validate_redemption_adapter(
+                &Some(source.clone()),
+                &Some(lst_asset_denom.clone()),
+                &min_redemption_rate,
+                &max_redemption_rate,
+                &max_redemption_rate_change_bps,
+                &max_rate_age_seconds,
+                &config.pair_data.token_0.denom,
+                &config.pair_data.token_1.denom,
+            )?;
+            Some(crate::state::RedemptionAdapterConfig {
+                lst_asset_denom,
+                source: source.validate(deps.api)?,
+                min_redemption_rate: min_redemption_rate.unwrap(),
+                max_redemption_rate: max_redemption_rate.unwrap(),
+                max_redemption_rate_change_bps: max_redemption_rate_change_bps.unwrap(),
+                max_rate_age_seconds: max_rate_age_seconds.unwrap(),
+            })
+        }
+        None => None,
+    };
+    config.redemption_adapter = redemption_adapter;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_redemption_adapter")
+        .add_attribute(
+            "redemption_adapter",
+            config
+                .redemption_adapter
+                .map(|cfg| crate::utils::redemption_rate_source_label(&cfg.source))
+                .unwrap_or_default(),
+        ))
+}
+
+// admin/cron-only: refreshes `state::APY_EMA`'s smoothed running average for
+// `instance` from `apy_contract`'s `ApySourceQueryMsg::GetApy`, rejecting a
+// response older than `max_blocks_old` so a dead quote can't rebalance
+// `query_calculated_fee_tiers`'s fee tiers. See `state::APY_EMA` for why
+// `alpha` travels with the call instead of living in `Config`.
+pub fn execute_update_apy_ema(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    apy_contract: String,
+    instance: String,
+    time_span_hours: u64,
+    alpha: PrecDec,
+    max_blocks_old: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let cron_address = Addr::unchecked(CRON_MODULE_ADDRESS);
+    if info.sender != config.admin && info.sender != cron_address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if alpha <= PrecDec::zero() || alpha > PrecDec::one() {
+        return Err(ContractError::MalformedInput {
+            input: "alpha".to_string(),
+            reason: "must be in (0, 1]".to_string(),
+        });
+    }
+
+    let apy_contract = deps.api.addr_validate(&apy_contract)?;
+    let response: ApyResponse = deps.querier.query_wasm_smart(
+        apy_contract,
+        &ApySourceQueryMsg::GetApy {
+            instance: instance.clone(),
+            time_span_hours,
+        },
+    )?;
+    let age = env.block.height.saturating_sub(response.block_height);
+    if age > max_blocks_old {
+        return Err(ContractError::ApyTooOld {
+            instance,
+            max_blocks: max_blocks_old,
+        });
+    }
+
+    let prev = APY_EMA.may_load(deps.storage, instance.clone())?;
+    let ema_apy = match prev {
+        Some(cache) => alpha * response.apy + (PrecDec::one() - alpha) * cache.ema_apy,
+        None => response.apy,
+    };
+    APY_EMA.save(
+        deps.storage,
+        instance.clone(),
+        &ApyEmaCache {
+            ema_apy,
+            alpha,
+            last_block: env.block.height,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_apy_ema")
+        .add_attribute("instance", instance)
+        .add_attribute("raw_apy", response.apy.to_string())
+        .add_attribute("ema_apy", ema_apy.to_string()))
+}
+
+/// Admin-only: (re)configures `Config::signers`/`Config::threshold`.
+pub fn set_signers(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    signers: Vec<String>,
+    threshold: u32,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    InstantiateMsg::validate_signers(&signers, threshold)?;
+
+    config.signers = signers
+        .iter()
+        .map(|addr| deps.api.addr_validate(addr))
+        .collect::<Result<Vec<_>, _>>()?;
+    config.threshold = threshold;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_signers")
+        .add_attribute("signers", config.signers.len().to_string())
+        .add_attribute("threshold", config.threshold.to_string()))
+}
+
+/// Signer-gated: stages `update` as a new `PROPOSALS` entry, counting the
+/// proposer's own approval immediately.
+pub fn propose_config_update(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    update: crate::msg::ConfigOverride,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.signers.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let allowed_fee_tiers = crate::state::ALLOWED_FEE_TIERS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| crate::state::FALLBACK_FEE_TIERS.to_vec());
+    update.validate(&allowed_fee_tiers)?;
+
+    let id = PROPOSAL_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+    PROPOSAL_SEQ.save(deps.storage, &id)?;
+    PROPOSALS.save(
+        deps.storage,
+        id,
+        &ConfigProposal {
+            update,
+            approvals: vec![info.sender],
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_config_update")
+        .add_attribute("proposal_id", id.to_string()))
+}
+
+/// Signer-gated: records the sender's approval of `PROPOSALS[id]`. Approving
+/// twice from the same address is a no-op rather than double-counting.
+pub fn approve_config_update(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.signers.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut proposal = PROPOSALS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::ProposalNotFound { id })?;
+    if !proposal.approvals.contains(&info.sender) {
+        proposal.approvals.push(info.sender);
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "approve_config_update")
+        .add_attribute("proposal_id", id.to_string())
+        .add_attribute("approvals", proposal.approvals.len().to_string()))
+}
+
+/// Signer-gated: applies `PROPOSALS[id]`'s staged `update` once its
+/// approvals reach `Config::threshold`, the same `apply_to`/`Config::
+/// validate`/`CONFIG_HISTORY` path `commit_config` uses for the timelocked
+/// single-admin flow, then removes the entry.
+///
+/// Approvals are re-checked against the *current* `config.signers` here
+/// rather than trusted at face value: `approve_config_update` only ever
+/// validated membership at the moment each approval was recorded, so a
+/// proposal approved to threshold under one signer set stays sitting in
+/// `PROPOSALS` as-is through a later `set_signers` rotation. Counting only
+/// approvals still held by a current signer means a rotation that drops a
+/// proposal's approvers below threshold makes it unexecutable again without
+/// requiring `set_signers` itself to reach into `PROPOSALS` and reconcile
+/// every open entry.
+pub fn execute_config_update(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if !config.signers.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let proposal = PROPOSALS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::ProposalNotFound { id })?;
+    let approvals = proposal
+        .approvals
+        .iter()
+        .filter(|addr| config.signers.contains(addr))
+        .count() as u32;
+    if approvals < config.threshold {
+        return Err(ContractError::ThresholdNotMet {
+            id,
+            approvals,
+            threshold: config.threshold,
+        });
+    }
+
+    let allowed_fee_tiers = crate::state::ALLOWED_FEE_TIERS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| crate::state::FALLBACK_FEE_TIERS.to_vec());
+    let diff_attributes = proposal.update.diff_attributes(&config);
+    proposal.update.apply_to(&mut config);
+    config.validate(&allowed_fee_tiers)?;
+    CONFIG.save(deps.storage, &config)?;
+    crate::state::CONFIG_HISTORY.save(deps.storage, env.block.height, &config)?;
+    PROPOSALS.remove(deps.storage, id);
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_config_update")
+        .add_attribute("proposal_id", id.to_string())
+        .add_attributes(diff_attributes))
+}
+
+/// Signer-gated: discards `PROPOSALS[id]` without applying it. The only way
+/// to retire a stale or unwanted proposal before it reaches `Config::
+/// threshold` - e.g. after a `set_signers` rotation drops its remaining
+/// approvers below threshold anyway, or a signer simply changes their mind -
+/// short of letting it sit unexecuted forever.
+pub fn cancel_config_update(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.signers.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if PROPOSALS.may_load(deps.storage, id)?.is_none() {
+        return Err(ContractError::ProposalNotFound { id });
+    }
+    PROPOSALS.remove(deps.storage, id);
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_config_update")
+        .add_attribute("proposal_id", id.to_string()))
+}
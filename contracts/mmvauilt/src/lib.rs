@@ -1,11 +1,31 @@
+pub mod checked_math;
 pub mod contract;
 pub mod error;
 pub mod execute;
+pub mod migrations;
 pub mod msg;
+pub mod permit;
+pub mod quantity;
 pub mod query;
+pub mod spread_curve;
+pub mod stableswap;
 pub mod state;
+pub mod twap;
 pub mod utils;
+pub mod volatility;
 
 #[cfg(test)]
 #[path = "./tests/utils_tests.rs"]
 pub mod utils_tests;
+
+#[cfg(test)]
+#[path = "./tests/quantity_tests.rs"]
+pub mod quantity_tests;
+
+#[cfg(test)]
+#[path = "./tests/invariants_tests.rs"]
+pub mod invariants_tests;
+
+#[cfg(test)]
+#[path = "./tests/sequential_deposit_tests.rs"]
+pub mod sequential_deposit_tests;
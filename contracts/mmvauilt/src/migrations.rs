@@ -0,0 +1,366 @@
+use crate::error::{ContractError, ContractResult};
+use crate::msg::MigrateMsg;
+use crate::state::{
+    Balances, Config, ContractStatus, DepositBandConfig, FeeSplitterConfig, IncentiveConfig,
+    PairData, TokenData, ALLOWED_FEE_TIERS, CONFIG, FALLBACK_FEE_TIERS,
+};
+use crate::utils::default_stable_denoms;
+use cosmwasm_std::{Addr, Coin, Decimal, DepsMut, Uint128};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Item;
+use neutron_std::types::slinky::types::v1::CurrencyPair;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// `TokenData` as it was stored before `price_path` existed. Deserialized
+/// only by [`v0_1_0_to_v0_2_0`] via `PairDataV1`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+struct TokenDataV1 {
+    denom: String,
+    decimals: u8,
+    pair: CurrencyPair,
+}
+
+/// `PairData` as it was stored before `TokenData` gained `price_path`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+struct PairDataV1 {
+    token_0: TokenDataV1,
+    token_1: TokenDataV1,
+    pair_id: String,
+}
+
+impl TokenDataV1 {
+    /// A pre-`price_path`/`max_price_age_seconds`/`aggregation` token never
+    /// routed through an intermediate, had no wall-clock staleness bound, and
+    /// priced off a single feed, so all three backfill to their disabled
+    /// defaults (empty path, `0` seconds, `None`).
+    fn upgrade(self) -> TokenData {
+        TokenData {
+            denom: self.denom,
+            decimals: self.decimals,
+            pair: self.pair,
+            price_path: vec![],
+            max_price_age_seconds: 0,
+            aggregation: None,
+        }
+    }
+}
+
+/// `Config` as it was stored before `swap_fee_bps`, `staking_target`,
+/// `unbonding_period_seconds`, `target_rate_amortization_seconds`,
+/// `book_aware_valuation`, `price_ema_tau_seconds`, `max_price_deviation_bps`,
+/// `change_limiter`, `per_address_cap`, `dynamic_spread_cap`,
+/// `cw20_token_0`/`cw20_token_1`, `withdrawal_queue_period_seconds`,
+/// `fee_tiers`, `volatility_spread`, `timelock_blocks`,
+/// `oracle_contracts`/`min_sources`/`max_oracle_deviation_bps`, `twap_window_seconds`/
+/// `max_twap_deviation_bps`, `pause_block`, `redemption_adapter`,
+/// `management_fee_bps`/`fee_collector`, `max_total_shares`,
+/// `max_target_rate_deviation_bps`, `target_rate_max_drift_bps`, `market_making`,
+/// `reward_claim_contracts`, `max_price_jump_bps`, `stable_denoms`,
+/// `config_frozen`, `min_dex_deposit_interval_seconds`,
+/// `stableswap_amplification`, `dex_deviation_bps`/
+/// `dex_deviation_cooldown_blocks`, `min_deposit_amount_0`/
+/// `min_deposit_amount_1`/`min_rebalance_amount_0`/`min_rebalance_amount_1`,
+/// `rebalance_strategy`, `price_divergence_fallback`, `deposit_curve`,
+/// `max_oracle_price_skew_ticks`, and the graduated `status`/`status_reason` killswitch (which replaced the old
+/// boolean `paused`) existed, and before `TokenData` gained `price_path`/
+/// `max_price_age_seconds`.
+/// Deserialized only by [`v0_1_0_to_v0_2_0`] to recover a pre-upgrade
+/// vault's stored `Config` so the new fields can be backfilled with safe
+/// defaults rather than requiring the caller to hand in a full replacement
+/// struct.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+struct ConfigV1 {
+    pair_data: PairDataV1,
+    max_blocks_old: u64,
+    balances: Balances,
+    base_fee: u64,
+    base_deposit_percentage: u64,
+    ambient_fee: u64,
+    deposit_ambient: bool,
+    owner: Addr,
+    deposit_cap: Uint128,
+    total_shares: Uint128,
+    admin: Addr,
+    paused: bool,
+    withdrawal_limit_token_0: Option<Uint128>,
+    withdrawal_limit_token_1: Option<Uint128>,
+    max_slippage_bps: u64,
+    incentives: Option<IncentiveConfig>,
+    ema_alpha: Decimal,
+    ema_max_deviation_bps: u64,
+    ema_fallback: bool,
+    target_rate_provider: Option<Addr>,
+    target_rate_max_blocks_old: u64,
+    fee_splitter: Option<FeeSplitterConfig>,
+    skew: bool,
+    imbalance_bps: u64,
+    oracle_price_skew: i32,
+    max_ema_age_seconds: u64,
+    max_conf_ratio_bps: Option<u64>,
+    deposit_band: Option<DepositBandConfig>,
+    rebalance_threshold_bps: Option<u64>,
+    rebalance_target_bps: u64,
+    max_rebalance_ticks: u64,
+    max_rebalance_slippage_bps: u64,
+    performance_fee_bps: u64,
+}
+
+/// Same storage key as `CONFIG`, used only to read a pre-`swap_fee_bps`
+/// `Config` under its old shape.
+const CONFIG_V1: Item<ConfigV1> = Item::new("data");
+
+fn v0_1_0_to_v0_2_0(deps: &mut DepsMut) -> ContractResult<()> {
+    let old = CONFIG_V1.load(deps.storage)?;
+    let token_0_denom = old.balances.token_0.denom.clone();
+    let token_1_denom = old.balances.token_1.denom.clone();
+    let pair_data = PairData {
+        token_0: old.pair_data.token_0.upgrade(),
+        token_1: old.pair_data.token_1.upgrade(),
+        pair_id: old.pair_data.pair_id,
+    };
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            pair_data,
+            max_blocks_old: old.max_blocks_old,
+            balances: old.balances,
+            base_fee: old.base_fee,
+            base_deposit_percentage: old.base_deposit_percentage,
+            ambient_fee: old.ambient_fee,
+            deposit_ambient: old.deposit_ambient,
+            owner: old.owner,
+            deposit_cap: old.deposit_cap,
+            total_shares: old.total_shares,
+            admin: old.admin,
+            // old `paused` only ever gated `Deposit`/`DexDeposit`, the same
+            // scope as the new `DepositsFrozen` level, not a full `Frozen`.
+            status: if old.paused {
+                ContractStatus::DepositsFrozen
+            } else {
+                ContractStatus::Operational
+            },
+            status_reason: None,
+            // a freshly-upgraded vault has never been auto-paused by
+            // `get_prices_with_fallback`.
+            pause_block: None,
+            withdrawal_limit_token_0: old.withdrawal_limit_token_0,
+            withdrawal_limit_token_1: old.withdrawal_limit_token_1,
+            max_slippage_bps: old.max_slippage_bps,
+            incentives: old.incentives,
+            ema_alpha: old.ema_alpha,
+            ema_max_deviation_bps: old.ema_max_deviation_bps,
+            ema_fallback: old.ema_fallback,
+            target_rate_provider: old.target_rate_provider,
+            target_rate_max_blocks_old: old.target_rate_max_blocks_old,
+            fee_splitter: old.fee_splitter,
+            // a freshly-upgraded vault has no earned fees pending yet; the
+            // next `dex_withdrawal` reply starts crediting this normally.
+            accrued_fees: Balances {
+                token_0: Coin::new(Uint128::zero(), token_0_denom),
+                token_1: Coin::new(Uint128::zero(), token_1_denom),
+            },
+            skew: old.skew,
+            imbalance_bps: old.imbalance_bps,
+            oracle_price_skew: old.oracle_price_skew,
+            max_ema_age_seconds: old.max_ema_age_seconds,
+            max_conf_ratio_bps: old.max_conf_ratio_bps,
+            deposit_band: old.deposit_band,
+            rebalance_threshold_bps: old.rebalance_threshold_bps,
+            rebalance_target_bps: old.rebalance_target_bps,
+            max_rebalance_ticks: old.max_rebalance_ticks,
+            max_rebalance_slippage_bps: old.max_rebalance_slippage_bps,
+            performance_fee_bps: old.performance_fee_bps,
+            swap_fee_bps: 0,
+            staking_target: None,
+            unbonding_period_seconds: 0,
+            target_rate_amortization_seconds: 0,
+            book_aware_valuation: false,
+            price_ema_tau_seconds: 0,
+            max_price_deviation_bps: 0,
+            // a freshly-upgraded vault keeps the pre-existing hard-fail
+            // behavior until the admin opts into falling back to the EMA
+            // price via `MigrateMsg::config_override`
+            price_divergence_fallback: false,
+            change_limiter: None,
+            per_address_cap: None,
+            dynamic_spread_cap: 0,
+            cw20_token_0: None,
+            cw20_token_1: None,
+            withdrawal_queue_period_seconds: 0,
+            fee_tiers: Vec::new(),
+            // a freshly-upgraded vault keeps the pre-existing fixed-offset
+            // tier placement until the admin opts into the x*y=k curve via
+            // `MigrateMsg::config_override`
+            deposit_curve: crate::state::DepositCurve::Linear,
+            volatility_spread: None,
+            // a freshly-upgraded vault has no staged update to wait out, so a
+            // `0` default is safe: it only takes effect once the admin starts
+            // using `UpdateConfig`, at which point they set a real value
+            timelock_blocks: 0,
+            // empty keeps `get_prices` on the pre-existing single-feed
+            // behavior until the admin opts in via `SetOracleSources`
+            oracle_contracts: Vec::new(),
+            min_sources: 0,
+            // disabled by default; only meaningful once the admin configures
+            // `oracle_contracts` via `SetOracleSources`
+            max_oracle_deviation_bps: 0,
+            // disabled by default; a freshly-upgraded vault has no
+            // `LAST_ACCEPTED_PAIR_PRICE` snapshot yet for the guard to compare
+            // against anyway
+            max_price_jump_bps: 0,
+            // preserves the pre-upgrade hardcoded USD/USDC-priced-at-1.0
+            // behavior exactly
+            stable_denoms: default_stable_denoms(),
+            // a safe non-zero default window; `max_twap_deviation_bps: 0`
+            // below leaves the guard itself disabled until the admin
+            // configures a real threshold.
+            twap_window_seconds: 3600,
+            max_twap_deviation_bps: 0,
+            // a freshly-upgraded vault has no adapter configured until the
+            // admin opts in via `SetRedemptionAdapter`
+            redemption_adapter: None,
+            // a freshly-upgraded vault charges no management fee until the
+            // admin opts in via `SetManagementFee`
+            management_fee_bps: 0,
+            fee_collector: None,
+            // a freshly-upgraded vault has no supply cap until the admin
+            // opts in via `MigrateMsg::config_override`
+            max_total_shares: None,
+            // a freshly-upgraded vault's `target_rate_provider` (if any) was
+            // already trusted with no deviation check, so `0` preserves that
+            // behavior until the admin opts in via `MigrateMsg::config_override`
+            max_target_rate_deviation_bps: 0,
+            // a freshly-upgraded vault's `target_rate_provider` (if any) was
+            // already trusted with no drift check, so `0` preserves that
+            // behavior until the admin opts in via `MigrateMsg::config_override`
+            target_rate_max_drift_bps: 0,
+            // a freshly-upgraded vault keeps quoting the pre-existing
+            // `MsgDeposit` pooling strategy; `market_making` is instantiate-
+            // only, the same convention as `deposit_band`/`volatility_spread`
+            market_making: None,
+            // a freshly-upgraded vault has no external reward source
+            // configured until the admin opts in via
+            // `ExecuteMsg::SetRewardClaimContracts`
+            reward_claim_contracts: Vec::new(),
+            // a freshly-upgraded vault has never had `ExecuteMsg::FreezeConfig`
+            // called on it
+            config_frozen: false,
+            // disabled by default; only meaningful once the admin opts in via
+            // `MigrateMsg::config_override`
+            min_dex_deposit_interval_seconds: 0,
+            // disabled by default; a freshly-upgraded vault keeps pricing
+            // off the plain oracle ratio until the admin opts in via
+            // `MigrateMsg::config_override`
+            stableswap_amplification: 0,
+            // disabled by default; a freshly-upgraded vault keeps depositing
+            // without the circuit breaker until the admin opts in via
+            // `MigrateMsg::config_override`
+            dex_deviation_bps: 0,
+            dex_deviation_cooldown_blocks: 0,
+            // a freshly-upgraded vault keeps the pre-existing fixed
+            // `Uint128::new(10)` dust guard/unconditional-nonzero-swap
+            // behavior until the admin opts into a real floor via
+            // `MigrateMsg::config_override`
+            min_deposit_amount_0: Uint128::zero(),
+            min_deposit_amount_1: Uint128::zero(),
+            min_rebalance_amount_0: Uint128::zero(),
+            min_rebalance_amount_1: Uint128::zero(),
+            // a freshly-upgraded vault keeps the pre-existing "offer the
+            // full idle balance of both sides" clearing policy until the
+            // admin opts into a different one via `MigrateMsg::config_override`
+            rebalance_strategy: crate::state::RebalanceStrategy::Balanced,
+            // wide open by default so a pre-existing `oracle_price_skew`
+            // (never previously bounded) can't suddenly fail `Config::validate`
+            // on upgrade; the admin tightens this via `MigrateMsg::config_override`
+            max_oracle_price_skew_ticks: u32::MAX,
+            // a freshly-upgraded vault keeps the pre-existing single-admin
+            // `UpdateConfig`/`CommitConfig` authority as its only config-change
+            // path until the admin opts into the `PROPOSALS` quorum flow via
+            // `ExecuteMsg::SetSigners`
+            signers: vec![],
+            threshold: 0,
+            // a pre-existing vault already has a deployed position at
+            // whatever tick/tiers it last ran with; `0` preserves the
+            // pre-existing behavior of always redeploying on the next
+            // `dex_deposit` rather than silently skipping it
+            rebalance_drift_tolerance_ticks: 0,
+        },
+    )?;
+    Ok(())
+}
+
+/// Ordered chain of in-place `Config`/`PairData` transforms, keyed by the
+/// `cw2` version each step upgrades *from* and *to*. New fields land here as
+/// a new `(from, to, step)` entry rather than in `InstantiateMsg`/`migrate`
+/// plumbing.
+const MIGRATIONS: &[(&str, &str, fn(&mut DepsMut) -> ContractResult<()>)] =
+    &[("0.1.0", "0.2.0", v0_1_0_to_v0_2_0)];
+
+/// Runs the ordered migration chain from the version stored by `cw2`, then
+/// applies `msg`'s optional targeted-field override on top, before recording
+/// `contract_version` as the new stored version. Rejects a stored contract
+/// name other than `contract_name`, a stored version newer than
+/// `contract_version` (a downgrade), and a stored version that isn't already
+/// `contract_version` and never appears as a `from` in `MIGRATIONS` (no
+/// known path forward) before touching any state. Returns the
+/// `(from_version, to_version)` pair so the caller can report it, even when
+/// `from_version == to_version` because no migration step ran.
+pub fn run(
+    mut deps: DepsMut,
+    contract_name: &str,
+    contract_version: &str,
+    msg: MigrateMsg,
+) -> ContractResult<(String, String)> {
+    let prev = get_contract_version(deps.storage)?;
+    if prev.contract != contract_name {
+        return Err(ContractError::MigrateWrongContract {
+            expected: contract_name.to_string(),
+            found: prev.contract,
+        });
+    }
+    if version_key(&prev.version) > version_key(contract_version) {
+        return Err(ContractError::MigrateDowngrade {
+            from: prev.version,
+            to: contract_version.to_string(),
+        });
+    }
+
+    let from_version = prev.version;
+    let mut version = from_version.clone();
+    while let Some((_, to, step)) = MIGRATIONS.iter().find(|(from, _, _)| *from == version) {
+        step(&mut deps)?;
+        version = to.to_string();
+    }
+
+    // The chain must land exactly on `contract_version`; a stored version
+    // that never appears as a `from` in `MIGRATIONS` (and isn't already the
+    // target) has no path forward and would otherwise be stamped as
+    // up-to-date without ever running the transform it needs.
+    if version != contract_version {
+        return Err(ContractError::MigrateUnknownVersion {
+            version,
+            target: contract_version.to_string(),
+        });
+    }
+
+    if let Some(config_override) = msg.config_override {
+        let allowed_fee_tiers = ALLOWED_FEE_TIERS
+            .may_load(deps.storage)?
+            .unwrap_or_else(|| FALLBACK_FEE_TIERS.to_vec());
+        config_override.validate(&allowed_fee_tiers)?;
+        let mut config = CONFIG.load(deps.storage)?;
+        config_override.apply_to(&mut config);
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    set_contract_version(deps.storage, contract_name, contract_version)?;
+    Ok((from_version, contract_version.to_string()))
+}
+
+fn version_key(version: &str) -> Vec<u64> {
+    version.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+}
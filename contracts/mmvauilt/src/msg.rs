@@ -1,8 +1,15 @@
 use crate::{
     error::{ContractError, ContractResult},
-    state::TokenData,
+    state::{
+        ChangeLimiterConfig, ContractStatus, DepositBandConfig, FailedDeposit, FeeTier,
+        MarketMakingConfig, PositionNft, StableDenomConfig, TokenData, UnbondingEntry,
+        VolatilitySpreadConfig,
+    },
 };
-use cosmwasm_std::{Coin, Decimal, Response, Uint128};
+use cosmwasm_schema::QueryResponses;
+use cosmwasm_std::{Coin, CosmosMsg, Decimal, Response, Uint128};
+use cw20::Cw20ReceiveMsg;
+use neutron_std::types::neutron::util::precdec::PrecDec;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -19,7 +26,485 @@ pub struct ReceiveFunds {}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// emergency escape hatch: after the version-gated migration chain runs,
+    /// overwrites only the named `Config` fields (validated the same way
+    /// `InstantiateMsg` validates them) instead of requiring a full
+    /// replacement `Config` that could brick the vault if malformed. `None`
+    /// for an ordinary version upgrade.
+    pub config_override: Option<ConfigOverride>,
+}
+
+/// Targeted `Config` field overrides applied by `MigrateMsg`. Every field is
+/// optional and left untouched when `None`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigOverride {
+    pub base_fee: Option<u64>,
+    pub status: Option<ContractStatus>,
+    pub imbalance_bps: Option<u64>,
+    pub rebalance_target_bps: Option<u64>,
+    pub performance_fee_bps: Option<u64>,
+    pub management_fee_bps: Option<u64>,
+    pub max_total_shares: Option<Uint128>,
+    pub swap_fee_bps: Option<u64>,
+    pub skew: Option<bool>,
+    pub oracle_price_skew: Option<i32>,
+    pub dynamic_spread_cap: Option<u64>,
+    pub deposit_cap: Option<Uint128>,
+    pub max_blocks_old: Option<u64>,
+    pub fee_tiers: Option<Vec<FeeTier>>,
+    /// see `InstantiateMsg::deposit_curve`.
+    pub deposit_curve: Option<crate::state::DepositCurve>,
+    /// see `InstantiateMsg::price_ema_tau_seconds`.
+    pub price_ema_tau_seconds: Option<u64>,
+    /// see `InstantiateMsg::max_price_deviation_bps`.
+    pub max_price_deviation_bps: Option<u64>,
+    /// see `InstantiateMsg::price_divergence_fallback`.
+    pub price_divergence_fallback: Option<bool>,
+    /// see `InstantiateMsg::ema_max_deviation_bps`.
+    pub ema_max_deviation_bps: Option<u64>,
+    /// see `InstantiateMsg::max_target_rate_deviation_bps`.
+    pub max_target_rate_deviation_bps: Option<u64>,
+    /// see `InstantiateMsg::target_rate_max_drift_bps`.
+    pub target_rate_max_drift_bps: Option<u64>,
+    /// see `InstantiateMsg::min_dex_deposit_interval_seconds`.
+    pub min_dex_deposit_interval_seconds: Option<u64>,
+    /// see `InstantiateMsg::stableswap_amplification`.
+    pub stableswap_amplification: Option<u64>,
+    /// see `Config::dex_deviation_bps`.
+    pub dex_deviation_bps: Option<u64>,
+    /// see `Config::dex_deviation_cooldown_blocks`.
+    pub dex_deviation_cooldown_blocks: Option<u64>,
+    /// see `Config::min_deposit_amount_0`.
+    pub min_deposit_amount_0: Option<Uint128>,
+    /// see `Config::min_deposit_amount_1`.
+    pub min_deposit_amount_1: Option<Uint128>,
+    /// see `Config::min_rebalance_amount_0`.
+    pub min_rebalance_amount_0: Option<Uint128>,
+    /// see `Config::min_rebalance_amount_1`.
+    pub min_rebalance_amount_1: Option<Uint128>,
+    /// see `Config::rebalance_strategy`.
+    pub rebalance_strategy: Option<crate::state::RebalanceStrategy>,
+    /// see `Config::max_oracle_price_skew_ticks`.
+    pub max_oracle_price_skew_ticks: Option<u32>,
+}
+
+impl ConfigOverride {
+    pub fn validate(&self, allowed_fee_tiers: &[u64]) -> ContractResult<()> {
+        if let Some(base_fee) = self.base_fee {
+            InstantiateMsg::validate_base_fee(base_fee, allowed_fee_tiers)?;
+        }
+        if let Some(imbalance_bps) = self.imbalance_bps {
+            InstantiateMsg::validate_imbalance_bps(imbalance_bps)?;
+        }
+        if let Some(rebalance_target_bps) = self.rebalance_target_bps {
+            InstantiateMsg::validate_rebalance_target_bps(rebalance_target_bps)?;
+        }
+        if let Some(performance_fee_bps) = self.performance_fee_bps {
+            InstantiateMsg::validate_performance_fee_bps(performance_fee_bps)?;
+        }
+        if let Some(management_fee_bps) = self.management_fee_bps {
+            InstantiateMsg::validate_management_fee_bps(management_fee_bps)?;
+        }
+        if let Some(swap_fee_bps) = self.swap_fee_bps {
+            InstantiateMsg::validate_swap_fee_bps(swap_fee_bps)?;
+        }
+        if let Some(dynamic_spread_cap) = self.dynamic_spread_cap {
+            InstantiateMsg::validate_dynamic_spread_cap(dynamic_spread_cap)?;
+        }
+        if let Some(max_blocks_old) = self.max_blocks_old {
+            if max_blocks_old == 0 {
+                return Err(ContractError::MalformedInput {
+                    input: "max_blocks_old".to_string(),
+                    reason: "must be >=1".to_string(),
+                });
+            }
+        }
+        if let Some(fee_tiers) = &self.fee_tiers {
+            InstantiateMsg::validate_fee_tiers(fee_tiers)?;
+            for tier in fee_tiers {
+                if !allowed_fee_tiers.contains(&tier.fee) {
+                    return Err(ContractError::InvalidBaseFee { fee: tier.fee });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn apply_to(&self, config: &mut crate::state::Config) {
+        if let Some(base_fee) = self.base_fee {
+            config.base_fee = base_fee;
+        }
+        if let Some(status) = self.status.clone() {
+            config.status = status;
+        }
+        if let Some(imbalance_bps) = self.imbalance_bps {
+            config.imbalance_bps = imbalance_bps;
+        }
+        if let Some(rebalance_target_bps) = self.rebalance_target_bps {
+            config.rebalance_target_bps = rebalance_target_bps;
+        }
+        if let Some(performance_fee_bps) = self.performance_fee_bps {
+            config.performance_fee_bps = performance_fee_bps;
+        }
+        if let Some(management_fee_bps) = self.management_fee_bps {
+            config.management_fee_bps = management_fee_bps;
+        }
+        if let Some(max_total_shares) = self.max_total_shares {
+            config.max_total_shares = Some(max_total_shares);
+        }
+        if let Some(swap_fee_bps) = self.swap_fee_bps {
+            config.swap_fee_bps = swap_fee_bps;
+        }
+        if let Some(skew) = self.skew {
+            config.skew = skew;
+        }
+        if let Some(oracle_price_skew) = self.oracle_price_skew {
+            config.oracle_price_skew = oracle_price_skew;
+        }
+        if let Some(dynamic_spread_cap) = self.dynamic_spread_cap {
+            config.dynamic_spread_cap = dynamic_spread_cap;
+        }
+        if let Some(deposit_cap) = self.deposit_cap {
+            config.deposit_cap = deposit_cap;
+        }
+        if let Some(max_blocks_old) = self.max_blocks_old {
+            config.max_blocks_old = max_blocks_old;
+        }
+        if let Some(fee_tiers) = self.fee_tiers.clone() {
+            config.fee_tiers = fee_tiers;
+        }
+        if let Some(deposit_curve) = self.deposit_curve.clone() {
+            config.deposit_curve = deposit_curve;
+        }
+        if let Some(price_ema_tau_seconds) = self.price_ema_tau_seconds {
+            config.price_ema_tau_seconds = price_ema_tau_seconds;
+        }
+        if let Some(max_price_deviation_bps) = self.max_price_deviation_bps {
+            config.max_price_deviation_bps = max_price_deviation_bps;
+        }
+        if let Some(price_divergence_fallback) = self.price_divergence_fallback {
+            config.price_divergence_fallback = price_divergence_fallback;
+        }
+        if let Some(ema_max_deviation_bps) = self.ema_max_deviation_bps {
+            config.ema_max_deviation_bps = ema_max_deviation_bps;
+        }
+        if let Some(max_target_rate_deviation_bps) = self.max_target_rate_deviation_bps {
+            config.max_target_rate_deviation_bps = max_target_rate_deviation_bps;
+        }
+        if let Some(target_rate_max_drift_bps) = self.target_rate_max_drift_bps {
+            config.target_rate_max_drift_bps = target_rate_max_drift_bps;
+        }
+        if let Some(min_dex_deposit_interval_seconds) = self.min_dex_deposit_interval_seconds {
+            config.min_dex_deposit_interval_seconds = min_dex_deposit_interval_seconds;
+        }
+        if let Some(stableswap_amplification) = self.stableswap_amplification {
+            config.stableswap_amplification = stableswap_amplification;
+        }
+        if let Some(dex_deviation_bps) = self.dex_deviation_bps {
+            config.dex_deviation_bps = dex_deviation_bps;
+        }
+        if let Some(dex_deviation_cooldown_blocks) = self.dex_deviation_cooldown_blocks {
+            config.dex_deviation_cooldown_blocks = dex_deviation_cooldown_blocks;
+        }
+        if let Some(min_deposit_amount_0) = self.min_deposit_amount_0 {
+            config.min_deposit_amount_0 = min_deposit_amount_0;
+        }
+        if let Some(min_deposit_amount_1) = self.min_deposit_amount_1 {
+            config.min_deposit_amount_1 = min_deposit_amount_1;
+        }
+        if let Some(min_rebalance_amount_0) = self.min_rebalance_amount_0 {
+            config.min_rebalance_amount_0 = min_rebalance_amount_0;
+        }
+        if let Some(min_rebalance_amount_1) = self.min_rebalance_amount_1 {
+            config.min_rebalance_amount_1 = min_rebalance_amount_1;
+        }
+        if let Some(rebalance_strategy) = self.rebalance_strategy.clone() {
+            config.rebalance_strategy = rebalance_strategy;
+        }
+        if let Some(max_oracle_price_skew_ticks) = self.max_oracle_price_skew_ticks {
+            config.max_oracle_price_skew_ticks = max_oracle_price_skew_ticks;
+        }
+    }
+
+    /// One `changed_<field>` attribute per field this override actually set,
+    /// each carrying its `old=<v>`/`new=<v>` values against `old`, so
+    /// `ExecuteMsg::CommitConfig`'s response tells indexers/LPs exactly what
+    /// moved instead of just `action: commit_config`. Fields left `None`
+    /// (unchanged) contribute no attribute.
+    pub fn diff_attributes(&self, old: &crate::state::Config) -> Vec<cosmwasm_std::Attribute> {
+        let mut attrs = Vec::new();
+        if let Some(base_fee) = self.base_fee {
+            attrs.push(cosmwasm_std::attr(
+                "changed_base_fee",
+                format!("old={} new={}", old.base_fee, base_fee),
+            ));
+        }
+        if let Some(status) = &self.status {
+            attrs.push(cosmwasm_std::attr(
+                "changed_status",
+                format!("old={:?} new={:?}", old.status, status),
+            ));
+        }
+        if let Some(imbalance_bps) = self.imbalance_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_imbalance_bps",
+                format!("old={} new={}", old.imbalance_bps, imbalance_bps),
+            ));
+        }
+        if let Some(rebalance_target_bps) = self.rebalance_target_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_rebalance_target_bps",
+                format!(
+                    "old={} new={}",
+                    old.rebalance_target_bps, rebalance_target_bps
+                ),
+            ));
+        }
+        if let Some(performance_fee_bps) = self.performance_fee_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_performance_fee_bps",
+                format!("old={} new={}", old.performance_fee_bps, performance_fee_bps),
+            ));
+        }
+        if let Some(management_fee_bps) = self.management_fee_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_management_fee_bps",
+                format!("old={} new={}", old.management_fee_bps, management_fee_bps),
+            ));
+        }
+        if let Some(max_total_shares) = self.max_total_shares {
+            attrs.push(cosmwasm_std::attr(
+                "changed_max_total_shares",
+                format!(
+                    "old={:?} new={max_total_shares}",
+                    old.max_total_shares
+                ),
+            ));
+        }
+        if let Some(swap_fee_bps) = self.swap_fee_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_swap_fee_bps",
+                format!("old={} new={}", old.swap_fee_bps, swap_fee_bps),
+            ));
+        }
+        if let Some(skew) = self.skew {
+            attrs.push(cosmwasm_std::attr(
+                "changed_skew",
+                format!("old={} new={}", old.skew, skew),
+            ));
+        }
+        if let Some(oracle_price_skew) = self.oracle_price_skew {
+            attrs.push(cosmwasm_std::attr(
+                "changed_oracle_price_skew",
+                format!("old={} new={}", old.oracle_price_skew, oracle_price_skew),
+            ));
+        }
+        if let Some(dynamic_spread_cap) = self.dynamic_spread_cap {
+            attrs.push(cosmwasm_std::attr(
+                "changed_dynamic_spread_cap",
+                format!("old={} new={}", old.dynamic_spread_cap, dynamic_spread_cap),
+            ));
+        }
+        if let Some(deposit_cap) = self.deposit_cap {
+            attrs.push(cosmwasm_std::attr(
+                "changed_deposit_cap",
+                format!("old={} new={}", old.deposit_cap, deposit_cap),
+            ));
+        }
+        if let Some(max_blocks_old) = self.max_blocks_old {
+            attrs.push(cosmwasm_std::attr(
+                "changed_max_blocks_old",
+                format!("old={} new={}", old.max_blocks_old, max_blocks_old),
+            ));
+        }
+        if let Some(fee_tiers) = &self.fee_tiers {
+            attrs.push(cosmwasm_std::attr(
+                "changed_fee_tiers",
+                format!("old={:?} new={:?}", old.fee_tiers, fee_tiers),
+            ));
+        }
+        if let Some(deposit_curve) = &self.deposit_curve {
+            attrs.push(cosmwasm_std::attr(
+                "changed_deposit_curve",
+                format!("old={:?} new={:?}", old.deposit_curve, deposit_curve),
+            ));
+        }
+        if let Some(price_ema_tau_seconds) = self.price_ema_tau_seconds {
+            attrs.push(cosmwasm_std::attr(
+                "changed_price_ema_tau_seconds",
+                format!(
+                    "old={} new={}",
+                    old.price_ema_tau_seconds, price_ema_tau_seconds
+                ),
+            ));
+        }
+        if let Some(max_price_deviation_bps) = self.max_price_deviation_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_max_price_deviation_bps",
+                format!(
+                    "old={} new={}",
+                    old.max_price_deviation_bps, max_price_deviation_bps
+                ),
+            ));
+        }
+        if let Some(price_divergence_fallback) = self.price_divergence_fallback {
+            attrs.push(cosmwasm_std::attr(
+                "changed_price_divergence_fallback",
+                format!(
+                    "old={} new={}",
+                    old.price_divergence_fallback, price_divergence_fallback
+                ),
+            ));
+        }
+        if let Some(ema_max_deviation_bps) = self.ema_max_deviation_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_ema_max_deviation_bps",
+                format!(
+                    "old={} new={}",
+                    old.ema_max_deviation_bps, ema_max_deviation_bps
+                ),
+            ));
+        }
+        if let Some(max_target_rate_deviation_bps) = self.max_target_rate_deviation_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_max_target_rate_deviation_bps",
+                format!(
+                    "old={} new={}",
+                    old.max_target_rate_deviation_bps, max_target_rate_deviation_bps
+                ),
+            ));
+        }
+        if let Some(target_rate_max_drift_bps) = self.target_rate_max_drift_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_target_rate_max_drift_bps",
+                format!(
+                    "old={} new={}",
+                    old.target_rate_max_drift_bps, target_rate_max_drift_bps
+                ),
+            ));
+        }
+        if let Some(min_dex_deposit_interval_seconds) = self.min_dex_deposit_interval_seconds {
+            attrs.push(cosmwasm_std::attr(
+                "changed_min_dex_deposit_interval_seconds",
+                format!(
+                    "old={} new={}",
+                    old.min_dex_deposit_interval_seconds, min_dex_deposit_interval_seconds
+                ),
+            ));
+        }
+        if let Some(stableswap_amplification) = self.stableswap_amplification {
+            attrs.push(cosmwasm_std::attr(
+                "changed_stableswap_amplification",
+                format!(
+                    "old={} new={}",
+                    old.stableswap_amplification, stableswap_amplification
+                ),
+            ));
+        }
+        if let Some(dex_deviation_bps) = self.dex_deviation_bps {
+            attrs.push(cosmwasm_std::attr(
+                "changed_dex_deviation_bps",
+                format!("old={} new={}", old.dex_deviation_bps, dex_deviation_bps),
+            ));
+        }
+        if let Some(dex_deviation_cooldown_blocks) = self.dex_deviation_cooldown_blocks {
+            attrs.push(cosmwasm_std::attr(
+                "changed_dex_deviation_cooldown_blocks",
+                format!(
+                    "old={} new={}",
+                    old.dex_deviation_cooldown_blocks, dex_deviation_cooldown_blocks
+                ),
+            ));
+        }
+        if let Some(min_deposit_amount_0) = self.min_deposit_amount_0 {
+            attrs.push(cosmwasm_std::attr(
+                "changed_min_deposit_amount_0",
+                format!("old={} new={}", old.min_deposit_amount_0, min_deposit_amount_0),
+            ));
+        }
+        if let Some(min_deposit_amount_1) = self.min_deposit_amount_1 {
+            attrs.push(cosmwasm_std::attr(
+                "changed_min_deposit_amount_1",
+                format!("old={} new={}", old.min_deposit_amount_1, min_deposit_amount_1),
+            ));
+        }
+        if let Some(min_rebalance_amount_0) = self.min_rebalance_amount_0 {
+            attrs.push(cosmwasm_std::attr(
+                "changed_min_rebalance_amount_0",
+                format!(
+                    "old={} new={}",
+                    old.min_rebalance_amount_0, min_rebalance_amount_0
+                ),
+            ));
+        }
+        if let Some(min_rebalance_amount_1) = self.min_rebalance_amount_1 {
+            attrs.push(cosmwasm_std::attr(
+                "changed_min_rebalance_amount_1",
+                format!(
+                    "old={} new={}",
+                    old.min_rebalance_amount_1, min_rebalance_amount_1
+                ),
+            ));
+        }
+        if let Some(rebalance_strategy) = self.rebalance_strategy.clone() {
+            attrs.push(cosmwasm_std::attr(
+                "changed_rebalance_strategy",
+                format!("old={:?} new={:?}", old.rebalance_strategy, rebalance_strategy),
+            ));
+        }
+        if let Some(max_oracle_price_skew_ticks) = self.max_oracle_price_skew_ticks {
+            attrs.push(cosmwasm_std::attr(
+                "changed_max_oracle_price_skew_ticks",
+                format!(
+                    "old={} new={}",
+                    old.max_oracle_price_skew_ticks, max_oracle_price_skew_ticks
+                ),
+            ));
+        }
+        attrs
+    }
+}
+
+impl crate::state::Config {
+    /// Re-checks the subset of `InstantiateMsg::validate`'s field bounds that
+    /// can still be violated after a `ConfigOverride` merges into a live
+    /// `Config` (`ConfigOverride::validate` only sees the override in
+    /// isolation, so e.g. raising `oracle_price_skew` and loosening its own
+    /// bound in the same override would otherwise slip past both the staging
+    /// and commit checks). Called from both `instantiate` and
+    /// `ExecuteMsg::CommitConfig` against the fully merged `Config`.
+    ///
+    /// Deliberately does not enforce a `dynamic_spread_factor >= 0` bound:
+    /// this tree has no field by that name. The closest analog,
+    /// `spread_curve::SpreadFactors { widen, narrow }`, is documented
+    /// elsewhere as meaningfully negative (a negative factor selects a
+    /// gentler exponential response, not an error condition), so clamping it
+    /// to `>=0` here would reject valid configurations.
+    pub fn validate(&self, allowed_fee_tiers: &[u64]) -> ContractResult<()> {
+        InstantiateMsg::validate_fee_tiers(&self.fee_tiers)?;
+        for tier in &self.fee_tiers {
+            InstantiateMsg::validate_base_fee(tier.fee, allowed_fee_tiers)?;
+        }
+        InstantiateMsg::validate_base_fee(self.base_fee, allowed_fee_tiers)?;
+        let max_skew = i32::try_from(self.max_oracle_price_skew_ticks).unwrap_or(i32::MAX);
+        if self.oracle_price_skew > max_skew || self.oracle_price_skew < -max_skew {
+            return Err(ContractError::SkewOutOfRange {
+                value: self.oracle_price_skew,
+                max: max_skew,
+            });
+        }
+        InstantiateMsg::validate_imbalance_bps(self.imbalance_bps)?;
+        if self.dynamic_spread_cap > 10000 {
+            return Err(ContractError::SpreadCapOutOfRange {
+                value: self.dynamic_spread_cap,
+                max: 10000,
+            });
+        }
+        Ok(())
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -30,6 +515,430 @@ pub struct InstantiateMsg {
     pub max_block_old: u64,
     pub base_fee: u64,
     pub base_deposit_percentage: u64,
+    /// human-readable per-window withdrawal cap (e.g. `1.5`), scaled to each
+    /// token's own `decimals` at instantiation. `None` disables the limit.
+    pub withdrawal_limit: Option<Decimal>,
+    /// maximum allowed deviation, in basis points, between the oracle price
+    /// and the price implied by a simulated DEX deposit before it is rejected.
+    pub max_slippage_bps: u64,
+    /// EMA weight applied to `price_0_to_1` on each `DexDeposit`, in `[0, 1]`.
+    pub ema_alpha: Decimal,
+    /// maximum allowed deviation, in basis points, between the spot oracle
+    /// price and the tracked EMA before `DexDeposit` guards against it.
+    pub ema_max_deviation_bps: u64,
+    /// when the deviation guard trips: if true, deposit around the EMA price
+    /// instead of the spot price; if false, reject the deposit outright.
+    pub ema_fallback: bool,
+    /// contract queried for the pair's redemption/exchange rate `r`. `None`
+    /// disables the `p_eff = price_0_to_1 * r` adjustment.
+    pub target_rate_provider: Option<String>,
+    /// max age, in blocks, of a cached `target_rate_provider` sample before
+    /// it is re-queried.
+    pub target_rate_max_blocks_old: u64,
+    /// seconds over which a newly-queried `target_rate_provider` rate is
+    /// linearly ramped into effect. `0` applies each refreshed rate immediately.
+    pub target_rate_amortization_seconds: u64,
+    /// maximum allowed deviation, in basis points, between the raw oracle
+    /// price and the `target_rate_provider`-adjusted price before
+    /// `apply_target_rate` rejects it. `0` disables the check.
+    pub max_target_rate_deviation_bps: u64,
+    /// maximum allowed change, in basis points per second elapsed, between a
+    /// freshly-queried `target_rate_provider` sample and the previous
+    /// effective rate before `get_target_rate` rejects it with
+    /// `ContractError::TargetRateDrift`. `0` disables the check.
+    pub target_rate_max_drift_bps: u64,
+    /// when true, `Deposit` mints shares off the more conservative of the
+    /// oracle price and a simulated DEX book fill for the deposited inventory.
+    pub book_aware_valuation: bool,
+    /// `(recipient, weight)` pairs for `ExecuteMsg::DistributeFees`, weights
+    /// summing to `FEE_SPLITTER_DENOMINATOR`. `None` disables
+    /// `DistributeFees`; `SweepFees` does not depend on it.
+    pub fee_splitter: Option<Vec<(String, u64)>>,
+    /// when true, `DexDeposit` shifts the token_0/token_1 split away from
+    /// 50/50 toward `imbalance_bps` and offsets the center tick by
+    /// `oracle_price_skew`. When false, deposits are always balanced 50/50.
+    pub skew: bool,
+    /// target token_0 share of the deposit's value, in basis points out of
+    /// `10000`, applied only when `skew` is true.
+    pub imbalance_bps: u64,
+    /// tick offset applied to the deposit's center tick, applied only when
+    /// `skew` is true.
+    pub oracle_price_skew: i32,
+    /// symmetric bound on `oracle_price_skew`: `Config::validate` rejects
+    /// `oracle_price_skew` outside `+/-max_oracle_price_skew_ticks`.
+    pub max_oracle_price_skew_ticks: u32,
+    /// max age, in seconds, of a tracked EMA sample before `DexDeposit`
+    /// rejects with `ContractError::EmaStale` rather than deposit against it.
+    pub max_ema_age_seconds: u64,
+    /// max allowed ratio, in basis points, of a token's reported oracle
+    /// confidence/standard-deviation band to its spot price. `None` disables
+    /// the check.
+    pub max_conf_ratio_bps: Option<u64>,
+    /// spreads the base fee tier's computed deposit across a band of ticks
+    /// around the center instead of placing it all at one tick. `None`
+    /// deposits entirely at the center tick, the pre-existing behavior.
+    pub deposit_band: Option<DepositBandConfig>,
+    /// max allowed drift, in basis points, between the portfolio's oracle
+    /// value split and `rebalance_target_bps` before `DexDeposit` swaps
+    /// toward the target ahead of computing fee-tier allocations. `None`
+    /// disables the pre-deposit rebalance step.
+    pub rebalance_threshold_bps: Option<u64>,
+    /// target token_0 share of the portfolio's oracle value, in basis points
+    /// out of `10000`, the rebalance step swaps toward.
+    pub rebalance_target_bps: u64,
+    /// max ticks the rebalance swap's simulated fill price may imply moving
+    /// away from the deposit's center tick before the swap is skipped.
+    pub max_rebalance_ticks: u64,
+    /// max allowed deviation, in basis points, between the rebalance swap's
+    /// simulated fill price and the oracle price before the swap is skipped.
+    pub max_rebalance_slippage_bps: u64,
+    /// performance fee, in basis points out of `10000`, taken from each
+    /// token's new per-share appreciation when `ExecuteMsg::HarvestPerformanceFee`
+    /// runs. `0` disables performance fees.
+    pub performance_fee_bps: u64,
+    /// time-based management fee, in basis points out of `10000` per year,
+    /// minted to `fee_collector` as fresh shares when
+    /// `ExecuteMsg::HarvestPerformanceFee` runs. `0` disables it.
+    pub management_fee_bps: u64,
+    /// recipient of minted `management_fee_bps` shares. Required (and only
+    /// meaningful) when `management_fee_bps` is non-zero.
+    pub fee_collector: Option<String>,
+    /// fee, in basis points out of `10000`, charged on the input side of
+    /// `ExecuteMsg::Swap` and left in the vault's reserves rather than paid
+    /// out, so it accrues to LP holders the same way DEX trading fees do.
+    pub swap_fee_bps: u64,
+    /// validator/staking module shares delegate their value to when bonded
+    /// via `ExecuteMsg::Bond`. `None` disables `Bond`/`Unbond` entirely.
+    pub staking_target: Option<String>,
+    /// how long, in seconds, `ExecuteMsg::Unbond` locks shares for before
+    /// `withdraw` will burn them. Only meaningful when `staking_target` is set.
+    pub unbonding_period_seconds: u64,
+    /// time constant, in seconds, of the per-token spot-price EMA tracked for
+    /// `Deposit`'s divergence guard.
+    pub price_ema_tau_seconds: u64,
+    /// maximum allowed deviation, in basis points, between a token's spot
+    /// oracle price and its tracked EMA before `Deposit` rejects with
+    /// `ContractError::PriceDivergence`.
+    pub max_price_deviation_bps: u64,
+    /// when the deviation guard above trips: if true, `Deposit` values that
+    /// token off its tracked EMA instead of its spot price; if false, reject
+    /// the deposit outright. Mirrors `ema_fallback`'s semantics on the older
+    /// `price_0_to_1`-scalar guard.
+    pub price_divergence_fallback: bool,
+    /// sliding-window rate limiter on how fast `total_shares` can grow or
+    /// shrink, checked by every `Deposit`/`Withdraw`. `None` disables the
+    /// check entirely.
+    pub change_limiter: Option<ChangeLimiterConfig>,
+    /// max cumulative oracle-valued `Deposit` contribution a single
+    /// beneficiary address may hold. `None` disables the per-address check.
+    pub per_address_cap: Option<PrecDec>,
+    /// maximum allowed deviation, in basis points, between the oracle price
+    /// and an `ExecuteMsg::Deposit { auto_balance: true }` swap's realized
+    /// DEX fill price before the deposit is rejected.
+    pub dynamic_spread_cap: u64,
+    /// CW20 contract whose tokens are accepted as a `token_0` deposit via
+    /// `ExecuteMsg::Receive`. `None` means `token_0` only accepts the native
+    /// `token_a` coin.
+    pub cw20_token_0: Option<String>,
+    /// CW20 contract whose tokens are accepted as a `token_1` deposit via
+    /// `ExecuteMsg::Receive`. `None` means `token_1` only accepts the native
+    /// `token_b` coin.
+    pub cw20_token_1: Option<String>,
+    /// how long, in seconds, `ExecuteMsg::QueueWithdrawal` locks a queued
+    /// withdrawal's redemption value for before `ExecuteMsg::Claim` will
+    /// pay it out. `0` makes queued withdrawals claimable immediately.
+    pub withdrawal_queue_period_seconds: u64,
+    /// ladders the base fee tier's computed deposit across several `(fee,
+    /// percentage)` rungs instead of depositing it all at `base_fee`.
+    /// `percentage`s must sum to `100`. Empty deposits entirely at
+    /// `base_fee`, the pre-existing behavior.
+    pub fee_tiers: Vec<FeeTier>,
+    /// which of `DepositCurve`'s policies `ladder_fee_tiers` places
+    /// `fee_tiers`' center ticks with. `DepositCurve::Linear` preserves the
+    /// pre-existing fixed-offset placement.
+    pub deposit_curve: crate::state::DepositCurve,
+    /// widens the deployed fee tier(s) by a spread derived from recent
+    /// oracle price volatility instead of always deploying at the
+    /// configured `base_fee`/`fee_tiers`. `None` disables the widening
+    /// entirely, the pre-existing behavior.
+    pub volatility_spread: Option<VolatilitySpreadConfig>,
+    /// number of blocks `ExecuteMsg::UpdateConfig` must wait before
+    /// `ExecuteMsg::CommitConfig` may apply it.
+    pub timelock_blocks: u64,
+    /// additional price sources queried alongside the primary x/oracle module
+    /// feed, each implementing `OracleSourceQueryMsg::Price`. Empty disables
+    /// multi-source aggregation, the pre-existing single-feed behavior.
+    pub oracle_contracts: Vec<String>,
+    /// minimum number of `oracle_contracts` responses (after dropping any
+    /// older than `max_blocks_old`) required before their element-wise
+    /// median is trusted over the primary feed. Only meaningful when
+    /// `oracle_contracts` is non-empty.
+    pub min_sources: u64,
+    /// maximum allowed deviation, in basis points, of any individual
+    /// `oracle_contracts` response's `price_0_to_1` from the group median
+    /// before `get_prices` rejects the whole price with
+    /// `ContractError::PriceDeviation` instead of silently trusting a median
+    /// one manipulated/broken feed could still skew. `0` disables the guard.
+    /// Only meaningful when `oracle_contracts` is non-empty.
+    pub max_oracle_deviation_bps: u64,
+    /// length, in seconds, of the trailing window `PRICE_OBSERVATIONS`
+    /// computes the TWAP over before `DexDeposit` compares it against spot.
+    pub twap_window_seconds: u64,
+    /// maximum allowed deviation, in basis points, between spot
+    /// `price_0_to_1` and its TWAP before `DexDeposit` skips the
+    /// `skew`/rebalance step for that call rather than rebalancing toward a
+    /// possibly-manipulated spot price. `0` disables the guard.
+    pub max_twap_deviation_bps: u64,
+    /// where the LST leg's redemption rate is fetched from. `None` disables
+    /// the adjustment; see `Config::redemption_adapter`.
+    pub redemption_adapter_source: Option<RedemptionRateSourceInput>,
+    /// denom of `token_a`/`token_b` the `redemption_adapter` rate is applied
+    /// to. Required (and only meaningful) when `redemption_adapter_source` is
+    /// set.
+    pub redemption_adapter_lst_denom: Option<String>,
+    /// absolute floor/ceiling a fetched `redemption_adapter` rate must clear,
+    /// guarding against a compromised or buggy source returning an absurd
+    /// rate. Required (and only meaningful) when `redemption_adapter_source`
+    /// is set.
+    pub redemption_adapter_min_rate: Option<PrecDec>,
+    pub redemption_adapter_max_rate: Option<PrecDec>,
+    /// maximum allowed increase over the last accepted rate, in bps of that
+    /// rate per second elapsed, enforced by
+    /// `crate::utils::apply_redemption_adapter`. Required (and only
+    /// meaningful) when `redemption_adapter_source` is set.
+    pub redemption_adapter_max_rate_change_bps: Option<u64>,
+    /// max age, in seconds, `LAST_REDEMPTION_RATE`'s cached baseline may
+    /// reach before `apply_redemption_adapter` rejects with
+    /// `ContractError::RedemptionRateStale` rather than trusting a value
+    /// nobody has successfully refreshed in too long. Required (and only
+    /// meaningful) when `redemption_adapter_source` is set.
+    pub redemption_adapter_max_rate_age_seconds: Option<u64>,
+    /// hard ceiling on `total_shares`, independent of `deposit_cap`'s
+    /// USD-denominated limit. `None` disables the check.
+    pub max_total_shares: Option<Uint128>,
+    /// quotes a passive maker ladder of `MsgPlaceLimitOrder`s instead of
+    /// pooling liquidity with `MsgDeposit`. `None` keeps the pre-existing
+    /// `MsgDeposit` pooling behavior, the default.
+    pub market_making: Option<MarketMakingConfig>,
+    /// external contracts `ExecuteMsg::CollectRewards` claims DEX/gauge
+    /// incentive emissions from. Empty disables `CollectRewards`, the
+    /// default; see `Config::reward_claim_contracts`.
+    pub reward_claim_contracts: Vec<String>,
+    /// maximum allowed per-pair price move, in basis points per block elapsed
+    /// since the last accepted snapshot, before `Deposit` rejects with
+    /// `ContractError::PriceJump` instead of minting shares off a single-block
+    /// oracle spike. `0` disables the guard.
+    pub max_price_jump_bps: u64,
+    /// denoms treated as a quote-side numeraire instead of requiring their
+    /// own oracle price, replacing the old hardcoded USD/USDC check; see
+    /// `Config::stable_denoms`. Empty requires every pair (including a
+    /// stable base) to resolve through the oracle.
+    pub stable_denoms: Vec<StableDenomConfig>,
+    /// minimum seconds between `dex_deposit` runs; see
+    /// `Config::min_dex_deposit_interval_seconds`. `0` disables the throttle,
+    /// the default.
+    pub min_dex_deposit_interval_seconds: u64,
+    /// amplification coefficient for an opt-in StableSwap pricing curve; see
+    /// `Config::stableswap_amplification`. `0` disables it, the default.
+    pub stableswap_amplification: u64,
+    /// see `Config::dex_deviation_bps`. `0` disables the circuit breaker,
+    /// the default.
+    pub dex_deviation_bps: u64,
+    /// see `Config::dex_deviation_cooldown_blocks`.
+    pub dex_deviation_cooldown_blocks: u64,
+    /// minimum atomic amount `get_deposit_data` will place as the base
+    /// deposit's token_0/token_1 leg; see `Config::min_deposit_amount_0`.
+    /// `0` keeps the pre-existing fixed dust guard as the only floor.
+    pub min_deposit_amount_0: Uint128,
+    pub min_deposit_amount_1: Uint128,
+    /// minimum simulated `amount_in` `prepare_state` requires before placing
+    /// a rebalancing order; see `Config::min_rebalance_amount_0`. `0` places
+    /// any nonzero simulated fill, the pre-existing behavior.
+    pub min_rebalance_amount_0: Uint128,
+    pub min_rebalance_amount_1: Uint128,
+    /// which `prepare_state` allocation policy to start with; see
+    /// `Config::rebalance_strategy`.
+    pub rebalance_strategy: crate::state::RebalanceStrategy,
+    /// addresses eligible to approve an `ExecuteMsg::ProposeConfigUpdate`;
+    /// see `Config::signers`. Empty disables the `PROPOSALS` flow entirely,
+    /// the default.
+    pub signers: Vec<String>,
+    /// quorum of `signers` approvals required to apply a proposal; see
+    /// `Config::threshold`. Only meaningful when `signers` is non-empty.
+    pub threshold: u32,
+    /// starting drift tolerance for `dex_deposit`'s no-op skip; see
+    /// `Config::rebalance_drift_tolerance_ticks`. `0` disables it.
+    pub rebalance_drift_tolerance_ticks: u64,
+}
+
+/// `InstantiateMsg`/`ExecuteMsg::SetRedemptionAdapter`'s unvalidated mirror of
+/// `crate::state::RedemptionRateSource` — identical shape, but contract
+/// addresses are plain `String`s pending `deps.api.addr_validate`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RedemptionRateSourceInput {
+    StaticConfig { rate: PrecDec },
+    CoreContractExchangeRate { contract: String, mint_fee_bps: u64 },
+    ConvertToAssets { contract: String },
+    Composed {
+        primary: Box<RedemptionRateSourceInput>,
+        secondary: Box<RedemptionRateSourceInput>,
+    },
+}
+
+impl RedemptionRateSourceInput {
+    pub fn validate(
+        &self,
+        api: &dyn cosmwasm_std::Api,
+    ) -> ContractResult<crate::state::RedemptionRateSource> {
+        Ok(match self {
+            RedemptionRateSourceInput::StaticConfig { rate } => {
+                crate::state::RedemptionRateSource::StaticConfig { rate: *rate }
+            }
+            RedemptionRateSourceInput::CoreContractExchangeRate {
+                contract,
+                mint_fee_bps,
+            } => crate::state::RedemptionRateSource::CoreContractExchangeRate {
+                contract: api.addr_validate(contract)?,
+                mint_fee_bps: *mint_fee_bps,
+            },
+            RedemptionRateSourceInput::ConvertToAssets { contract } => {
+                crate::state::RedemptionRateSource::ConvertToAssets {
+                    contract: api.addr_validate(contract)?,
+                }
+            }
+            RedemptionRateSourceInput::Composed { primary, secondary } => {
+                crate::state::RedemptionRateSource::Composed {
+                    primary: Box::new(primary.validate(api)?),
+                    secondary: Box::new(secondary.validate(api)?),
+                }
+            }
+        })
+    }
+}
+
+/// The `Cw20ReceiveMsg::msg` payload a `Config::cw20_token_0`/`cw20_token_1`
+/// sends along with a CW20 `Send`/`SendFrom` to deposit it into the vault.
+/// Carries the same fields as `ExecuteMsg::Deposit` so the CW20 path mints
+/// shares the same way the native-coin path does.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    Deposit {
+        min_shares_out: Option<Uint128>,
+        beneficiary: Option<String>,
+        auto_balance: bool,
+    },
+}
+
+/// Queried against `Config::target_rate_provider` to get the current
+/// redemption/exchange rate `r` used to compute `p_eff = price_0_to_1 * r`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetRateQueryMsg {
+    #[returns(Decimal)]
+    ExchangeRate {},
+}
+
+/// Queried against a `RedemptionRateSource::CoreContractExchangeRate`
+/// contract to get the LST leg's current redemption rate, the same shape the
+/// `lst-oracle` sibling contract's `QueryMsg::GetRedemptionRate` exposes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum RedemptionRateQueryMsg {
+    #[returns(PrecDec)]
+    GetRedemptionRate {},
+}
+
+/// Queried against a `RedemptionRateSource::ConvertToAssets` contract, the
+/// same shape an ERC-4626-style vault's `convert_to_assets` exposes: how many
+/// underlying assets `shares` of the wrapped token are worth.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw4626QueryMsg {
+    #[returns(Uint128)]
+    ConvertToAssets { shares: Uint128 },
+}
+
+/// Queried against each of `Config::oracle_contracts`, the same
+/// "wasm_smart query against a configured `Addr`" pattern
+/// `TargetRateQueryMsg` already uses for `target_rate_provider`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleSourceQueryMsg {
+    #[returns(OracleSourceResponse)]
+    Price {},
+}
+
+/// Queried against an external APY-reporting contract to drive
+/// `QueryMsg::GetCalculatedFeeTiers`/`execute_update_apy_ema`, the same
+/// `instance`/`time_span_hours` shape the `dasset-updator` sibling
+/// contract's `external_types::QueryMsg::GetApy` takes. This vault has no
+/// `FeeTierConfig`/`DropInstanceApy` types of its own - those belong to the
+/// `mmvault` and `dasset-updator` sibling contracts respectively - so rather
+/// than importing either, this follows the thin "wasm_smart query against a
+/// configured `Addr`" pattern `OracleSourceQueryMsg`/`TargetRateQueryMsg`
+/// already use. Returns [`ApyResponse`] rather than a bare `PrecDec` so
+/// `execute_update_apy_ema` can enforce freshness against the observation's
+/// own `block_height` instead of trusting it was sampled this block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum ApySourceQueryMsg {
+    #[returns(ApyResponse)]
+    GetApy { instance: String, time_span_hours: u64 },
+}
+
+/// `ApySourceQueryMsg::GetApy`'s response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ApyResponse {
+    pub apy: PrecDec,
+    pub block_height: u64,
+}
+
+/// `QueryMsg::GetCalculatedFeeTiers`'s response: the instantaneous APY
+/// queried fresh from `apy_contract` (`apy`) alongside `APY_EMA`'s smoothed
+/// running average for the same `instance` (`ema_apy`, absent until
+/// `execute_update_apy_ema` has observed that `instance` at least once), so a
+/// caller can see how far the smoothed value the fee tiers were actually
+/// derived from diverges from the raw sample. `fee_tiers`/`oracle_skew` are
+/// derived from `ema_apy` when present, falling back to the raw `apy`
+/// otherwise, so a caller feeding this straight into
+/// `ConfigOverride::fee_tiers`/`oracle_price_skew` can log what drove the
+/// change.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CalculatedFeeTiersResponse {
+    pub denom: String,
+    pub apy: PrecDec,
+    pub ema_apy: Option<PrecDec>,
+    pub base_fee: u64,
+    pub oracle_skew: i32,
+    pub fee_tiers: Vec<FeeTier>,
+}
+
+/// One `Config::oracle_contracts` source's view of the pair's spot prices,
+/// plus the block height it was observed at so `get_prices` can drop it if
+/// it's older than `Config::max_blocks_old`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OracleSourceResponse {
+    pub token_0_price: PrecDec,
+    pub token_1_price: PrecDec,
+    pub price_0_to_1: PrecDec,
+    pub block_height: u64,
+}
+
+/// The `WasmMsg::Execute` payload `execute_collect_rewards` sends to each
+/// `Config::reward_claim_contracts` entry. No two incentive-distributor
+/// contracts share a claim API, and no vendored schema for any real one
+/// exists in this tree, so this assumes only the bare minimum: a no-args
+/// variant that pays out whatever the contract owes this vault directly in
+/// bank funds. `handle_reward_claim_reply` never parses a reply from this -
+/// it diffs the vault's own balance before/after instead - so this type's
+/// only real job is picking a wire-compatible message shape to send.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardClaimExecuteMsg {
+    Claim {},
 }
 
 impl InstantiateMsg {
@@ -54,7 +963,7 @@ impl InstantiateMsg {
         }
         Self::validate_denom(&self.token_a.denom)?;
         Self::validate_denom(&self.token_b.denom)?;
-        Self::validate_base_fee(self.base_fee)?;
+        Self::validate_base_fee(self.base_fee, &crate::state::FALLBACK_FEE_TIERS)?;
         Self::validate_base_deposit_percentage(self.base_deposit_percentage)?;
 
         if self.token_a.pair.quote == self.token_b.pair.quote && self.token_b.pair.quote != "USD" {
@@ -63,14 +972,362 @@ impl InstantiateMsg {
                 quote1: self.token_b.pair.quote.clone(),
             });
         }
+        Self::validate_ema_alpha(self.ema_alpha)?;
+        Self::validate_fee_splitter(&self.fee_splitter)?;
+        Self::validate_imbalance_bps(self.imbalance_bps)?;
+        Self::validate_deposit_band(&self.deposit_band)?;
+        Self::validate_rebalance_target_bps(self.rebalance_target_bps)?;
+        Self::validate_performance_fee_bps(self.performance_fee_bps)?;
+        Self::validate_management_fee_bps(self.management_fee_bps)?;
+        Self::validate_swap_fee_bps(self.swap_fee_bps)?;
+        Self::validate_staking_target(&self.staking_target, self.unbonding_period_seconds)?;
+        Self::validate_change_limiter(&self.change_limiter)?;
+        Self::validate_dynamic_spread_cap(self.dynamic_spread_cap)?;
+        Self::validate_max_slippage_bps(self.max_slippage_bps)?;
+        Self::validate_fee_tiers(&self.fee_tiers)?;
+        Self::validate_volatility_spread(&self.volatility_spread)?;
+        Self::validate_timelock_blocks(self.timelock_blocks)?;
+        Self::validate_oracle_sources(&self.oracle_contracts, self.min_sources)?;
+        Self::validate_signers(&self.signers, self.threshold)?;
+        Self::validate_twap_window_seconds(self.twap_window_seconds)?;
+        Self::validate_redemption_adapter(
+            &self.redemption_adapter_source,
+            &self.redemption_adapter_lst_denom,
+            &self.redemption_adapter_min_rate,
+            &self.redemption_adapter_max_rate,
+            &self.redemption_adapter_max_rate_change_bps,
+            &self.redemption_adapter_max_rate_age_seconds,
+            &self.token_a.denom,
+            &self.token_b.denom,
+        )?;
         Ok(())
     }
 
-    pub fn validate_base_fee(fee: u64) -> ContractResult<Response> {
-        // TODO: GET FROM DEX, for now Define the allowed fees array
-        let allowed_fees: [u64; 12] = [0, 1, 2, 3, 4, 5, 10, 20, 50, 100, 150, 200];
+    /// A configured `redemption_adapter_source` must name a `lst_denom`
+    /// matching `token_a`/`token_b`'s denom, so `get_prices` always has a leg
+    /// to apply the fetched rate to; a sane `min_rate <= max_rate` band and a
+    /// non-zero `max_rate_change_bps` so `apply_redemption_adapter` has
+    /// bounds to enforce; and, for `CoreContractExchangeRate`, a
+    /// `mint_fee_bps` that isn't itself over 100%.
+    #[allow(clippy::too_many_arguments)]
+    pub fn validate_redemption_adapter(
+        redemption_adapter_source: &Option<RedemptionRateSourceInput>,
+        redemption_adapter_lst_denom: &Option<String>,
+        redemption_adapter_min_rate: &Option<PrecDec>,
+        redemption_adapter_max_rate: &Option<PrecDec>,
+        redemption_adapter_max_rate_change_bps: &Option<u64>,
+        redemption_adapter_max_rate_age_seconds: &Option<u64>,
+        token_a_denom: &str,
+        token_b_denom: &str,
+    ) -> ContractResult<Response> {
+        let Some(source) = redemption_adapter_source else {
+            return Ok(Response::new());
+        };
+        match redemption_adapter_lst_denom {
+            Some(denom) if denom == token_a_denom || denom == token_b_denom => {}
+            _ => {
+                return Err(ContractError::MalformedInput {
+                    input: "redemption_adapter_lst_denom".to_string(),
+                    reason: "must be set and match token_a or token_b's denom when redemption_adapter_source is set"
+                        .to_string(),
+                })
+            }
+        }
+        match (redemption_adapter_min_rate, redemption_adapter_max_rate) {
+            (Some(min), Some(max)) if min <= max => {}
+            _ => {
+                return Err(ContractError::MalformedInput {
+                    input: "redemption_adapter_min_rate/max_rate".to_string(),
+                    reason: "both must be set, with min_rate <= max_rate, when redemption_adapter_source is set"
+                        .to_string(),
+                })
+            }
+        }
+        match redemption_adapter_max_rate_change_bps {
+            Some(bps) if *bps > 0 => {}
+            _ => {
+                return Err(ContractError::MalformedInput {
+                    input: "redemption_adapter_max_rate_change_bps".to_string(),
+                    reason: "must be set and non-zero when redemption_adapter_source is set".to_string(),
+                })
+            }
+        }
+        match redemption_adapter_max_rate_age_seconds {
+            Some(seconds) if *seconds > 0 => {}
+            _ => {
+                return Err(ContractError::MalformedInput {
+                    input: "redemption_adapter_max_rate_age_seconds".to_string(),
+                    reason: "must be set and non-zero when redemption_adapter_source is set".to_string(),
+                })
+            }
+        }
+        if let RedemptionRateSourceInput::CoreContractExchangeRate { mint_fee_bps, .. } = source {
+            if *mint_fee_bps > 10_000 {
+                return Err(ContractError::MalformedInput {
+                    input: "mint_fee_bps".to_string(),
+                    reason: "must be at most 10000 (100%)".to_string(),
+                });
+            }
+        }
+        Ok(Response::new())
+    }
+
+    /// `0` would evict every `PRICE_OBSERVATIONS` sample older than the one
+    /// just pushed, collapsing the TWAP to spot and defeating the guard.
+    pub fn validate_twap_window_seconds(twap_window_seconds: u64) -> ContractResult<Response> {
+        if twap_window_seconds == 0 {
+            return Err(ContractError::MalformedInput {
+                input: "twap_window_seconds".to_string(),
+                reason: "must be >=1".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    /// `oracle_contracts` empty (the pre-existing single-feed behavior)
+    /// always passes. A configured set must require at least one survivor,
+    /// and can't require more sources than are actually configured.
+    pub fn validate_oracle_sources(
+        oracle_contracts: &[String],
+        min_sources: u64,
+    ) -> ContractResult<Response> {
+        if !oracle_contracts.is_empty() {
+            if min_sources == 0 {
+                return Err(ContractError::MalformedInput {
+                    input: "min_sources".to_string(),
+                    reason: "must be >=1 when oracle_contracts is non-empty".to_string(),
+                });
+            }
+            if min_sources > oracle_contracts.len() as u64 {
+                return Err(ContractError::MalformedInput {
+                    input: "min_sources".to_string(),
+                    reason: "cannot exceed the number of configured oracle_contracts".to_string(),
+                });
+            }
+        }
+        Ok(Response::new())
+    }
+
+    /// Mirrors `validate_oracle_sources`' non-empty-implies-quorum-bounded
+    /// shape for `Config::signers`/`Config::threshold`.
+    pub fn validate_signers(signers: &[String], threshold: u32) -> ContractResult<Response> {
+        if !signers.is_empty() {
+            if threshold == 0 {
+                return Err(ContractError::MalformedInput {
+                    input: "threshold".to_string(),
+                    reason: "must be >=1 when signers is non-empty".to_string(),
+                });
+            }
+            if threshold as usize > signers.len() {
+                return Err(ContractError::MalformedInput {
+                    input: "threshold".to_string(),
+                    reason: "cannot exceed the number of configured signers".to_string(),
+                });
+            }
+        }
+        Ok(Response::new())
+    }
+
+    /// `0` would let `CommitConfig` apply a staged update in the same block
+    /// it was proposed, defeating the point of a timelock.
+    pub fn validate_timelock_blocks(timelock_blocks: u64) -> ContractResult<Response> {
+        if timelock_blocks == 0 {
+            return Err(ContractError::MalformedInput {
+                input: "timelock_blocks".to_string(),
+                reason: "must be >=1".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    /// Empty `fee_tiers` (the pre-existing single-tier behavior) always
+    /// passes; a non-empty ladder's `percentage`s must sum to exactly `100`
+    /// so `ladder_fee_tiers` splits the base deposit without leftover.
+    pub fn validate_fee_tiers(fee_tiers: &[FeeTier]) -> ContractResult<Response> {
+        if !fee_tiers.is_empty() {
+            let total_percentage: u64 = fee_tiers.iter().map(|tier| tier.percentage).sum();
+            if total_percentage != 100 {
+                return Err(ContractError::InvalidFeeTierWeights {
+                    actual: total_percentage,
+                    expected: 100,
+                });
+            }
+        }
+        Ok(Response::new())
+    }
+
+    /// `None` disables the widening entirely. A configured window must track
+    /// at least two samples (`realized_volatility` needs a return to measure
+    /// dispersion from), and `max_spread_bps` must be a valid basis-point
+    /// fraction so `dynamic_spread_bps`'s clamp can't itself widen past 100%.
+    pub fn validate_volatility_spread(
+        volatility_spread: &Option<VolatilitySpreadConfig>,
+    ) -> ContractResult<Response> {
+        if let Some(cfg) = volatility_spread {
+            if cfg.window_size < 2 {
+                return Err(ContractError::MalformedInput {
+                    input: "volatility_spread.window_size".to_string(),
+                    reason: "must be >=2".to_string(),
+                });
+            }
+            if cfg.max_spread_bps > 10000 {
+                return Err(ContractError::MalformedInput {
+                    input: "volatility_spread.max_spread_bps".to_string(),
+                    reason: "must be <=10000".to_string(),
+                });
+            }
+        }
+        Ok(Response::new())
+    }
+
+    /// `get_deposit_data`'s pre-ladder tick-deviation guard and
+    /// `get_deposit_messages`' post-ladder execution-price guard both read
+    /// this as a ticks/bps tolerance, so `0` (every deposit rejected) and
+    /// anything above `10000` (no basis-point tolerance is meaningful past
+    /// 100%) are both configuration mistakes worth catching here rather than
+    /// at the first rejected `DexDeposit`.
+    pub fn validate_max_slippage_bps(max_slippage_bps: u64) -> ContractResult<Response> {
+        if max_slippage_bps == 0 || max_slippage_bps > 10000 {
+            return Err(ContractError::MalformedInput {
+                input: "max_slippage_bps".to_string(),
+                reason: "must be >0 and <=10000".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_dynamic_spread_cap(dynamic_spread_cap: u64) -> ContractResult<Response> {
+        if dynamic_spread_cap > 10000 {
+            return Err(ContractError::MalformedInput {
+                input: "dynamic_spread_cap".to_string(),
+                reason: "must be <=10000".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_change_limiter(
+        change_limiter: &Option<ChangeLimiterConfig>,
+    ) -> ContractResult<Response> {
+        if let Some(limiter) = change_limiter {
+            if limiter.divisions == 0 {
+                return Err(ContractError::MalformedInput {
+                    input: "change_limiter.divisions".to_string(),
+                    reason: "must be >=1".to_string(),
+                });
+            }
+            if limiter.window_size == 0 {
+                return Err(ContractError::MalformedInput {
+                    input: "change_limiter.window_size".to_string(),
+                    reason: "must be >=1".to_string(),
+                });
+            }
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_staking_target(
+        staking_target: &Option<String>,
+        unbonding_period_seconds: u64,
+    ) -> ContractResult<Response> {
+        if staking_target.is_some() && unbonding_period_seconds == 0 {
+            return Err(ContractError::MalformedInput {
+                input: "unbonding_period_seconds".to_string(),
+                reason: "must be >=1 when staking_target is set".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_swap_fee_bps(swap_fee_bps: u64) -> ContractResult<Response> {
+        if swap_fee_bps > 10000 {
+            return Err(ContractError::MalformedInput {
+                input: "swap_fee_bps".to_string(),
+                reason: "must be <=10000".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_performance_fee_bps(performance_fee_bps: u64) -> ContractResult<Response> {
+        if performance_fee_bps > 10000 {
+            return Err(ContractError::MalformedInput {
+                input: "performance_fee_bps".to_string(),
+                reason: "must be <=10000".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_management_fee_bps(management_fee_bps: u64) -> ContractResult<Response> {
+        if management_fee_bps > 10000 {
+            return Err(ContractError::MalformedInput {
+                input: "management_fee_bps".to_string(),
+                reason: "must be <=10000".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_rebalance_target_bps(rebalance_target_bps: u64) -> ContractResult<Response> {
+        if rebalance_target_bps > 10000 {
+            return Err(ContractError::MalformedInput {
+                input: "rebalance_target_bps".to_string(),
+                reason: "must be <=10000".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_imbalance_bps(imbalance_bps: u64) -> ContractResult<Response> {
+        if imbalance_bps > 10000 {
+            return Err(ContractError::MalformedInput {
+                input: "imbalance_bps".to_string(),
+                reason: "must be <=10000".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_deposit_band(deposit_band: &Option<DepositBandConfig>) -> ContractResult<Response> {
+        if let Some(band) = deposit_band {
+            if band.half_width > 0 && band.tick_step == 0 {
+                return Err(ContractError::MalformedInput {
+                    input: "deposit_band.tick_step".to_string(),
+                    reason: "must be >=1 when half_width is >0".to_string(),
+                });
+            }
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_fee_splitter(fee_splitter: &Option<Vec<(String, u64)>>) -> ContractResult<Response> {
+        if let Some(recipients) = fee_splitter {
+            let total_weight: u64 = recipients.iter().map(|(_, weight)| weight).sum();
+            if total_weight != crate::state::FEE_SPLITTER_DENOMINATOR {
+                return Err(ContractError::InvalidFeeSplitterWeights {
+                    actual: total_weight,
+                    expected: crate::state::FEE_SPLITTER_DENOMINATOR,
+                });
+            }
+        }
+        Ok(Response::new())
+    }
+
+    pub fn validate_ema_alpha(ema_alpha: Decimal) -> ContractResult<Response> {
+        if ema_alpha > Decimal::one() {
+            return Err(ContractError::MalformedInput {
+                input: "ema_alpha".to_string(),
+                reason: "must be <=1".to_string(),
+            });
+        }
+        Ok(Response::new())
+    }
 
-        // Check if the fee is in the allowed_fees array
+    /// Validates `fee` against the DEX module's currently live fee tiers
+    /// (queried at instantiate / `RefreshFeeTiers` time and cached in
+    /// `ALLOWED_FEE_TIERS`, falling back to `FALLBACK_FEE_TIERS`).
+    pub fn validate_base_fee(fee: u64, allowed_fees: &[u64]) -> ContractResult<Response> {
         if !allowed_fees.contains(&fee) {
             return Err(ContractError::InvalidBaseFee { fee });
         }
@@ -128,19 +1385,239 @@ impl InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    // deposit funds to use for market making
-    Deposit {},
-    // withdraw free unutilised funds
-    Withdraw {},
+    // deposit funds to use for market making, minting vault shares to the
+    // sender (or `beneficiary`, if set). `min_shares_out`, if set, reverts
+    // the deposit if fewer shares would be minted. `auto_balance`, if true,
+    // swaps the excess side of a single-sided/imbalanced deposit toward
+    // `Config::imbalance_bps` before minting, so shares are minted on
+    // balanced value instead of raw deposited value. This is also how a
+    // single-asset (one denom, zero of the other) deposit gets rebalanced
+    // into the pool's target ratio -- there is no separate
+    // "deposit_single_sided" variant, since `auto_balance` already covers
+    // both the single-sided and merely-lopsided cases identically via
+    // `utils::auto_balance_deposit`'s DEX-simulated swap.
+    Deposit {
+        min_shares_out: Option<Uint128>,
+        beneficiary: Option<String>,
+        auto_balance: bool,
+    },
+    // CW20 receiver hook: accepts a token0/token1 deposit paid in a
+    // configured `Config::cw20_token_0`/`cw20_token_1`, following the
+    // standard `Cw20ReceiveMsg` "send with attached message" pattern.
+    // `cw20_msg.msg` deserializes into `Cw20HookMsg`
+    Receive(Cw20ReceiveMsg),
+    // burn `amount` vault shares and withdraw a pro-rata slice of the idle
+    // token balances. `min_amount_0_out`/`min_amount_1_out`, if set, revert
+    // the withdrawal if the pro-rata payout would fall below them -- this is
+    // what lets a caller refuse a withdrawal that floor-rounds to a zero/dust
+    // payout rather than silently burning shares for nothing. Whatever a
+    // withdrawal's floor division rounds away is not lost: it accrues into
+    // `DustRemainder`/`DUST` (see their docs) and is exposed via
+    // `QueryMsg::GetDust`, rather than being stranded in the contract forever.
+    Withdraw {
+        amount: Uint128,
+        min_amount_0_out: Option<Uint128>,
+        min_amount_1_out: Option<Uint128>,
+        // block height by which the withdrawal must settle (for the async
+        // pro-rata DEX leg, checked again once `handle_user_withdrawal_reply`
+        // receives it); `None` disables the check.
+        deadline: Option<u64>,
+        // delivers the payout to this address instead of the sender, the
+        // same router-friendly `recipient` convention `ExecuteMsg::Swap`
+        // already uses. Ungated: the sender only ever redirects the payout
+        // of their *own* burned shares, so unlike a hypothetical
+        // withdraw-another-account's-shares feature, there's no privilege
+        // here for an owner gate to actually guard.
+        receiver: Option<String>,
+    },
+    // burns the position NFT minted to the sender by `Deposit`, then withdraws
+    // the shares it was holding exactly like
+    // `Withdraw { amount: <those shares>, min_amount_0_out, min_amount_1_out, deadline, receiver }`
+    WithdrawPosition {
+        token_id: u64,
+        min_amount_0_out: Option<Uint128>,
+        min_amount_1_out: Option<Uint128>,
+        deadline: Option<u64>,
+        receiver: Option<String>,
+    },
     // // cancels and withdraws all active and filled Limit orders
     DexDeposit {},
     DexWithdrawal {},
-    // // pauses all deposit functionality
-    // Pause {},
-    // // helper to atomically purge and withdraw
-    // PurgeAnddWithdraw {},
-    // // helper to atomically purge and pause
-    // PurgeAndPause {},
+    // admin-only: sets the graduated killswitch. `DepositsFrozen` rejects
+    // Deposit/DexDeposit while leaving Withdraw/DexWithdrawal available;
+    // `Frozen` halts everything except owner/admin-gated messages
+    SetContractStatus {
+        status: ContractStatus,
+        reason: Option<String>,
+    },
+    // admin-only: cancels all open limit orders then sweeps the free funds to the owner
+    PurgeAndWithdraw {},
+    // admin-only: cancels all open limit orders then sets status to `Frozen`
+    PurgeAndPause {},
+    // re-syncs the cached allowed fee-tier set from the DEX module
+    RefreshFeeTiers {},
+    // admin-only: configures a fixed-rate emission of `reward_denom` to
+    // depositors, pro-rata by vault shares, over [start_time, end_time]
+    SetIncentives {
+        reward_denom: String,
+        total_reward: Uint128,
+        start_time: u64,
+        end_time: u64,
+    },
+    // claims the sender's currently accrued share of the configured incentives
+    ClaimIncentives {},
+    // admin/cron-only: dispatches a claim message to every configured
+    // `Config::reward_claim_contracts`; `handle_reward_claim_reply` folds
+    // whatever comes back (diffed against a pre-claim balance snapshot) into
+    // `DISTRIBUTED_REWARDS` and bumps `EXTERNAL_REWARD_PER_SHARE`
+    CollectRewards {},
+    // pays the sender their pro-rata share (by vault shares) of every
+    // external reward denom `CollectRewards` has realized, accrued since
+    // their last `ClaimRewards`/deposit/withdraw
+    ClaimRewards {},
+    // admin-only: (re)configures the external contracts `CollectRewards`
+    // claims incentive emissions from. `contracts: []` disables `CollectRewards`
+    SetRewardClaimContracts { contracts: Vec<String> },
+    // admin-only: pays the whole-unit rounding dust `withdraw`'s floor
+    // division has carved out of `DustRemainder` so far to `Config::fee_collector`,
+    // rather than waiting for the vault's last share to be burned
+    SweepDust {},
+    // admin-only: cancels all open limit orders, realizing accrued swap fees
+    // (and principal) into the vault's idle token_0/token_1 balances
+    SweepFees {},
+    // admin-only: pays out `Config::accrued_fees` (not the vault's whole idle
+    // balance) to `Config::fee_splitter`'s recipients, pro-rata by weight
+    DistributeFees {},
+    // admin-only: (re)configures the performance fee rate and the
+    // `fee_splitter` recipients it (and `DistributeFees`) pays out to
+    SetPerformanceFee {
+        fee_bps: u64,
+        recipients: Vec<(String, u64)>,
+    },
+    // admin-only: charges `Config::performance_fee_bps` of each token's new
+    // per-share appreciation since the last harvest and pays it out to
+    // `Config::fee_splitter`'s recipients, pro-rata by weight; also accrues
+    // `Config::management_fee_bps` of time elapsed since the last harvest,
+    // minted as fresh shares to `Config::fee_collector`
+    HarvestPerformanceFee {},
+    // admin-only: (re)configures the time-based management fee rate and the
+    // collector minted shares accrue to
+    SetManagementFee { fee_bps: u64, collector: String },
+    // swaps `amount_in` of `token_in` directly against the vault's own
+    // reserves at the more conservative of an oracle quote and a
+    // constant-product quote, rejecting if the fill is below `min_out`.
+    // `recipient` defaults to the sender, letting a router swap on a user's
+    // behalf and deliver `token_out` straight to them
+    Swap {
+        token_in: String,
+        amount_in: Uint128,
+        min_out: Uint128,
+        recipient: Option<String>,
+    },
+    // whitelist-gated (same as `DexDeposit`): rebuilds and resubmits a
+    // `FailedDeposit` recorded by the `reply` entry point, clearing the
+    // entry as it resubmits; a retry that fails again is recorded under a new id
+    RetryDeposit { id: u64 },
+    // bonds `amount` of the sender's vault shares to `Config::staking_target`.
+    // bonded shares keep earning the vault's own yield but cannot be
+    // withdrawn until `Unbond` then `Config::unbonding_period_seconds` elapses
+    Bond { amount: Uint128 },
+    // starts unbonding `amount` of the sender's bonded shares; they remain
+    // locked for `Config::unbonding_period_seconds` before `withdraw` will
+    // burn them
+    Unbond { amount: Uint128 },
+    // burns `shares` and snapshots their redemption value into a queued
+    // `WITHDRAWAL_QUEUE` entry instead of paying out immediately; `Claim`
+    // pays it out once `Config::withdrawal_queue_period_seconds` elapses
+    QueueWithdrawal { shares: Uint128 },
+    // pays out and removes every one of the sender's `QueueWithdrawal`
+    // entries whose `release_at` has passed, leaving unmatured entries intact
+    Claim {},
+    // admin-only: adds `pair_data` to the vault-wide pair registry, rejecting
+    // a denom_0/denom_1 combination that's already registered. Groundwork for
+    // running one vault across many markets; see `state::REGISTERED_PAIRS`
+    RegisterPair { pair_data: crate::state::PairData },
+    // admin-only: removes a previously `RegisterPair`-ed denom_0/denom_1 pair
+    DeregisterPair { denom_0: String, denom_1: String },
+    // admin-only: (re)configures the additional `oracle_contracts` queried
+    // alongside the primary x/oracle feed, the `min_sources` quorum
+    // `get_prices` requires from them before trusting their median, and the
+    // `max_oracle_deviation_bps` guard against any one source straying too
+    // far from that median. `oracle_contracts: []` disables multi-source
+    // aggregation
+    SetOracleSources {
+        oracle_contracts: Vec<String>,
+        min_sources: u64,
+        max_oracle_deviation_bps: u64,
+    },
+    // admin-only: (re)configures the LST redemption-rate adapter `get_prices`
+    // applies to whichever of `pair_data.token_0`/`token_1` matches
+    // `lst_asset_denom`. `adapter: None` disables the adjustment
+    SetRedemptionAdapter {
+        source: Option<RedemptionRateSourceInput>,
+        lst_asset_denom: Option<String>,
+        min_redemption_rate: Option<PrecDec>,
+        max_redemption_rate: Option<PrecDec>,
+        max_redemption_rate_change_bps: Option<u64>,
+        max_rate_age_seconds: Option<u64>,
+    },
+    // admin-only: stages `update` as a `PENDING_CONFIG`, eligible to apply
+    // `Config::timelock_blocks` blocks from now via `CommitConfig`. Errors if
+    // an update is already staged; `CancelConfig` it first. Emergency actions
+    // like `SetContractStatus` bypass this entirely and apply immediately
+    UpdateConfig { update: ConfigOverride },
+    // admin-only: applies the staged `PENDING_CONFIG` update once its
+    // timelock has elapsed, snapshotting the live `Config` into
+    // `PREVIOUS_CONFIG` first so `RevertConfig` can undo it
+    CommitConfig {},
+    // admin-only: discards the staged `PENDING_CONFIG` update without applying it
+    CancelConfig {},
+    // admin-only: restores `Config` from `PREVIOUS_CONFIG`, undoing the most
+    // recently committed `UpdateConfig`. One step of rollback only; calling it
+    // twice in a row has no further effect
+    RevertConfig {},
+    // admin-only, one-way: sets `Config::config_frozen`, after which
+    // `UpdateConfig` is rejected forever - there is no `UnfreezeConfig`.
+    // Mirrors the irrevocable freeze of a CW3-style timelock
+    FreezeConfig {},
+    // admin/cron-only: queries `apy_contract`'s `ApySourceQueryMsg::GetApy`
+    // for `instance`, rejects it as `ContractError::ApyTooOld` if its
+    // `block_height` is more than `max_blocks_old` behind the current
+    // height, then folds it into `state::APY_EMA`'s running average for that
+    // `instance` via `ema = alpha * raw + (1 - alpha) * prev_ema` (seeding
+    // `ema = raw` on the first call). `QueryMsg::GetCalculatedFeeTiers`
+    // derives `fee_tiers`/`oracle_skew` from this smoothed value once it
+    // exists, rather than the raw per-call sample
+    UpdateApyEma {
+        apy_contract: String,
+        instance: String,
+        time_span_hours: u64,
+        alpha: PrecDec,
+        max_blocks_old: u64,
+    },
+    // admin-only: (re)configures `Config::signers`/`Config::threshold`, the
+    // quorum `ExecuteConfigUpdate` requires before applying a
+    // `ProposeConfigUpdate`. `signers: []` disables the `PROPOSALS` flow
+    SetSigners {
+        signers: Vec<String>,
+        threshold: u32,
+    },
+    // signer-gated (one of `Config::signers`): stages `update` as a new
+    // `state::PROPOSALS` entry, counting the proposer's own approval
+    // immediately. Returns the assigned id via the `proposal_id` attribute
+    ProposeConfigUpdate { update: ConfigOverride },
+    // signer-gated: records the sender's approval of `PROPOSALS[id]`.
+    // Idempotent - approving twice from the same address has no further
+    // effect
+    ApproveConfigUpdate { id: u64 },
+    // signer-gated: applies `PROPOSALS[id]`'s staged `update` (the same
+    // `apply_to`/`Config::validate` path `CommitConfig` uses) once its
+    // approvals still held by a *current* signer reach `Config::threshold`,
+    // then removes the entry
+    ExecuteConfigUpdate { id: u64 },
+    // signer-gated: discards `PROPOSALS[id]` without applying it, e.g. once
+    // a `SetSigners` rotation has left it unexecutable anyway
+    CancelProposal { id: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -148,6 +1625,316 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     GetFormated {},
     GetDeposits {},
+    GetConfig {},
+    // amounts of token_a/token_b that `address` could currently redeem for their vault shares
+    GetShareValue { address: String },
+    // the admin address and the current graduated killswitch state
+    GetContractStatus {},
+    // the currently cached set of DEX fee tiers that `base_fee` is validated against
+    GetAllowedFeeTiers {},
+    // `address`'s currently claimable incentive reward amount
+    GetPendingIncentives { address: String },
+    // DEX deposits recorded after a `reply_on_error` came back with an error,
+    // awaiting `ExecuteMsg::RetryDeposit`
+    GetFailedDeposits {},
+    // idle bank balance, outstanding in-DEX position reserves, and oracle NAV
+    // for both tokens, folded into a single authoritative total
+    GetTotalValue {},
+    // `address`'s currently bonded shares and any shares still unbonding
+    GetBondedShares { address: String },
+    // `address`'s in-flight `QueueWithdrawal` entries, keyed by their
+    // `WITHDRAWAL_QUEUE` sequence id, oldest first
+    GetWithdrawalQueue { address: String },
+    // the recorded total_shares/idle-balance snapshot at or before `height`
+    GetSharePriceAtHeight { height: u64 },
+    // time-weighted average share price over [start_height, end_height],
+    // integrated across the recorded snapshots that fall in range
+    GetTwapSharePrice { start_height: u64, end_height: u64 },
+    // shares a deposit of token0_amount/token1_amount would mint right now,
+    // without executing it
+    PreviewDeposit {
+        token0_amount: Uint128,
+        token1_amount: Uint128,
+    },
+    // a query authenticated by a `QueryPermit` instead of a plaintext address,
+    // so the caller can prove which address they're querying on behalf of
+    WithPermit {
+        permit: crate::permit::QueryPermit,
+        query: PermitQueryMsg,
+    },
+    // cw721-style position NFT surface, so integrators can treat deposit
+    // positions as composable collateral: the current owner of `token_id`
+    OwnerOf { token_id: u64 },
+    // the shares a position NFT is holding, in its on-chain extension metadata
+    NftInfo { token_id: u64 },
+    // `owner`'s position NFT ids, oldest first, paginated by `start_after`/`limit`
+    Tokens {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // the vault-wide pair registry (`ExecuteMsg::RegisterPair`/
+    // `DeregisterPair`), paginated by `start_after`/`limit`
+    ListPairs {
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    },
+    // the `ExecuteMsg::UpdateConfig` staged in `PENDING_CONFIG`, if any
+    GetPendingConfig {},
+    // the `CONFIG_HISTORY` snapshots recorded by `ExecuteMsg::CommitConfig`/
+    // `RevertConfig`, oldest first, paginated by `start_after`/`limit`
+    ConfigHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // the `accrued_fees` pool `ExecuteMsg::DistributeFees` would pay out
+    // right now, so the split across `fee_splitter` recipients can be
+    // reconciled off-chain before it's triggered
+    GetAccruedFees {},
+    // `address`'s currently claimable `ExecuteMsg::ClaimRewards` amount for
+    // every external reward denom `ExecuteMsg::CollectRewards` has realized
+    GetPendingRewards { address: String },
+    // whole-unit rounding dust `withdraw` has carved out of `DUST_REMAINDER`
+    // so far, per `Config::pair_data` denom - see `DustBalances`'s docs
+    GetDust {},
+    // the current value of one vault share in token0/token1, computed live
+    // from idle balances plus in-DEX position reserves divided by
+    // `total_shares`, plus the block height/timestamp it was computed at so
+    // downstream integrators using the vault LP as a price source can detect
+    // staleness
+    GetRedemptionRate {},
+    // the `amount_out` an `ExecuteMsg::Swap { token_in, amount_in, .. }` would
+    // fill right now, without executing it - same quote math as `swap`
+    SimulateSwap { token_in: String, amount_in: Uint128 },
+    // the `CosmosMsg` list `execute::dex_deposit`'s `prepare_state` call would
+    // currently place to rebalance inventory before a deposit, plus the
+    // `token_0`/`token_1` left over afterward for that deposit to actually
+    // use, without placing anything
+    SimulateDeposit {},
+    // the vault's combined oracle-USD NAV, the per-share value it implies
+    // against `Config::total_shares`, and that share count itself
+    GetNav {},
+    // queries `apy_contract`'s `ApySourceQueryMsg::GetApy` and maps the
+    // realized APY onto a fee-tier ladder/widened `oracle_skew` via
+    // `derive_apy_fee_tiers`, without saving anything - the caller feeds the
+    // result into `ExecuteMsg::UpdateConfig` itself
+    GetCalculatedFeeTiers {
+        apy_contract: String,
+        instance: String,
+        time_span_hours: u64,
+        base_fee: u64,
+        oracle_skew: i32,
+    },
+    // every open `state::PROPOSALS` entry, its staged `update`, and its
+    // current approvals, so `Config::signers` can coordinate toward
+    // `ExecuteMsg::ExecuteConfigUpdate`'s `Config::threshold`
+    ListProposals {},
+    // a dry-run preview of what the next `ExecuteMsg::DexDeposit` would do:
+    // the action it would take (skip, withdrawal-only while deposits are
+    // frozen, or a full rebalance), the `base_fee`/`oracle_skew`/`fee_tiers`
+    // it would deploy with, and the actual `CosmosMsg` list it would emit,
+    // all computed against live oracle/DEX state without broadcasting
+    // anything
+    SimulateVaultUpdate {},
+}
+
+/// Queries gated behind a [`crate::permit::QueryPermit`], dispatched via
+/// `QueryMsg::WithPermit`. The permit's signing address is substituted for
+/// any address these would otherwise take as a plaintext argument.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PermitQueryMsg {
+    // the permit signer's cumulative oracle-valued `Deposit` contribution and
+    // minted shares, checked against `Config::per_address_cap`
+    GetMyDeposits {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct DepositsResponse {
+    pub deposited_value: PrecDec,
+    pub shares_minted: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct OwnerOfResponse {
+    pub owner: String,
+}
+
+/// cw721 `NftInfo`'s `extension` is the position's share claim; this vault
+/// has no separate token URI/metadata to report.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct NftInfoResponse {
+    pub extension: PositionNft,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TokensResponse {
+    pub tokens: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ListPairsResponse {
+    pub pairs: Vec<crate::state::PairData>,
+}
+
+/// `QueryMsg::ListProposals`'s response: every open `PROPOSALS` entry keyed
+/// by id, the same `(id, entry)` pairing `WithdrawalQueueResponse` uses.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ProposalsResponse {
+    pub proposals: Vec<(u64, crate::state::ConfigProposal)>,
+}
+
+/// One `CONFIG_HISTORY` entry: the full `Config` as of the block height it
+/// was recorded at.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConfigSnapshot {
+    pub height: u64,
+    pub config: crate::state::Config,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConfigHistoryResponse {
+    pub snapshots: Vec<ConfigSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ContractStatusResponse {
+    pub admin: String,
+    pub status: ContractStatus,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ShareValueResponse {
+    pub shares: Uint128,
+    pub total_shares: Uint128,
+    pub amount_0: Uint128,
+    pub amount_1: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TotalValueResponse {
+    /// undeployed, bank-held balance
+    pub idle_0: Uint128,
+    pub idle_1: Uint128,
+    /// outstanding DEX limit-order/position reserves, from simulating a full
+    /// withdrawal of every open position
+    pub in_dex_0: Uint128,
+    pub in_dex_1: Uint128,
+    /// oracle-denominated NAV of `idle_0 + in_dex_0` and `idle_1 + in_dex_1` combined
+    pub nav: PrecDec,
+    /// the 2-asset StableSwap invariant `D` over `idle_0 + in_dex_0`/
+    /// `idle_1 + in_dex_1`, when `Config::stableswap_amplification > 0` and
+    /// both reserves are nonzero; a share is worth `stableswap_invariant /
+    /// total_shares` under that pricing mode. `None` when the mode is
+    /// disabled or the curve can't be solved (e.g. an empty vault).
+    pub stableswap_invariant: Option<PrecDec>,
+}
+
+/// Single-call oracle-USD share price: `query_total_value`'s NAV divided by
+/// the vault's actual share-supply source. Named `lp_supply` for parity with
+/// vaults that mint a real tokenfactory LP denom (the sibling `mmvault`
+/// contract's `lp_denom`/`BankQuery::Supply`), but this vault has no such
+/// mint - `deposit`/`withdraw` track ownership entirely through the internal
+/// `Config::total_shares` counter (see `extract_denom`'s doc comment), so
+/// that's what `lp_supply` reports here.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct NavResponse {
+    pub total_value_usd: PrecDec,
+    pub nav_per_share: PrecDec,
+    pub lp_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BondedSharesResponse {
+    pub bonded: Uint128,
+    pub unbonding: Vec<UnbondingEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct WithdrawalQueueResponse {
+    pub entries: Vec<(u64, crate::state::UnbondEntry)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SharePriceResponse {
+    pub height: u64,
+    pub total_shares: Uint128,
+    pub price_0_per_share: PrecDec,
+    pub price_1_per_share: PrecDec,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TwapSharePriceResponse {
+    pub twap_price_0_per_share: PrecDec,
+    pub twap_price_1_per_share: PrecDec,
+}
+
+/// Live per-share redemption value, the same division
+/// `handle_user_withdrawal_reply` implicitly performs when it apportions its
+/// idle/DEX balances against `total_shares`, but computed read-only and
+/// independent of any one withdrawer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RedemptionRateResponse {
+    pub total_shares: Uint128,
+    pub balance_0: Uint128,
+    pub balance_1: Uint128,
+    pub rate_0_per_share: PrecDec,
+    pub rate_1_per_share: PrecDec,
+    pub height: u64,
+    pub time: u64,
+}
+
+/// `amount_out` an `ExecuteMsg::Swap` of `amount_in` would currently fill
+/// for, computed with the exact same quote `swap` uses (the more
+/// conservative of an oracle quote and a constant-product quote) but without
+/// executing it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SimulateSwapResponse {
+    pub amount_out: Uint128,
+}
+
+/// `CosmosMsg` list `prepare_state` would currently place to rebalance
+/// inventory ahead of a deposit, computed against live oracle prices and
+/// idle balances but without placing anything. `token_0_usable`/
+/// `token_1_usable` are exactly what `prepare_state` itself returns for
+/// those names: the idle amount of each leg left over once the rebalance
+/// orders above are accounted for - not yet deposited, and so not "usable"
+/// until the rest of that same `ExecuteMsg::DexDeposit` call deploys them. There's no
+/// separate leftover/unusable field: that's what these two already are.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SimulateDepositResponse {
+    pub messages: Vec<CosmosMsg>,
+    pub token_0_usable: Uint128,
+    pub token_1_usable: Uint128,
+}
+
+/// `QueryMsg::SimulateVaultUpdate`'s response: what the next
+/// `ExecuteMsg::DexDeposit` would do right now, without broadcasting
+/// anything. `action` is one of `"withdrawal_only"` (deposits frozen per
+/// `ContractStatus`, so nothing would be placed), `"skipped_no_drift"` (the
+/// same no-op `dex_deposit` itself would take, per
+/// `Config::rebalance_drift_tolerance_ticks`), or `"would_rebalance"` (a
+/// full `prepare_state`/deposit cycle would run, in which case `messages`
+/// carries the actual `CosmosMsg` list it would emit). `base_fee`/
+/// `oracle_skew`/`fee_tiers` are always the values the next call would
+/// deploy with, regardless of `action`, so a caller can tell a skip apart
+/// from a stale/unchanged config.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SimulateVaultUpdateResponse {
+    pub action: String,
+    pub base_fee: u64,
+    pub oracle_skew: i32,
+    pub fee_tiers: Vec<crate::state::FeeTier>,
+    pub messages: Vec<CosmosMsg>,
+}
+
+/// Shares `ExecuteMsg::Deposit` would mint for a deposit of `token0_amount`/
+/// `token1_amount` right now, computed with the exact same math `deposit`
+/// uses but without executing it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PreviewDepositResponse {
+    pub shares_minted: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -155,6 +1942,61 @@ pub struct CombinedPriceResponse {
     pub token_0_price: Decimal,
     pub token_1_price: Decimal,
     pub price_0_to_1: Decimal,
+    /// `token_0_price` before `10^decimals_0` normalization, i.e. the oracle
+    /// quote exactly as queried/aggregated (per one atomic unit of
+    /// `pair_data.token_0`, not per one whole token). `get_deposit_data`'s
+    /// `decimal_tick_offset`/`true_price`/`real_amount`/`atomic_amount` all
+    /// consume the normalized `token_0_price` above, never this raw value -
+    /// it's exposed for observability/debugging of the oracle feed itself.
+    pub token_0_price_raw: PrecDec,
+    /// see `token_0_price_raw`, for `token_1_price`.
+    pub token_1_price_raw: PrecDec,
+    /// reported oracle confidence/standard-deviation band for token_0's
+    /// price, if the queried oracle source exposes one.
+    pub token_0_confidence: Option<Decimal>,
+    /// reported oracle confidence/standard-deviation band for token_1's
+    /// price, if the queried oracle source exposes one.
+    pub token_1_confidence: Option<Decimal>,
+    /// tracked EMA of `token_0_price`, per `Config::price_ema_tau_seconds`.
+    /// Equal to `token_0_price` itself until `Deposit` has seeded a sample.
+    pub token_0_ema: PrecDec,
+    /// tracked EMA of `token_1_price`, per `Config::price_ema_tau_seconds`.
+    /// Equal to `token_1_price` itself until `Deposit` has seeded a sample.
+    pub token_1_ema: PrecDec,
+    /// redemption rate fetched from `Config::redemption_adapter` and applied
+    /// to the matching leg's price above. `None` when `redemption_adapter`
+    /// is unset.
+    pub redemption_rate: Option<PrecDec>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingIncentivesResponse {
+    pub reward_denom: Option<String>,
+    pub pending: Uint128,
+}
+
+/// `address`'s currently claimable `ExecuteMsg::ClaimRewards` balance, one
+/// `Coin` per external reward denom `ExecuteMsg::CollectRewards` has ever
+/// realized (zero-amount denoms omitted).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingRewardsResponse {
+    pub pending: Vec<Coin>,
+}
+
+/// Whole-unit rounding dust `withdraw` has carved out of `DustRemainder` so
+/// far, per `Config::pair_data` denom - real, sendable balance no depositor's
+/// share currently accounts for. Zero for both once the vault's last share
+/// is burned, since `withdraw` folds it into that final payout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct DustResponse {
+    pub token_0: Uint128,
+    pub token_1: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FailedDepositEntry {
+    pub id: u64,
+    pub deposit: FailedDeposit,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -0,0 +1,98 @@
+use cosmwasm_std::{to_json_vec, Addr, Api, Binary, CanonicalAddr, Deps};
+use ripemd::Ripemd160;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{ContractError, ContractResult};
+
+/// A single capability a [`QueryPermit`] can grant. Checked by the private
+/// query it's presented to; a permit that doesn't list the permission the
+/// query requires is rejected with `ContractError::PermitNotAuthorized`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Deposits,
+}
+
+/// The data a permit's signature is computed over. Nothing about a permit is
+/// stored on-chain, so a permit stays valid for as long as its `chain_id`
+/// matches and its signature verifies; signing new params (e.g. with a
+/// different `permit_name`) does not revoke one issued earlier.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PermitParams {
+    pub permit_name: String,
+    pub chain_id: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PermitSignature {
+    pub pub_key: Binary,
+    pub signature: Binary,
+}
+
+/// A self-contained, off-chain-signed credential that lets its holder run a
+/// `QueryMsg::WithPermit` query as the address that signed it, without that
+/// address submitting a transaction. The querying address is derived from
+/// `signature.pub_key` rather than taken from any caller-supplied string, so
+/// a permit can never be replayed to read a different address's data.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct QueryPermit {
+    pub params: PermitParams,
+    pub signature: PermitSignature,
+}
+
+impl QueryPermit {
+    /// Verifies `self.signature` against `self.params`, checks `chain_id`
+    /// matches, and checks `permission` is granted. Returns the bech32
+    /// address that signed the permit.
+    pub fn verify(&self, deps: Deps, chain_id: &str, permission: Permission) -> ContractResult<Addr> {
+        if self.params.chain_id != chain_id {
+            return Err(ContractError::InvalidPermit {
+                reason: format!(
+                    "permit was signed for chain_id {}, this chain is {}",
+                    self.params.chain_id, chain_id
+                ),
+            });
+        }
+        if !self.params.permissions.contains(&permission) {
+            return Err(ContractError::PermitNotAuthorized {
+                permission: format!("{:?}", permission),
+            });
+        }
+
+        let sign_bytes = to_json_vec(&self.params)?;
+        let message_hash = Sha256::digest(&sign_bytes);
+        let verified = deps
+            .api
+            .secp256k1_verify(&message_hash, &self.signature.signature, &self.signature.pub_key)
+            .map_err(|e| ContractError::InvalidPermit {
+                reason: format!("signature verification failed: {e}"),
+            })?;
+        if !verified {
+            return Err(ContractError::InvalidPermit {
+                reason: "signature does not match pub_key".to_string(),
+            });
+        }
+
+        pubkey_to_address(deps.api, &self.signature.pub_key)
+    }
+}
+
+/// Derives the standard Cosmos SDK account address `ripemd160(sha256(pub_key))`
+/// for a secp256k1 `pub_key`, then lets `deps.api` bech32-encode it the same
+/// way it would any other canonical address, so this contract never needs
+/// its own bech32 prefix/encoding logic.
+fn pubkey_to_address(api: &dyn Api, pub_key: &[u8]) -> ContractResult<Addr> {
+    let sha256_digest = Sha256::digest(pub_key);
+    let ripemd_digest = Ripemd160::digest(sha256_digest);
+    let canonical = CanonicalAddr::from(ripemd_digest.as_slice());
+    let addr = api.addr_humanize(&canonical).map_err(|e| ContractError::InvalidPermit {
+        reason: format!("could not derive address from pub_key: {e}"),
+    })?;
+    Ok(addr)
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::ContractError;
+
+/// A fee or share-weight quantity, letting operators author config in
+/// whichever unit they think in (`"30bps"`, `"0.30%"`, `"0.003"`, `"15"`)
+/// while the contract stores and computes on a single exact integer count
+/// of basis points — the same unit `FeeTier::fee`/`Config::dynamic_spread_cap`
+/// already use. Parsing rules, applied by suffix:
+/// - `"<int>bps"` — a literal basis-point count.
+/// - `"<decimal>%"` — a percentage (`1% == 100bps`).
+/// - `"<decimal>"` with a decimal point and no suffix — a fraction of `1`
+///   (`1.0 == 10000bps`).
+/// - a bare integer with no decimal point and no suffix — a literal
+///   basis-point count, same as the `bps` suffix; this is what lets
+///   already-deployed configs that pass raw integers keep working.
+///
+/// All parsing is exact decimal-string arithmetic (no `f64`): the Wasm VM
+/// rejects floating-point instructions outright, so converting operator
+/// input into the stored integer can never round through a float. Inputs
+/// with more fractional precision than basis points can represent (more
+/// than 4 digits after the point for a bare fraction, more than 2 for a
+/// percentage) are rejected rather than silently rounded, since which way
+/// to round is ambiguous to the caller.
+///
+/// [`BasisPoints`] always serializes back to the single canonical
+/// `"<bps>bps"` form, so `"0.30%"` and `"30bps"` round-trip identically
+/// and the same value is never re-emitted as `"0.300%"` or `"30.0bps"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BasisPoints(u64);
+
+impl BasisPoints {
+    /// `10000` basis points, the `100%`/whole-quantity ceiling config
+    /// fields built on `BasisPoints` should validate against.
+    pub const MAX: BasisPoints = BasisPoints(10_000);
+
+    pub fn from_bps(bps: u64) -> Self {
+        Self(bps)
+    }
+
+    pub fn bps(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for BasisPoints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}bps", self.0)
+    }
+}
+
+impl FromStr for BasisPoints {
+    type Err = ContractError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let malformed = || ContractError::MalformedInput {
+            input: raw.to_string(),
+            reason: "expected a bare basis-point integer, a percentage like \"0.30%\", a \
+                     fraction like \"0.003\", or a \"<n>bps\" literal"
+                .to_string(),
+        };
+        let trimmed = raw.trim();
+        let bps = if let Some(digits) = trimmed.strip_suffix("bps") {
+            decimal_str_to_scaled_integer(digits.trim(), 0).map_err(|_| malformed())?
+        } else if let Some(digits) = trimmed.strip_suffix('%') {
+            decimal_str_to_scaled_integer(digits.trim(), 2).map_err(|_| malformed())?
+        } else if trimmed.contains('.') {
+            decimal_str_to_scaled_integer(trimmed, 4).map_err(|_| malformed())?
+        } else {
+            decimal_str_to_scaled_integer(trimmed, 0).map_err(|_| malformed())?
+        };
+        Ok(BasisPoints(bps))
+    }
+}
+
+impl Serialize for BasisPoints {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for BasisPoints {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        BasisPoints::from_str(&raw).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parses a plain decimal string (`"whole"` or `"whole.frac"`, no sign, no
+/// suffix) into `whole * 10^scale_pow10 + frac` padded/validated against
+/// that same scale — i.e. interprets the string as a decimal number with
+/// exactly `scale_pow10` digits of fractional precision, rejecting inputs
+/// that carry more fractional digits than that (since truncating or
+/// rounding them away would silently discard precision the caller wrote
+/// down explicitly).
+fn decimal_str_to_scaled_integer(s: &str, scale_pow10: u32) -> Result<u64, ()> {
+    if s.is_empty() {
+        return Err(());
+    }
+    let (whole_str, frac_str) = match s.split_once('.') {
+        Some((whole, frac)) => (whole, frac),
+        None => (s, ""),
+    };
+    if whole_str.is_empty() || !whole_str.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(());
+    }
+    if !frac_str.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(());
+    }
+    if frac_str.len() > scale_pow10 as usize {
+        return Err(());
+    }
+    let whole: u64 = whole_str.parse().map_err(|_| ())?;
+    let scale = 10u64.checked_pow(scale_pow10).ok_or(())?;
+    let padded_frac = format!("{:0<width$}", frac_str, width = scale_pow10 as usize);
+    let frac: u64 = if padded_frac.is_empty() { 0 } else { padded_frac.parse().map_err(|_| ())? };
+    whole.checked_mul(scale).and_then(|w| w.checked_add(frac)).ok_or(())
+}
@@ -1,12 +1,30 @@
-use crate::error::ContractResult;
-use crate::msg::CombinedPriceResponse;
-use crate::state::CONFIG;
+use crate::error::{ContractError, ContractResult};
+use crate::msg::{
+    ApyResponse, ApySourceQueryMsg, BondedSharesResponse, CalculatedFeeTiersResponse,
+    CombinedPriceResponse, ContractStatusResponse, DepositsResponse, DustResponse,
+    FailedDepositEntry, ListPairsResponse, NavResponse, NftInfoResponse, OwnerOfResponse,
+    PendingIncentivesResponse, PendingRewardsResponse, PermitQueryMsg, PreviewDepositResponse,
+    ProposalsResponse, RedemptionRateResponse, SharePriceResponse, ShareValueResponse,
+    SimulateDepositResponse, SimulateSwapResponse, SimulateVaultUpdateResponse, TokensResponse,
+    TotalValueResponse, TwapSharePriceResponse, WithdrawalQueueResponse,
+};
+use crate::permit::{Permission, QueryPermit};
+use crate::state::{
+    ContractStatus, ALLOWED_FEE_TIERS, APY_EMA, BONDED_SHARES, CONFIG, DEPOSITS,
+    DISTRIBUTED_REWARDS, DUST, EXTERNAL_REWARD_PER_SHARE, FAILED_DEPOSITS, FALLBACK_FEE_TIERS,
+    LAST_DEPLOYED_STATE, POSITIONS, POSITIONS_BY_OWNER, PROPOSALS, REWARD_PER_SHARE, SHARES,
+    SNAPSHOTS, UNBONDING_SHARES, USER_EXTERNAL_REWARD_DEBT, USER_REWARD_DEBT, WITHDRAWAL_QUEUE,
+};
 use crate::utils::*;
-use cosmwasm_std::{to_json_binary, Binary, Deps, Env};
+use cosmwasm_std::{to_json_binary, Addr, Binary, Deps, Env, Order, Uint128};
+use cw_storage_plus::Bound;
 use neutron_std::types::neutron::dex::DexQuerier;
+use neutron_std::types::neutron::util::precdec::PrecDec;
 
 pub fn query_recent_valid_prices_formatted(deps: Deps, env: Env) -> ContractResult<Binary> {
-    let combined_responce: CombinedPriceResponse = get_prices(deps, env)?;
+    let combined_responce: CombinedPriceResponse = get_prices(deps, env.clone())?;
+    let config = CONFIG.load(deps.storage)?;
+    validate_price_reliability(deps, &env, &config, &combined_responce)?;
 
     Ok(to_json_binary(&combined_responce)?)
 }
@@ -24,3 +42,729 @@ pub fn query_config(deps: Deps, _env: Env) -> ContractResult<Binary> {
     let config = CONFIG.load(deps.storage)?;
     Ok(to_json_binary(&config)?)
 }
+
+/// The `ExecuteMsg::UpdateConfig` currently staged in `PENDING_CONFIG`, or
+/// `None` if nothing is staged.
+pub fn query_pending_config(deps: Deps, _env: Env) -> ContractResult<Binary> {
+    let pending = crate::state::PENDING_CONFIG.may_load(deps.storage)?;
+    Ok(to_json_binary(&pending)?)
+}
+
+/// `CONFIG_HISTORY` snapshots recorded by `ExecuteMsg::CommitConfig`/
+/// `RevertConfig`, oldest first, paginated by `start_after`/`limit`.
+pub fn query_config_history(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> ContractResult<Binary> {
+    let limit = limit.unwrap_or(30) as usize;
+    let snapshots = crate::state::CONFIG_HISTORY
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|entry| entry.map(|(height, config)| crate::msg::ConfigSnapshot { height, config }))
+        .collect::<cosmwasm_std::StdResult<Vec<_>>>()?;
+    Ok(to_json_binary(&crate::msg::ConfigHistoryResponse { snapshots })?)
+}
+
+/// Returns `address`'s per-user `SHARES` balance alongside the token_0/token_1
+/// amounts it could currently redeem for, valued pro-rata against the vault's
+/// true inventory (idle balances plus outstanding in-DEX position reserves)
+/// and the aggregate `Config.total_shares`. This is already the per-address
+/// share accounting query; `Config.per_address_cap` (checked in `deposit`
+/// against the beneficiary's cumulative `DEPOSITS` value) is the per-user
+/// deposit cap.
+pub fn query_share_value(deps: Deps, env: Env, address: String) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let addr = deps.api.addr_validate(&address)?;
+    let shares = SHARES.may_load(deps.storage, addr)?.unwrap_or_default();
+
+    let (amount_0, amount_1) = if config.total_shares.is_zero() || shares.is_zero() {
+        (Uint128::zero(), Uint128::zero())
+    } else {
+        let idle_0 = deps
+            .querier
+            .query_balance(env.contract.address.clone(), config.pair_data.token_0.denom.clone())?
+            .amount;
+        let idle_1 = deps
+            .querier
+            .query_balance(env.contract.address.clone(), config.pair_data.token_1.denom.clone())?
+            .amount;
+        let (in_dex_0, in_dex_1) = get_in_dex_token_amounts(deps, env, &config)?;
+        (
+            (idle_0 + in_dex_0).multiply_ratio(shares, config.total_shares),
+            (idle_1 + in_dex_1).multiply_ratio(shares, config.total_shares),
+        )
+    };
+
+    Ok(to_json_binary(&ShareValueResponse {
+        shares,
+        total_shares: config.total_shares,
+        amount_0,
+        amount_1,
+    })?)
+}
+
+pub fn query_contract_status(deps: Deps, _env: Env) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(to_json_binary(&ContractStatusResponse {
+        admin: config.admin.to_string(),
+        status: config.status,
+        reason: config.status_reason,
+    })?)
+}
+
+/// The `Config::accrued_fees` pool `ExecuteMsg::DistributeFees` would pay out
+/// right now, so the split across `fee_splitter` recipients can be
+/// reconciled off-chain before it's triggered.
+pub fn query_accrued_fees(deps: Deps, _env: Env) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(to_json_binary(&config.accrued_fees)?)
+}
+
+/// The currently cached set of DEX fee tiers that `base_fee` is validated
+/// against, falling back to `FALLBACK_FEE_TIERS` if never refreshed.
+pub fn query_allowed_fee_tiers(deps: Deps, _env: Env) -> ContractResult<Binary> {
+    let allowed_fee_tiers = ALLOWED_FEE_TIERS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| FALLBACK_FEE_TIERS.to_vec());
+    Ok(to_json_binary(&allowed_fee_tiers)?)
+}
+
+/// `address`'s currently claimable incentive reward, as of the last time
+/// rewards were accrued (i.e. not including any time elapsed since the vault
+/// was last touched). `None` `reward_denom` means no incentives are configured.
+pub fn query_pending_incentives(deps: Deps, _env: Env, address: String) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let addr = deps.api.addr_validate(&address)?;
+
+    let pending = match &config.incentives {
+        Some(_) => {
+            let shares = SHARES.may_load(deps.storage, addr.clone())?.unwrap_or_default();
+            let reward_per_share = REWARD_PER_SHARE.may_load(deps.storage)?.unwrap_or_default();
+            let reward_debt = USER_REWARD_DEBT
+                .may_load(deps.storage, addr)?
+                .unwrap_or_default();
+            pending_incentives(shares, reward_per_share, reward_debt)?
+        }
+        None => Uint128::zero(),
+    };
+
+    Ok(to_json_binary(&PendingIncentivesResponse {
+        reward_denom: config.incentives.map(|i| i.reward_denom),
+        pending,
+    })?)
+}
+
+/// `address`'s currently claimable `ExecuteMsg::ClaimRewards` balance, as of
+/// the last settled `ExecuteMsg::CollectRewards` (i.e. not including any
+/// claim still in flight). Mirrors `query_pending_incentives`, generalized
+/// across however many denoms `DISTRIBUTED_REWARDS` has ever realized.
+pub fn query_pending_rewards(deps: Deps, _env: Env, address: String) -> ContractResult<Binary> {
+    let addr = deps.api.addr_validate(&address)?;
+    let shares = SHARES.may_load(deps.storage, addr.clone())?.unwrap_or_default();
+    let distributed = DISTRIBUTED_REWARDS.may_load(deps.storage)?.unwrap_or_default();
+
+    let mut pending = vec![];
+    for coin in distributed.0.iter() {
+        let reward_per_share = EXTERNAL_REWARD_PER_SHARE
+            .may_load(deps.storage, coin.denom.clone())?
+            .unwrap_or_default();
+        let reward_debt = USER_EXTERNAL_REWARD_DEBT
+            .may_load(deps.storage, (addr.clone(), coin.denom.clone()))?
+            .unwrap_or_default();
+        let owed = pending_incentives(shares, reward_per_share, reward_debt)?;
+        if !owed.is_zero() {
+            pending.push(cosmwasm_std::Coin { denom: coin.denom.clone(), amount: owed });
+        }
+    }
+
+    Ok(to_json_binary(&PendingRewardsResponse { pending })?)
+}
+
+/// Whole-unit rounding dust `withdraw` has carved out of `DustRemainder` so
+/// far, per `Config::pair_data` denom. See `DustBalances`'s docs.
+pub fn query_dust(deps: Deps, _env: Env) -> ContractResult<Binary> {
+    let dust = DUST.may_load(deps.storage)?.unwrap_or_default();
+    Ok(to_json_binary(&DustResponse { token_0: dust.token_0, token_1: dust.token_1 })?)
+}
+
+/// DEX deposits recorded after a `reply_on_error` came back with an error,
+/// awaiting `ExecuteMsg::RetryDeposit`.
+pub fn query_failed_deposits(deps: Deps, _env: Env) -> ContractResult<Binary> {
+    let entries = FAILED_DEPOSITS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (id, deposit) = item?;
+            Ok(FailedDepositEntry { id, deposit })
+        })
+        .collect::<ContractResult<Vec<_>>>()?;
+    Ok(to_json_binary(&entries)?)
+}
+
+/// Idle bank balance, outstanding in-DEX position reserves, and oracle NAV
+/// for both tokens, folded into a single authoritative total so integrators
+/// price the vault against its true inventory rather than only its
+/// undeployed funds.
+pub fn query_total_value(deps: Deps, env: Env) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Goes through `query_contract_balance_readonly` rather than a raw bank
+    // `query_balance` so a `cw20_token_0`/`cw20_token_1`-configured leg is
+    // read from that CW20 contract instead of silently pricing it at zero.
+    let idle_balances = query_contract_balance_readonly(deps, env.clone(), &config)?;
+    let idle_0 = idle_balances[0].amount;
+    let idle_1 = idle_balances[1].amount;
+    let (in_dex_0, in_dex_1) = get_in_dex_token_amounts(deps, env.clone(), &config)?;
+
+    let prices = get_prices(deps, env)?;
+    let nav = total_vault_value(idle_0 + in_dex_0, idle_1 + in_dex_1, &prices)?;
+
+    let stableswap_invariant = if config.stableswap_amplification > 0 {
+        crate::stableswap::solve_invariant_d(
+            config.stableswap_amplification,
+            PrecDec::from_ratio(idle_0 + in_dex_0, 1u128),
+            PrecDec::from_ratio(idle_1 + in_dex_1, 1u128),
+        )
+    } else {
+        None
+    };
+
+    Ok(to_json_binary(&TotalValueResponse {
+        idle_0,
+        idle_1,
+        in_dex_0,
+        in_dex_1,
+        nav,
+        stableswap_invariant,
+    })?)
+}
+
+/// `query_total_value`'s oracle NAV divided by `Config::total_shares`, so
+/// integrators get a single per-share USD price instead of reimplementing
+/// this division themselves from `GetTotalValue`/`GetConfig`. `0`
+/// `total_shares` reports a zero rate rather than dividing by zero, the
+/// same convention `query_redemption_rate` uses.
+pub fn query_nav(deps: Deps, env: Env) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Same CW20-aware idle lookup `query_total_value` uses, so a
+    // `cw20_token_0`/`cw20_token_1`-configured leg is read from that CW20
+    // contract instead of silently pricing it at zero.
+    let idle_balances = query_contract_balance_readonly(deps, env.clone(), &config)?;
+    let idle_0 = idle_balances[0].amount;
+    let idle_1 = idle_balances[1].amount;
+    let (in_dex_0, in_dex_1) = get_in_dex_token_amounts(deps, env.clone(), &config)?;
+
+    let prices = get_prices(deps, env)?;
+    let total_value_usd = total_vault_value(idle_0 + in_dex_0, idle_1 + in_dex_1, &prices)?;
+
+    let nav_per_share = if config.total_shares.is_zero() {
+        PrecDec::zero()
+    } else {
+        total_value_usd / PrecDec::from_ratio(config.total_shares, 1u128)
+    };
+
+    Ok(to_json_binary(&NavResponse {
+        total_value_usd,
+        nav_per_share,
+        lp_supply: config.total_shares,
+    })?)
+}
+
+/// Queries `apy_contract` for `instance`'s instantaneous realized APY over
+/// `time_span_hours`, then maps `state::APY_EMA`'s smoothed running average
+/// for that `instance` (falling back to the instantaneous sample if
+/// `execute_update_apy_ema` has never observed it) onto a fee-tier ladder via
+/// `derive_apy_fee_tiers`. Read-only - closing the loop between measured LST
+/// yield and on-chain liquidity placement is left to whatever off-chain
+/// caller feeds this straight into `ExecuteMsg::UpdateConfig`.
+pub fn query_calculated_fee_tiers(
+    deps: Deps,
+    _env: Env,
+    apy_contract: String,
+    instance: String,
+    time_span_hours: u64,
+    base_fee: u64,
+    oracle_skew: i32,
+) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let apy_contract = deps.api.addr_validate(&apy_contract)?;
+    let response: ApyResponse = deps.querier.query_wasm_smart(
+        apy_contract,
+        &ApySourceQueryMsg::GetApy {
+            instance: instance.clone(),
+            time_span_hours,
+        },
+    )?;
+    let ema_apy = APY_EMA.may_load(deps.storage, instance)?.map(|cache| cache.ema_apy);
+
+    let allowed_fee_tiers = ALLOWED_FEE_TIERS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| FALLBACK_FEE_TIERS.to_vec());
+    let (fee_tiers, oracle_skew) = derive_apy_fee_tiers(
+        ema_apy.unwrap_or(response.apy),
+        base_fee,
+        oracle_skew,
+        &allowed_fee_tiers,
+    );
+
+    Ok(to_json_binary(&CalculatedFeeTiersResponse {
+        denom: config.pair_data.token_0.denom,
+        apy: response.apy,
+        ema_apy,
+        base_fee,
+        oracle_skew,
+        fee_tiers,
+    })?)
+}
+
+/// Every open `PROPOSALS` entry, oldest id first.
+pub fn query_list_proposals(deps: Deps, _env: Env) -> ContractResult<Binary> {
+    let proposals = PROPOSALS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<cosmwasm_std::StdResult<Vec<_>>>()?;
+    Ok(to_json_binary(&ProposalsResponse { proposals })?)
+}
+
+/// Current value of one vault share in token_0/token_1, valued against the
+/// vault's true inventory (idle balances plus outstanding in-DEX position
+/// reserves) rather than the oracle-priced NAV `query_total_value` returns,
+/// so integrators pricing the LP as collateral see the exact rate
+/// `handle_user_withdrawal_reply` would apportion a redemption at. `0`
+/// `total_shares` reports a zero rate rather than dividing by zero.
+pub fn query_redemption_rate(deps: Deps, env: Env) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let idle_0 = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.pair_data.token_0.denom.clone())?
+        .amount;
+    let idle_1 = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.pair_data.token_1.denom.clone())?
+        .amount;
+    let (in_dex_0, in_dex_1) = get_in_dex_token_amounts(deps, env.clone(), &config)?;
+    let balance_0 = idle_0 + in_dex_0;
+    let balance_1 = idle_1 + in_dex_1;
+
+    let (rate_0_per_share, rate_1_per_share) = if config.total_shares.is_zero() {
+        (PrecDec::zero(), PrecDec::zero())
+    } else {
+        (
+            PrecDec::from_ratio(balance_0, config.total_shares),
+            PrecDec::from_ratio(balance_1, config.total_shares),
+        )
+    };
+
+    Ok(to_json_binary(&RedemptionRateResponse {
+        total_shares: config.total_shares,
+        balance_0,
+        balance_1,
+        rate_0_per_share,
+        rate_1_per_share,
+        height: env.block.height,
+        time: env.block.time.seconds(),
+    })?)
+}
+
+/// `address`'s currently bonded shares and any shares still unbonding.
+pub fn query_bonded_shares(deps: Deps, _env: Env, address: String) -> ContractResult<Binary> {
+    let addr = deps.api.addr_validate(&address)?;
+    let bonded = BONDED_SHARES.may_load(deps.storage, addr.clone())?.unwrap_or_default();
+    let unbonding = UNBONDING_SHARES.may_load(deps.storage, addr)?.unwrap_or_default();
+    Ok(to_json_binary(&BondedSharesResponse { bonded, unbonding })?)
+}
+
+/// `address`'s in-flight `ExecuteMsg::QueueWithdrawal` entries, oldest first.
+pub fn query_withdrawal_queue(deps: Deps, _env: Env, address: String) -> ContractResult<Binary> {
+    let addr = deps.api.addr_validate(&address)?;
+    let entries = WITHDRAWAL_QUEUE
+        .prefix(addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<cosmwasm_std::StdResult<Vec<_>>>()?;
+    Ok(to_json_binary(&WithdrawalQueueResponse { entries })?)
+}
+
+/// Mirrors `execute::deposit`'s share-minting math against the vault's
+/// current inventory, without executing it, so callers can quote/slippage-check
+/// a deposit of `token0_amount`/`token1_amount` beforehand.
+pub fn query_preview_deposit(
+    deps: Deps,
+    env: Env,
+    token0_amount: Uint128,
+    token1_amount: Uint128,
+) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let prices = get_prices(deps, env.clone())?;
+
+    let idle_0 = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.pair_data.token_0.denom.clone())?
+        .amount;
+    let idle_1 = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.pair_data.token_1.denom.clone())?
+        .amount;
+    let (in_dex_0, in_dex_1) = get_in_dex_token_amounts(deps, env, &config)?;
+
+    let total_value_before = total_vault_value(idle_0 + in_dex_0, idle_1 + in_dex_1, &prices)?;
+    let deposit_value = total_vault_value(token0_amount, token1_amount, &prices)?;
+    let shares_minted = shares_to_mint(deposit_value, config.total_shares, total_value_before)?;
+
+    Ok(to_json_binary(&PreviewDepositResponse { shares_minted })?)
+}
+
+/// Mirrors `execute::swap`'s quote math against the vault's current
+/// reserves, without executing it, so callers can quote/slippage-check a
+/// swap of `amount_in` of `token_in` beforehand.
+pub fn query_simulate_swap(
+    deps: Deps,
+    env: Env,
+    token_in: String,
+    amount_in: Uint128,
+) -> ContractResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let prices = get_prices(deps, env)?;
+
+    let token_0_denom = config.pair_data.token_0.denom.clone();
+    let token_1_denom = config.pair_data.token_1.denom.clone();
+    let (reserve_in, reserve_out, price_in, price_out) = if token_in == token_0_denom {
+        (
+            config.balances.token_0.amount,
+            config.balances.token_1.amount,
+            prices.token_0_price,
+            prices.token_1_price,
+        )
+    } else if token_in == token_1_denom {
+        (
+            config.balances.token_1.amount,
+            config.balances.token_0.amount,
+            prices.token_1_price,
+            prices.token_0_price,
+        )
+    } else {
+        return Err(ContractError::InvalidToken);
+    };
+
+    let amount_out = compute_swap_out(
+        reserve_in,
+        reserve_out,
+        amount_in,
+        price_in,
+        price_out,
+        config.swap_fee_bps,
+    )?;
+
+    Ok(to_json_binary(&SimulateSwapResponse { amount_out })?)
+}
+
+/// Mirrors `execute::dex_deposit`'s `prepare_state` rebalance step against the
+/// vault's current idle balances and oracle price, without placing
+/// anything, so callers can preview exactly what a deposit's pre-deposit
+/// rebalance would do and why a given leg did or didn't clear. Uses
+/// `simulate_prepare_state`, `prepare_state`'s `Deps`-only twin, and the same
+/// plain `get_prices` (no EMA guard, no target-rate nudge, no state writes)
+/// `query_preview_deposit`/`query_simulate_swap` already use for a read-only
+/// price.
+pub fn query_simulate_deposit(deps: Deps, env: Env) -> ContractResult<Binary> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let prices = get_prices(deps, env.clone())?;
+    let tick_index = price_to_tick_index(prices.price_0_to_1)?;
+
+    let idle_0 = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.pair_data.token_0.denom.clone())?
+        .amount;
+    let idle_1 = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.pair_data.token_1.denom.clone())?
+        .amount;
+
+    config.balances.token_0.amount = idle_0;
+    config.balances.token_1.amount = idle_1;
+
+    let (messages, token_0_usable, token_1_usable) =
+        simulate_prepare_state(deps, &env, &config, tick_index)?;
+
+    Ok(to_json_binary(&SimulateDepositResponse {
+        messages,
+        token_0_usable,
+        token_1_usable,
+    })?)
+}
+
+/// Dry-run preview of what the next `ExecuteMsg::DexDeposit` would do, so
+/// keepers/monitoring can validate a rebalance off-chain before broadcasting
+/// it. Classifies the action the same way `dex_deposit` itself would -
+/// `"withdrawal_only"` while `ContractStatus` blocks deposits,
+/// `"skipped_no_drift"` when the freshly computed `tick_index`/`fee_tiers`/
+/// `base_fee` match `LAST_DEPLOYED_STATE` within
+/// `Config::rebalance_drift_tolerance_ticks`, or `"would_rebalance"` with the
+/// actual `CosmosMsg` list otherwise - built from `simulate_prepare_state`
+/// and `simulate_get_deposit_messages`/`get_limit_order_messages`, the same
+/// `Deps`-only twins `query_simulate_deposit` uses. Like that query, this
+/// reads the plain `get_prices` (no EMA guard, no target-rate nudge, no
+/// `volatility_spread` widening, no state writes) rather than `dex_deposit`'s
+/// full `DepsMut`-gated guard pipeline, so a result near the drift threshold
+/// should still be re-checked against the real call before relying on it.
+pub fn query_simulate_vault_update(deps: Deps, env: Env) -> ContractResult<Binary> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let prices = get_prices(deps, env.clone())?;
+    let tick_index = price_to_tick_index(prices.price_0_to_1)?;
+
+    let idle_0 = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.pair_data.token_0.denom.clone())?
+        .amount;
+    let idle_1 = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.pair_data.token_1.denom.clone())?
+        .amount;
+    config.balances.token_0.amount = idle_0;
+    config.balances.token_1.amount = idle_1;
+
+    if !matches!(config.status, ContractStatus::Operational) {
+        return Ok(to_json_binary(&SimulateVaultUpdateResponse {
+            action: "withdrawal_only".to_string(),
+            base_fee: config.base_fee,
+            oracle_skew: config.oracle_price_skew,
+            fee_tiers: config.fee_tiers,
+            messages: vec![],
+        })?);
+    }
+
+    if let Some(last) = LAST_DEPLOYED_STATE.may_load(deps.storage)? {
+        let tick_drift = (tick_index - last.tick_index).unsigned_abs();
+        let tiers_unchanged =
+            last.fee_tiers == config.fee_tiers && last.base_fee == config.base_fee;
+        if tiers_unchanged && tick_drift <= config.rebalance_drift_tolerance_ticks {
+            return Ok(to_json_binary(&SimulateVaultUpdateResponse {
+                action: "skipped_no_drift".to_string(),
+                base_fee: config.base_fee,
+                oracle_skew: config.oracle_price_skew,
+                fee_tiers: config.fee_tiers,
+                messages: vec![],
+            })?);
+        }
+    }
+
+    let (lo_messages, token_0_usable, token_1_usable) =
+        simulate_prepare_state(deps, &env, &config, tick_index)?;
+    let mut messages = lo_messages;
+
+    if let Some(market_making) = config.market_making.clone() {
+        messages.extend(get_limit_order_messages(
+            &env,
+            &config,
+            tick_index,
+            &prices,
+            token_0_usable,
+            token_1_usable,
+            &market_making,
+        )?);
+    } else {
+        messages.extend(simulate_get_deposit_messages(
+            deps,
+            &env,
+            config.clone(),
+            tick_index,
+            prices,
+            token_0_usable,
+            token_1_usable,
+        )?);
+    }
+
+    Ok(to_json_binary(&SimulateVaultUpdateResponse {
+        action: "would_rebalance".to_string(),
+        base_fee: config.base_fee,
+        oracle_skew: config.oracle_price_skew,
+        fee_tiers: config.fee_tiers,
+        messages,
+    })?)
+}
+
+/// Verifies `permit` and dispatches `query` as the permit's signing address.
+pub fn query_with_permit(
+    deps: Deps,
+    env: Env,
+    permit: QueryPermit,
+    query: PermitQueryMsg,
+) -> ContractResult<Binary> {
+    match query {
+        PermitQueryMsg::GetMyDeposits {} => {
+            let addr = permit.verify(deps, &env.block.chain_id, Permission::Deposits)?;
+            query_deposits(deps, addr)
+        }
+    }
+}
+
+/// `addr`'s cumulative oracle-valued `Deposit` contribution and minted
+/// shares, checked against `Config::per_address_cap`.
+fn query_deposits(deps: Deps, addr: Addr) -> ContractResult<Binary> {
+    let (deposited_value, shares_minted) = match DEPOSITS.may_load(deps.storage, addr)? {
+        Some(record) => (record.deposited_value, record.shares_minted),
+        None => (PrecDec::zero(), Uint128::zero()),
+    };
+    Ok(to_json_binary(&DepositsResponse {
+        deposited_value,
+        shares_minted,
+    })?)
+}
+
+/// cw721 `OwnerOf`: the current owner of position NFT `token_id`.
+pub fn query_owner_of(deps: Deps, _env: Env, token_id: u64) -> ContractResult<Binary> {
+    let position = POSITIONS
+        .may_load(deps.storage, token_id)?
+        .ok_or(crate::error::ContractError::PositionNotFound { token_id })?;
+    Ok(to_json_binary(&OwnerOfResponse {
+        owner: position.owner.to_string(),
+    })?)
+}
+
+/// cw721 `NftInfo`: position NFT `token_id`'s extension metadata (its share claim).
+pub fn query_nft_info(deps: Deps, _env: Env, token_id: u64) -> ContractResult<Binary> {
+    let position = POSITIONS
+        .may_load(deps.storage, token_id)?
+        .ok_or(crate::error::ContractError::PositionNotFound { token_id })?;
+    Ok(to_json_binary(&NftInfoResponse { extension: position })?)
+}
+
+/// cw721 `Tokens`: `owner`'s position NFT ids, oldest first.
+pub fn query_tokens(
+    deps: Deps,
+    _env: Env,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> ContractResult<Binary> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(30) as usize;
+    let tokens = POSITIONS_BY_OWNER
+        .prefix(owner)
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<cosmwasm_std::StdResult<Vec<_>>>()?;
+    Ok(to_json_binary(&TokensResponse { tokens })?)
+}
+
+/// The vault-wide pair registry (`ExecuteMsg::RegisterPair`/`DeregisterPair`),
+/// paginated by `(denom_0, denom_1)` in ascending key order.
+pub fn query_list_pairs(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+) -> ContractResult<Binary> {
+    let limit = limit.unwrap_or(30) as usize;
+    let pairs = crate::state::REGISTERED_PAIRS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|entry| entry.map(|(_, pair_data)| pair_data))
+        .collect::<cosmwasm_std::StdResult<Vec<_>>>()?;
+    Ok(to_json_binary(&ListPairsResponse { pairs })?)
+}
+
+/// The recorded `Snapshot` at or before `height`, converted to a per-share
+/// redemption rate for each token.
+pub fn query_share_price_at_height(deps: Deps, _env: Env, height: u64) -> ContractResult<Binary> {
+    let (found_height, snapshot) = SNAPSHOTS
+        .range(deps.storage, None, Some(Bound::inclusive(height)), Order::Descending)
+        .next()
+        .transpose()?
+        .ok_or(crate::error::ContractError::NoSnapshotAvailable { height })?;
+    let (price_0_per_share, price_1_per_share) = snapshot_price(&snapshot);
+    Ok(to_json_binary(&SharePriceResponse {
+        height: found_height,
+        total_shares: snapshot.total_shares,
+        price_0_per_share,
+        price_1_per_share,
+    })?)
+}
+
+/// Time-weighted average share price over `[start_height, end_height]`:
+/// walks the recorded snapshots in range and holds each one's price constant
+/// over the block-span up to the next snapshot (or `end_height`, for the
+/// last one). A snapshot recorded with zero `total_shares` doesn't update the
+/// held price, so its span is valued at the prior valid snapshot's price
+/// instead of dividing by zero.
+pub fn query_twap_share_price(
+    deps: Deps,
+    _env: Env,
+    start_height: u64,
+    end_height: u64,
+) -> ContractResult<Binary> {
+    let zero_response = TwapSharePriceResponse {
+        twap_price_0_per_share: PrecDec::zero(),
+        twap_price_1_per_share: PrecDec::zero(),
+    };
+    if end_height <= start_height {
+        return Ok(to_json_binary(&zero_response)?);
+    }
+
+    let snapshots = SNAPSHOTS
+        .range(
+            deps.storage,
+            Some(Bound::inclusive(start_height)),
+            Some(Bound::inclusive(end_height)),
+            Order::Ascending,
+        )
+        .collect::<cosmwasm_std::StdResult<Vec<(u64, crate::state::Snapshot)>>>()?;
+    let Some((_, first)) = snapshots.first() else {
+        return Ok(to_json_binary(&zero_response)?);
+    };
+
+    let mut held_price = snapshot_price(first);
+    let mut cursor = start_height;
+    let mut weighted_0 = PrecDec::zero();
+    let mut weighted_1 = PrecDec::zero();
+    let mut total_span = 0u64;
+
+    for (height, snapshot) in &snapshots {
+        let span = height.saturating_sub(cursor);
+        if span > 0 {
+            weighted_0 += held_price.0 * PrecDec::from_ratio(span, 1u128);
+            weighted_1 += held_price.1 * PrecDec::from_ratio(span, 1u128);
+            total_span += span;
+        }
+        if !snapshot.total_shares.is_zero() {
+            held_price = snapshot_price(snapshot);
+        }
+        cursor = *height;
+    }
+    let tail_span = end_height.saturating_sub(cursor);
+    if tail_span > 0 {
+        weighted_0 += held_price.0 * PrecDec::from_ratio(tail_span, 1u128);
+        weighted_1 += held_price.1 * PrecDec::from_ratio(tail_span, 1u128);
+        total_span += tail_span;
+    }
+
+    let (twap_price_0_per_share, twap_price_1_per_share) = if total_span == 0 {
+        held_price
+    } else {
+        (
+            weighted_0 / PrecDec::from_ratio(total_span, 1u128),
+            weighted_1 / PrecDec::from_ratio(total_span, 1u128),
+        )
+    };
+
+    Ok(to_json_binary(&TwapSharePriceResponse {
+        twap_price_0_per_share,
+        twap_price_1_per_share,
+    })?)
+}
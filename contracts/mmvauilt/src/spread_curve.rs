@@ -0,0 +1,305 @@
+use std::str::FromStr;
+
+use cosmwasm_std::Uint128;
+use neutron_std::types::neutron::util::precdec::PrecDec;
+
+/// `ln(2)`, to `PrecDec`'s fixed-point precision. The pivot both
+/// `checked_exp`'s range reduction (`x = k*ln2 + r`) and `bend`'s curve
+/// scaling are built around.
+fn ln2() -> PrecDec {
+    PrecDec::from_str("0.693147180559945309").unwrap()
+}
+
+/// Largest `k` `checked_exp`'s range reduction may scale `2^k` by. `2^100`
+/// is a little over `10^30`, comfortably inside `PrecDec`'s representable
+/// range and `u128`'s shift width. `bend`'s exponential regime only passes
+/// magnitudes up to `ln2` (`k = 0`); its logistic regime passes up to
+/// `steepness / 2`, which exceeds this bound only at extreme steepness, where
+/// `checked_exp` returns `None` and `bend` falls back to linear.
+const MAX_EXP_SHIFT: u128 = 100;
+
+/// Number of terms `e^r` (`r` in `[0, ln2)`) is Taylor-expanded to. Seven
+/// terms leave a relative truncation error of at most about `1.3e-6`
+/// (`ln2^8 / 8!`) over that range, far coarser than `PrecDec`'s own precision.
+const EXP_TAYLOR_TERMS: u128 = 7;
+
+/// Number of terms `ln(1+u)` (`u` in `[0, 1)`, after mantissa range
+/// reduction) is Taylor-expanded to. More terms than `EXP_TAYLOR_TERMS`
+/// since the series converges more slowly near `u`'s upper edge.
+const LN_TAYLOR_TERMS: u128 = 40;
+
+/// Iteration cap on `checked_ln`'s mantissa range-reduction loop, a
+/// corruption/overflow backstop rather than a value ever expected to bind:
+/// reducing even `PrecDec`'s largest representable value into `[1, 2)` takes
+/// a few hundred halvings at most.
+const MAX_RANGE_REDUCTION_STEPS: u32 = 4096;
+
+fn negate(x: PrecDec) -> Option<PrecDec> {
+    PrecDec::zero().checked_sub(x).ok()
+}
+
+/// `e^x` via range reduction `x = k*ln2 + r` (`r` in `[0, ln2)`), evaluating
+/// `e^r` with a fixed `EXP_TAYLOR_TERMS`-term Taylor expansion on `PrecDec`
+/// fixed-point, then scaling the result by `2^k`. Returns `None` instead of
+/// overflowing/panicking when `k` would push `2^k` past `MAX_EXP_SHIFT`;
+/// [`bend`] falls back to its linear response on `None` rather than
+/// propagating a panic.
+pub fn checked_exp(x: PrecDec) -> Option<PrecDec> {
+    if x.is_zero() {
+        return Some(PrecDec::one());
+    }
+
+    let negative = x < PrecDec::zero();
+    let magnitude = if negative { negate(x)? } else { x };
+
+    let ln2 = ln2();
+    let k = Uint128::try_from(magnitude.checked_div(ln2).ok()?.to_uint_floor()).ok()?;
+    let k: u128 = k.u128();
+    if k > MAX_EXP_SHIFT {
+        return None;
+    }
+    let r = magnitude
+        .checked_sub(ln2.checked_mul(PrecDec::from_ratio(k, 1u128)).ok()?)
+        .ok()?;
+
+    // e^r = sum_{n=0}^{N} r^n / n!
+    let mut term = PrecDec::one();
+    let mut sum = PrecDec::one();
+    for n in 1..=EXP_TAYLOR_TERMS {
+        term = term.checked_mul(r).ok()?.checked_div(PrecDec::from_ratio(n, 1u128)).ok()?;
+        sum = sum.checked_add(term).ok()?;
+    }
+
+    let scale = PrecDec::from_ratio(1u128 << k, 1u128);
+    let result = sum.checked_mul(scale).ok()?;
+
+    if negative {
+        PrecDec::one().checked_div(result).ok()
+    } else {
+        Some(result)
+    }
+}
+
+/// `ln(x)` for `x > 0`, via range-reducing the mantissa `x = m * 2^e` into
+/// `m` in `[1, 2)`, evaluating `ln(1+u)` (`u = m - 1`, `u` in `[0, 1)`) with
+/// a fixed `LN_TAYLOR_TERMS`-term Taylor expansion, then adding back
+/// `e*ln2`. Returns `None` for non-positive `x`, since `ln` is undefined
+/// there, rather than panicking; [`bend`] falls back to its linear response
+/// on `None`.
+pub fn checked_ln(x: PrecDec) -> Option<PrecDec> {
+    if x.is_zero() || x < PrecDec::zero() {
+        return None;
+    }
+    if x == PrecDec::one() {
+        return Some(PrecDec::zero());
+    }
+
+    let one = PrecDec::one();
+    let two = PrecDec::from_ratio(2u128, 1u128);
+
+    let mut m = x;
+    let mut e: i64 = 0;
+    for _ in 0..MAX_RANGE_REDUCTION_STEPS {
+        if m >= two {
+            m = m.checked_div(two).ok()?;
+            e += 1;
+        } else if m < one {
+            m = m.checked_mul(two).ok()?;
+            e -= 1;
+        } else {
+            break;
+        }
+    }
+    if m >= two || m < one {
+        // range reduction didn't converge within the iteration cap
+        return None;
+    }
+
+    let u = m.checked_sub(one).ok()?;
+    let mut power = u;
+    let mut sum = PrecDec::zero();
+    for n in 1..=LN_TAYLOR_TERMS {
+        let signed_term = power.checked_div(PrecDec::from_ratio(n, 1u128)).ok()?;
+        sum = if n % 2 == 1 {
+            sum.checked_add(signed_term).ok()?
+        } else {
+            sum.checked_sub(signed_term).ok()?
+        };
+        power = power.checked_mul(u).ok()?;
+    }
+
+    let ln2 = ln2();
+    let e_ln2 = if e >= 0 {
+        ln2.checked_mul(PrecDec::from_ratio(e as u128, 1u128)).ok()?
+    } else {
+        negate(ln2.checked_mul(PrecDec::from_ratio((-e) as u128, 1u128)).ok()?)?
+    };
+
+    sum.checked_add(e_ln2).ok()
+}
+
+/// `factor <= LOGISTIC_FACTOR_THRESHOLD` selects [`bend`]'s logistic regime,
+/// with steepness `k = (-dynamic_spread_factor) / LOGISTIC_STEEPNESS_SCALE`.
+/// `-1` is reserved for the plain exponential curve (unchanged from before
+/// the logistic regime existed), so the logistic range starts one further out.
+const LOGISTIC_FACTOR_THRESHOLD: i32 = -2;
+
+/// Divisor turning a `dynamic_spread_factor` magnitude into the logistic
+/// curve's steepness `k`, e.g. `factor = -500` is `k = 5.0`.
+const LOGISTIC_STEEPNESS_SCALE: u128 = 100;
+
+/// A `dynamic_spread_factor` pair, letting the side of an imbalance that
+/// widens fees (`widen`) take a different curve than the side that narrows
+/// them (`narrow`) — e.g. penalizing draining the scarce asset more steeply
+/// than accumulating the abundant one. [`SpreadFactors::symmetric`] is the
+/// back-compatible constructor for the single-factor case: both sides bend
+/// through the same curve, reproducing the old behavior byte-for-byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpreadFactors {
+    pub widen: i32,
+    pub narrow: i32,
+}
+
+impl SpreadFactors {
+    pub fn symmetric(factor: i32) -> Self {
+        Self { widen: factor, narrow: factor }
+    }
+}
+
+/// Explicit curve selector for [`bend_with_mode`], for callers that would
+/// rather name a shape directly than thread a `dynamic_spread_factor`
+/// through [`bend`]'s sign/magnitude convention (`0`/positive/`-1`/
+/// [`LOGISTIC_FACTOR_THRESHOLD`]). Produces byte-identical output to the
+/// equivalent factor, since `bend_with_mode` is a thin dispatch onto
+/// [`bend`] itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpreadCurveMode {
+    Linear,
+    Logarithmic,
+    Exponential,
+    /// Sigmoid response with steepness `k = steepness_x100 / 100`, e.g.
+    /// `steepness_x100 = 500` is `k = 5.0`.
+    Logistic { steepness_x100: u32 },
+}
+
+/// Dispatches to [`bend`] via an explicit [`SpreadCurveMode`] rather than a
+/// numeric `dynamic_spread_factor`.
+pub fn bend_with_mode(imbalance: PrecDec, mode: SpreadCurveMode) -> PrecDec {
+    match mode {
+        SpreadCurveMode::Linear => bend(imbalance, 0),
+        SpreadCurveMode::Logarithmic => bend(imbalance, 1),
+        SpreadCurveMode::Exponential => bend(imbalance, -1),
+        SpreadCurveMode::Logistic { steepness_x100 } => {
+            // clamped to at least 2 so the factor always lands at or below
+            // `LOGISTIC_FACTOR_THRESHOLD`; `-1` is reserved for `Exponential`.
+            let factor = -(steepness_x100.max(2) as i32);
+            bend(imbalance, factor)
+        }
+    }
+}
+
+/// A `(dynamic_spread_factor, dynamic_spread_cap)` pair selected by the
+/// sign of a *signed* imbalance ratio, letting a vault defend one side of
+/// the book with a different curve and magnitude cap than the other — e.g.
+/// widening aggressively while the scarce asset is being drained but
+/// staying tight on inflows. [`SpreadBounds::symmetric`] is the
+/// back-compatible constructor for the old single `(factor, cap)` case:
+/// both signs share the same curve and cap, reproducing the prior
+/// behavior byte-for-byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpreadBounds {
+    pub positive: (i32, u64),
+    pub negative: (i32, u64),
+}
+
+impl SpreadBounds {
+    pub fn symmetric(factor: i32, cap: u64) -> Self {
+        Self { positive: (factor, cap), negative: (factor, cap) }
+    }
+
+    /// `positive` for `signed_imbalance >= 0`, `negative` otherwise.
+    pub fn for_signed_imbalance(self, signed_imbalance: PrecDec) -> (i32, u64) {
+        if signed_imbalance >= PrecDec::zero() {
+            self.positive
+        } else {
+            self.negative
+        }
+    }
+}
+
+/// Bends `imbalance` (`[0, 1]`) through a curve selected by
+/// `dynamic_spread_factor`: `0` is linear (identity); positive factors take
+/// the logarithmic shape through [`checked_ln`], which (being concave)
+/// responds more aggressively than linear for every imbalance strictly
+/// between the two endpoints; `-1` takes the exponential shape through
+/// [`checked_exp`], which (being convex) responds more gently; factors at or
+/// below [`LOGISTIC_FACTOR_THRESHOLD`] take a logistic (S-curve) shape instead
+/// on `|s|` (see below) — flat near the `0.5` midpoint and near the
+/// endpoints, steepest around `imbalance` `0.25`/`0.75`, with steepness
+/// `k = (-dynamic_spread_factor) / LOGISTIC_STEEPNESS_SCALE`. Centered on
+/// `s = 2*imbalance - 1` (`[-1, 1]`), so every curve agrees with linear
+/// exactly at the `0.5` midpoint (`s = 0`) and at the `0`/`1` endpoints
+/// (`s = -1`/`s = 1`), only diverging in between. Falls back to the linear
+/// response wherever the underlying `checked_exp`/`checked_ln` returns `None`
+/// (e.g. pathological input that would overflow `PrecDec`'s representable
+/// range), so a curve never panics or saturates to a nonsensical result —
+/// it simply degrades to linear instead.
+pub fn bend(imbalance: PrecDec, dynamic_spread_factor: i32) -> PrecDec {
+    if dynamic_spread_factor == 0 {
+        return imbalance;
+    }
+    bend_checked(imbalance, dynamic_spread_factor).unwrap_or(imbalance)
+}
+
+fn bend_checked(imbalance: PrecDec, dynamic_spread_factor: i32) -> Option<PrecDec> {
+    let one = PrecDec::one();
+    let two = PrecDec::from_ratio(2u128, 1u128);
+
+    let s = imbalance.checked_mul(two).ok()?.checked_sub(one).ok()?;
+    let negative = s < PrecDec::zero();
+    let magnitude = if negative { negate(s)? } else { s };
+
+    let curved_magnitude = if dynamic_spread_factor > 0 {
+        checked_ln(magnitude.checked_add(one).ok()?)?
+            .checked_div(ln2())
+            .ok()?
+    } else if dynamic_spread_factor <= LOGISTIC_FACTOR_THRESHOLD {
+        let k = PrecDec::from_ratio((-dynamic_spread_factor) as u128, LOGISTIC_STEEPNESS_SCALE);
+        logistic_normalized(magnitude, k)?
+    } else {
+        checked_exp(magnitude.checked_mul(ln2()).ok()?)?
+            .checked_sub(one)
+            .ok()?
+    };
+
+    let curved_s = if negative {
+        negate(curved_magnitude)?
+    } else {
+        curved_magnitude
+    };
+    curved_s.checked_add(one).ok()?.checked_div(two).ok()
+}
+
+/// Standard logistic curve `L(x) = 1 / (1 + e^(-k*(x-0.5)))`, centered at
+/// `x = 0.5` so it's symmetric over `[0, 1]`.
+fn logistic(x: PrecDec, k: PrecDec) -> Option<PrecDec> {
+    let half = PrecDec::from_ratio(1u128, 2u128);
+    let exponent = negate(k.checked_mul(x.checked_sub(half).ok()?).ok()?)?;
+    let denominator = PrecDec::one().checked_add(checked_exp(exponent)?).ok()?;
+    PrecDec::one().checked_div(denominator).ok()
+}
+
+/// `(L(x) - L(0)) / (L(1) - L(0))`: rescales [`logistic`] so it hits exactly
+/// `0` at `x = 0` and `1` at `x = 1` regardless of steepness `k`, the same
+/// endpoint guarantee the logarithmic/exponential curves get from their own
+/// closed forms.
+fn logistic_normalized(x: PrecDec, k: PrecDec) -> Option<PrecDec> {
+    let l0 = logistic(PrecDec::zero(), k)?;
+    let l1 = logistic(PrecDec::one(), k)?;
+    let lx = logistic(x, k)?;
+    let span = l1.checked_sub(l0).ok()?;
+    if span.is_zero() {
+        return None;
+    }
+    lx.checked_sub(l0).ok()?.checked_div(span).ok()
+}
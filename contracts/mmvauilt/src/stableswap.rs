@@ -0,0 +1,157 @@
+use neutron_std::types::neutron::util::precdec::PrecDec;
+
+/// Iteration cap on [`solve_invariant_d`]/[`solve_for_reserve`]'s Newton
+/// loops, a backstop against a pathological amplification/reserve
+/// combination looping forever rather than a bound expected to bind - both
+/// converge in well under a dozen steps for any realistic `amplification`
+/// and reserve ratio.
+const MAX_NEWTON_ITERATIONS: u32 = 255;
+
+/// `Ann = A * n^n` with `n = 2` fixed (this vault only ever stableswaps a
+/// single token_0/token_1 pair), the quantity Curve's whitepaper invariant
+/// and `get_y` are both expressed in terms of.
+fn ann(amplification: u64) -> PrecDec {
+    PrecDec::from_ratio(amplification.saturating_mul(4), 1u128)
+}
+
+fn diff(a: PrecDec, b: PrecDec) -> PrecDec {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// Solves the 2-asset StableSwap invariant for `D` given `reserve_0`/
+/// `reserve_1` (already scaled to the same base-unit precision - see
+/// `scale_to_redemption_rate`), via Newton iteration on Curve's whitepaper
+/// formula specialized to `n = 2`:
+///
+/// `D_{k+1} = (Ann*S + 2*Dp) * D_k / ((Ann - 1)*D_k + 3*Dp)`, where
+/// `Dp = D_k^3 / (4 * reserve_0 * reserve_1)` and `S = reserve_0 + reserve_1`,
+/// seeded at `D_0 = S`. Stops once successive `D` values differ by at most
+/// `1e-18`. Returns `None` if either reserve is zero
+/// (the invariant degenerates - there's no curve to solve), `amplification`
+/// is zero (meaningless - `Ann` would be zero), or the iteration fails to
+/// converge within [`MAX_NEWTON_ITERATIONS`].
+pub fn solve_invariant_d(amplification: u64, reserve_0: PrecDec, reserve_1: PrecDec) -> Option<PrecDec> {
+    if amplification == 0 || reserve_0.is_zero() || reserve_1.is_zero() {
+        return None;
+    }
+    let ann = ann(amplification);
+    let sum = reserve_0.checked_add(reserve_1).ok()?;
+    let four = PrecDec::from_ratio(4u128, 1u128);
+    let two = PrecDec::from_ratio(2u128, 1u128);
+    let three = PrecDec::from_ratio(3u128, 1u128);
+    let four_xy = four.checked_mul(reserve_0).ok()?.checked_mul(reserve_1).ok()?;
+
+    let mut d = sum;
+    for _ in 0..MAX_NEWTON_ITERATIONS {
+        let d_p = d
+            .checked_mul(d)
+            .ok()?
+            .checked_mul(d)
+            .ok()?
+            .checked_div(four_xy)
+            .ok()?;
+        let numerator = ann
+            .checked_mul(sum)
+            .ok()?
+            .checked_add(two.checked_mul(d_p).ok()?)
+            .ok()?
+            .checked_mul(d)
+            .ok()?;
+        let denominator = ann
+            .checked_sub(PrecDec::one())
+            .ok()?
+            .checked_mul(d)
+            .ok()?
+            .checked_add(three.checked_mul(d_p).ok()?)
+            .ok()?;
+        if denominator.is_zero() {
+            return None;
+        }
+        let d_next = numerator.checked_div(denominator).ok()?;
+        let converged = diff(d_next, d) <= PrecDec::one().checked_div(PrecDec::from_ratio(10u128.pow(18), 1u128)).ok()?;
+        d = d_next;
+        if converged {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// Solves the invariant for the reserve of the *other* asset given `d` (from
+/// [`solve_invariant_d`]), `amplification`, and one known reserve -
+/// Curve's `get_y`, specialized to `n = 2`: `y^2 + y*(b - D) = c`, where
+/// `c = D^3 / (4 * Ann * known_reserve)` and `b = known_reserve + D/Ann`,
+/// iterated as `y_{k+1} = (y_k^2 + c) / (2*y_k + b - D)` from `y_0 = D`.
+/// Used by [`marginal_price_0_to_1`] to read the curve's slope by finite
+/// difference instead of hand-deriving its partial derivatives. Returns
+/// `None` on the same degenerate inputs as [`solve_invariant_d`], or if the
+/// iteration fails to converge.
+pub fn solve_for_reserve(amplification: u64, d: PrecDec, known_reserve: PrecDec) -> Option<PrecDec> {
+    if amplification == 0 || known_reserve.is_zero() || d.is_zero() {
+        return None;
+    }
+    let ann = ann(amplification);
+    let four = PrecDec::from_ratio(4u128, 1u128);
+    let two = PrecDec::from_ratio(2u128, 1u128);
+
+    let c = d
+        .checked_mul(d)
+        .ok()?
+        .checked_mul(d)
+        .ok()?
+        .checked_div(four.checked_mul(ann).ok()?.checked_mul(known_reserve).ok()?)
+        .ok()?;
+    let b = known_reserve.checked_add(d.checked_div(ann).ok()?).ok()?;
+
+    let mut y = d;
+    for _ in 0..MAX_NEWTON_ITERATIONS {
+        let numerator = y.checked_mul(y).ok()?.checked_add(c).ok()?;
+        // `2*y + b - D`, computed as `2*y + b` then subtracting `D` so the
+        // intermediate never needs to go negative before the final subtract.
+        let denominator = two
+            .checked_mul(y)
+            .ok()?
+            .checked_add(b)
+            .ok()?
+            .checked_sub(d)
+            .ok()?;
+        if denominator.is_zero() {
+            return None;
+        }
+        let y_next = numerator.checked_div(denominator).ok()?;
+        let converged = diff(y_next, y) <= PrecDec::one().checked_div(PrecDec::from_ratio(10u128.pow(18), 1u128)).ok()?;
+        y = y_next;
+        if converged {
+            return Some(y);
+        }
+    }
+    None
+}
+
+/// Marginal price of token_0 in terms of token_1 (the same convention as
+/// `CombinedPriceResponse::price_0_to_1`) on the 2-asset StableSwap curve
+/// described by `amplification`/`reserve_0`/`reserve_1`, read off the curve
+/// by finite difference rather than a hand-derived closed form: solves `D`
+/// once, nudges `reserve_0` up by one part in a million, re-solves for the
+/// paired `reserve_1` at the same `D`, and reports `-Δreserve_1/Δreserve_0`.
+/// Near the peg (reserves close to equal) this sits close to `1`, the same
+/// way a well-pegged Curve pool prices a stable pair; it widens smoothly as
+/// the pool drifts away from balance, per `amplification`. Returns `None` on
+/// any of [`solve_invariant_d`]/[`solve_for_reserve`]'s degenerate inputs.
+pub fn marginal_price_0_to_1(amplification: u64, reserve_0: PrecDec, reserve_1: PrecDec) -> Option<PrecDec> {
+    let d = solve_invariant_d(amplification, reserve_0, reserve_1)?;
+    let bump = reserve_0
+        .checked_div(PrecDec::from_ratio(1_000_000u128, 1u128))
+        .ok()?;
+    if bump.is_zero() {
+        return None;
+    }
+    let bumped_reserve_0 = reserve_0.checked_add(bump).ok()?;
+    let bumped_reserve_1 = solve_for_reserve(amplification, d, bumped_reserve_0)?;
+    let delta_reserve_1 = diff(reserve_1, bumped_reserve_1);
+    delta_reserve_1.checked_div(bump).ok()
+}
@@ -1,11 +1,28 @@
-use cosmwasm_std::{Addr, Coin, Uint128};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_storage_plus::{Item, Map};
+use neutron_std::types::neutron::util::precdec::PrecDec;
 use neutron_std::types::slinky::types::v1::CurrencyPair;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub const DEX_WITHDRAW_REPLY_ID: u64 = 1;
+pub const DEX_DEPOSIT_REPLY_ID: u64 = 2;
+pub const DEX_USER_WITHDRAW_REPLY_ID: u64 = 3;
+pub const REWARD_CLAIM_REPLY_ID: u64 = 4;
+/// hardcoded authorization for the chain's cron module, checked alongside
+/// `config.owner`/`config.admin` by `dex_deposit`/`dex_withdrawal`/
+/// `retry_deposit`/`execute_collect_rewards` - the self-rebalancing
+/// operations a cron tick drives on this vault. This is a single flat
+/// allowlist of one address; this contract manages exactly one vault's own
+/// DEX position, so there is no `config.vault_addresses` list, no
+/// `execute_run_rebalancing`, and no per-caller scoping/expiration to grant
+/// over a subset of vaults - there is only ever one vault to authorize
+/// against, and `config.owner`/`config.admin` already cover the delegation
+/// this contract needs.
 pub const CRON_MODULE_ADDRESS: &str = "neutron1cd6wafvehv79pm2yxth40thpyc7dc0yrqkyk95";
+/// shares permanently locked on the first deposit so the vault can never be
+/// fully drained of shares (protects against the empty-vault donation attack).
+pub const MINIMUM_LIQUIDITY: Uint128 = Uint128::new(1000);
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -13,6 +30,54 @@ pub struct TokenData {
     pub denom: String,
     pub decimals: u8,
     pub pair: CurrencyPair,
+    /// Chain of Slinky pairs to walk when this token has no direct USD
+    /// quote, e.g. `[ATOM/USDC, USDC/USD]` for an asset only priced against
+    /// an intermediate. Empty means `pair` already quotes directly (or is
+    /// itself a USD denom); resolved by `resolve_path_price`.
+    pub price_path: Vec<CurrencyPair>,
+    /// Maximum age, in seconds, allowed between `env.block.time` and the
+    /// oracle's reported `block_timestamp`, checked by `validate_price_fresh`
+    /// alongside `Config::max_blocks_old`'s height-based bound. Wall-clock
+    /// time stays robust across chains with variable block cadence, where a
+    /// height bound alone can drift. `0` disables the check.
+    pub max_price_age_seconds: u64,
+    /// When set, `get_prices` prices this token via `aggregate_price` across
+    /// `pair` plus this policy's `alternate_pairs` instead of querying `pair`
+    /// alone, requiring agreement across several independent feeds before a
+    /// critical collateral asset's price is trusted. `None` keeps the
+    /// single-pair (or `price_path`) behavior.
+    pub aggregation: Option<PriceAggregationPolicy>,
+}
+
+/// One denom recognized as a stable/quote numeraire by `is_stable_denom`,
+/// replacing the old hardcoded `matches!(currency, "USD" | "USDC")` check so
+/// operators can register USDT, DAI, or a non-USD numeraire. `assume_one`
+/// controls whether its price is still taken to be exactly `PrecDec::one()`
+/// (the pre-existing USD/USDC behavior) or, for a stable that can still drift
+/// off its peg, fetched and validated through the oracle like any other base
+/// asset instead of short-circuiting.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StableDenomConfig {
+    pub denom: String,
+    pub assume_one: bool,
+}
+
+/// Configures `aggregate_price`'s multi-feed quorum for one `TokenData`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PriceAggregationPolicy {
+    /// Additional feeds queried alongside `TokenData::pair`; all are treated
+    /// as independent readings of the same asset.
+    pub alternate_pairs: Vec<CurrencyPair>,
+    /// Minimum number of feeds (out of `pair` plus `alternate_pairs`) that
+    /// must pass the not-nil/recency/freshness checks, else
+    /// `ContractError::InsufficientPriceSources`.
+    pub min_valid_sources: u64,
+    /// Maximum allowed spread, in basis points, between the lowest- and
+    /// highest-surviving feed before `aggregate_price` rejects with
+    /// `ContractError::PriceFeedDeviation`. `None` disables the check.
+    pub max_deviation_bps: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -43,9 +108,1325 @@ pub struct Config {
     pub base_deposit_percentage: u64,
     pub ambient_fee: u64,
     pub deposit_ambient: bool,
+    /// single address authorized for owner-gated actions (fund sweeps, the
+    /// cron-gated messages below). Unlike `admin`, there is no multi-address
+    /// set/list here and no `AddOwners`/`RemoveOwners`/`ListOwners` surface —
+    /// ownership transfers by changing this one field, the same pattern
+    /// `admin` uses.
     pub owner: Addr,
+    /// ceiling on the vault's total value (idle balances plus whatever's
+    /// deployed in active DEX positions, oracle-priced the same way
+    /// `query_total_value` reports it) that `deposit` enforces after the
+    /// incoming funds land, rejecting with
+    /// `ContractError::DepositCapExceeded` when it would be exceeded. `0`
+    /// disables the check.
     pub deposit_cap: Uint128,
+    /// total number of vault shares in existence, including the permanently
+    /// locked `MINIMUM_LIQUIDITY`.
+    pub total_shares: Uint128,
+    /// address allowed to pause/unpause the vault and run the purge helpers.
+    /// defaults to `owner` at instantiation.
+    pub admin: Addr,
+    /// graduated killswitch. `Operational` allows everything; `DepositsFrozen`
+    /// rejects `Deposit`/`DexDeposit` but leaves `Withdraw`/`DexWithdrawal`
+    /// (and other non-deposit actions) available so depositors can always
+    /// exit; `Frozen` halts everything except owner/admin-gated messages.
+    pub status: ContractStatus,
+    /// human-readable explanation set alongside `status` by the last
+    /// `ExecuteMsg::SetContractStatus` call. `None` if none was given.
+    pub status_reason: Option<String>,
+    /// block height `status` was last forced to `DepositsFrozen` by
+    /// [`crate::utils::get_prices_with_fallback`] auto-pausing into
+    /// redeem-only mode on an oracle failure with no usable `LAST_GOOD_PRICE`.
+    /// `None` if the vault has never been auto-paused, or was last
+    /// frozen/unfrozen by an explicit `ExecuteMsg::SetContractStatus` instead.
+    pub pause_block: Option<u64>,
+    /// maximum amount of token_0/token_1 (scaled to each token's own decimals)
+    /// that can leave the vault via `Withdraw` within a rolling `max_blocks_old`
+    /// block window. `None` means no limit.
+    pub withdrawal_limit_token_0: Option<Uint128>,
+    pub withdrawal_limit_token_1: Option<Uint128>,
+    /// maximum allowed deviation, in basis points, between the oracle price
+    /// and the price implied by a simulated DEX deposit before it is rejected.
+    pub max_slippage_bps: u64,
+    /// external reward token distributed pro-rata to LPs over time, if configured.
+    pub incentives: Option<IncentiveConfig>,
+    /// EMA weight applied to `price_0_to_1` on each `DexDeposit`, in `[0, 1]`.
+    /// Higher values track the spot price more closely; lower values smooth
+    /// out more of its short-term noise.
+    pub ema_alpha: Decimal,
+    /// maximum allowed deviation, in basis points, between the spot oracle
+    /// price and the tracked EMA before `DexDeposit` guards against it.
+    pub ema_max_deviation_bps: u64,
+    /// when the deviation guard trips: if true, deposit around the EMA price
+    /// instead of the spot price; if false, reject the deposit outright.
+    pub ema_fallback: bool,
+    /// contract queried for the pair's redemption/exchange rate `r`, used to
+    /// compute `p_eff = price_0_to_1 * r` for LSD-correlated pairs whose real
+    /// peg drifts away from 1:1 over time. `None` disables the adjustment.
+    /// Lives here rather than as a `TargetRate` variant on `TokenData`
+    /// because this vault only ever holds one pair, and `p_eff` replaces
+    /// `price_0_to_1`/`token_0_price` wherever the pair as a whole is priced
+    /// (center tick, and `get_deposit_data`'s skew/imbalance split) — a
+    /// per-token enum variant would just mean threading the same provider
+    /// through both `TokenData::token_0`/`token_1` for the one side that
+    /// actually is the stToken. `apply_target_rate` is the one place this
+    /// feeds into the price-assembly path.
+    pub target_rate_provider: Option<Addr>,
+    /// max age, in blocks, of a cached `target_rate_provider` sample before
+    /// it is re-queried.
+    pub target_rate_max_blocks_old: u64,
+    /// seconds over which a newly-queried `target_rate_provider` rate is
+    /// linearly ramped into effect, rather than applied instantly. `0`
+    /// applies each refreshed rate immediately.
+    pub target_rate_amortization_seconds: u64,
+    /// maximum allowed deviation, in basis points, between the raw oracle
+    /// `price_0_to_1` and the `target_rate_provider`-adjusted effective
+    /// price before `apply_target_rate` rejects with
+    /// `ContractError::TargetRateDeviation` instead of deriving the center
+    /// tick from a rate that disagrees sharply with what the market is
+    /// actually quoting. `0` disables the check.
+    pub max_target_rate_deviation_bps: u64,
+    /// maximum allowed change, in basis points per second elapsed, between a
+    /// freshly-queried `target_rate_provider` sample and the previous
+    /// effective rate, the same per-unit-time drift cap
+    /// `RedemptionAdapterConfig::max_redemption_rate_change_bps` applies to a
+    /// CW20 redemption-rate source, so a depegged or compromised provider
+    /// can't yank the center tick in one query. `0` disables the check.
+    pub target_rate_max_drift_bps: u64,
+    /// governed payout split for `ExecuteMsg::DistributeFees`. `None` disables
+    /// `DistributeFees`; `SweepFees` does not depend on it.
+    pub fee_splitter: Option<FeeSplitterConfig>,
+    /// DEX trading fees earned on withdrawn positions (the delta
+    /// `handle_dex_withdrawal_reply` finds above `DEPLOYED_PRINCIPAL`),
+    /// pending `ExecuteMsg::DistributeFees`. Kept separate from `balances` so
+    /// a payout to `fee_splitter` can never dip into LP principal.
+    pub accrued_fees: Balances,
+    /// when true, `DexDeposit` shifts the token_0/token_1 split away from
+    /// 50/50 toward `imbalance_bps` and offsets the center tick by
+    /// `oracle_price_skew`, letting the vault lean into or against inventory
+    /// imbalance. When false, deposits are always balanced 50/50.
+    pub skew: bool,
+    /// target token_0 share of the deposit's value, in basis points out of
+    /// `10000`, applied only when `skew` is true.
+    pub imbalance_bps: u64,
+    /// tick offset applied to the deposit's center tick, applied only when
+    /// `skew` is true.
+    pub oracle_price_skew: i32,
+    /// symmetric bound on `oracle_price_skew`, checked by `Config::validate`:
+    /// `oracle_price_skew` must fall within `+/-max_oracle_price_skew_ticks`.
+    /// Catches a misconfigured skew large enough to walk the deposit's center
+    /// tick off the liquid part of the book before it's saved, rather than
+    /// surfacing as a degenerate `DexDeposit` later.
+    pub max_oracle_price_skew_ticks: u32,
+    /// max age, in seconds, of a tracked EMA sample before `DexDeposit`
+    /// rejects with `ContractError::EmaStale` rather than deposit against it.
+    pub max_ema_age_seconds: u64,
+    /// max allowed ratio, in basis points, of a token's reported oracle
+    /// confidence/standard-deviation band to its spot price. `None` disables
+    /// the check (e.g. when the queried oracle doesn't report confidence).
+    pub max_conf_ratio_bps: Option<u64>,
+    /// spreads the base fee tier's computed deposit across a band of ticks
+    /// around the center instead of placing it all at one tick. `None`
+    /// deposits entirely at the center tick, the pre-existing behavior.
+    pub deposit_band: Option<DepositBandConfig>,
+    /// max allowed drift, in basis points, between the portfolio's oracle
+    /// value split and `rebalance_target_bps` before `DexDeposit` swaps
+    /// toward the target ahead of computing fee-tier allocations. `None`
+    /// disables the pre-deposit rebalance step.
+    pub rebalance_threshold_bps: Option<u64>,
+    /// target token_0 share of the portfolio's oracle value, in basis points
+    /// out of `10000`, the rebalance step swaps toward.
+    pub rebalance_target_bps: u64,
+    /// max ticks the rebalance swap's simulated fill price may imply moving
+    /// away from the deposit's center tick before the swap is skipped.
+    pub max_rebalance_ticks: u64,
+    /// max allowed deviation, in basis points, between the rebalance swap's
+    /// simulated fill price and the oracle price before the swap is skipped.
+    pub max_rebalance_slippage_bps: u64,
+    /// performance fee, in basis points out of `10000`, taken from each
+    /// token's new per-share appreciation above its
+    /// [`PerformanceFeeHighWaterMark`] when `ExecuteMsg::HarvestPerformanceFee`
+    /// runs. `0` disables performance fees (the high-water mark still
+    /// ratchets up). Distributed across `fee_splitter`'s recipients.
+    ///
+    /// Deliberately harvested on its own schedule against the high-water
+    /// mark rather than skimmed out of each `Withdraw`'s payout: a
+    /// withdrawal-time skim would charge the fee on a withdrawer's whole
+    /// balance (principal included) every time they exit, instead of only
+    /// on the share price's appreciation since the last harvest, and would
+    /// let a vault with no activity between withdrawals dodge the fee
+    /// entirely. `management_fee_bps` is the time-based counterpart, accrued
+    /// continuously regardless of performance.
+    pub performance_fee_bps: u64,
+    /// fee, in basis points out of `10000`, charged on the input side of
+    /// `ExecuteMsg::Swap` and left in the vault's reserves rather than paid
+    /// out, so it accrues to LP holders the same way DEX trading fees do.
+    pub swap_fee_bps: u64,
+    /// validator/staking module shares delegate their value to when bonded
+    /// via `ExecuteMsg::Bond`. `None` disables `Bond`/`Unbond` entirely.
+    pub staking_target: Option<Addr>,
+    /// how long, in seconds, `ExecuteMsg::Unbond` locks shares for before
+    /// `withdraw` will burn them.
+    pub unbonding_period_seconds: u64,
+    /// when true, `Deposit` additionally simulates selling the larger side of
+    /// the deposited inventory through the Neutron DEX book and mints shares
+    /// off whichever of the oracle-vs-simulated-fill valuation is lower,
+    /// instead of always trusting the oracle mid-price. Protects existing
+    /// LPs from an imbalanced deposit the oracle prices richer than the book
+    /// could actually absorb.
+    pub book_aware_valuation: bool,
+    /// time constant, in seconds, of the per-token spot-price EMA tracked for
+    /// `Deposit`'s divergence guard. Smaller values track the spot price more
+    /// closely; larger values smooth out more of its short-term noise.
+    pub price_ema_tau_seconds: u64,
+    /// maximum allowed deviation, in basis points, between a token's spot
+    /// oracle price and its tracked EMA before `Deposit` rejects with
+    /// `ContractError::PriceDivergence`.
+    pub max_price_deviation_bps: u64,
+    /// when the per-token deviation guard above trips: if true, `Deposit`
+    /// values that token off its tracked EMA instead of its spot price; if
+    /// false (the default carried over by `v0_1_0_to_v0_2_0`), reject the
+    /// deposit outright. Mirrors `ema_fallback`'s semantics on the older
+    /// `price_0_to_1`-scalar guard.
+    pub price_divergence_fallback: bool,
+    /// sliding-window rate limiter on how fast `Config::total_shares` can
+    /// grow or shrink, checked by every `Deposit`/`Withdraw`. `None` disables
+    /// the check entirely.
+    pub change_limiter: Option<ChangeLimiterConfig>,
+    /// max cumulative oracle-valued `Deposit` contribution a single
+    /// beneficiary address may hold, tracked in `DEPOSITS`. `None` disables
+    /// the per-address check (the pre-existing `deposit_cap` above is a
+    /// separate, vault-wide cap).
+    pub per_address_cap: Option<PrecDec>,
+    /// maximum allowed deviation, in basis points, between the oracle price
+    /// and an `ExecuteMsg::Deposit { auto_balance: true }` swap's realized
+    /// DEX fill price before the deposit aborts with
+    /// `ContractError::SwapSlippageExceeded`.
+    pub dynamic_spread_cap: u64,
+    /// CW20 contract whose tokens are accepted as a `token_0` deposit via
+    /// `ExecuteMsg::Receive`. `None` means `token_0` only accepts the native
+    /// `pair_data.token_0.denom` coin. Also governs how `token_0` is read back
+    /// out: `query_contract_balance` queries this contract's `Balance` instead
+    /// of a bank query, and `payout_message` builds a `Cw20ExecuteMsg::Transfer`
+    /// instead of a `BankMsg::Send` for it. A CW20-backed leg still can't be
+    /// placed into `MsgDeposit`/`MsgWithdrawal` DEX positions - the dex module
+    /// only holds bank-module denoms - so it's limited to the idle side of the
+    /// vault.
+    pub cw20_token_0: Option<Addr>,
+    /// CW20 contract whose tokens are accepted as a `token_1` deposit via
+    /// `ExecuteMsg::Receive`. `None` means `token_1` only accepts the native
+    /// `pair_data.token_1.denom` coin. See `cw20_token_0`'s doc comment for how
+    /// this also governs balance queries and withdrawal payouts.
+    pub cw20_token_1: Option<Addr>,
+    /// how long, in seconds, `ExecuteMsg::QueueWithdrawal` locks a queued withdrawal's
+    /// redemption value for before `ExecuteMsg::Claim` will pay it out.
+    pub withdrawal_queue_period_seconds: u64,
+    /// ladders the base fee tier's computed deposit across several `(fee,
+    /// percentage)` rungs instead of depositing it all at `base_fee`, so a
+    /// vault can concentrate most liquidity tightly while still laddering a
+    /// tail into wider, higher-fee tiers for volatility capture. Empty
+    /// deposits entirely at `base_fee`, the pre-existing behavior.
+    pub fee_tiers: Vec<FeeTier>,
+    /// which of `DepositCurve`'s policies `ladder_fee_tiers` places
+    /// `fee_tiers`' center ticks with. `DepositCurve::Linear` preserves the
+    /// pre-existing fixed-offset placement.
+    pub deposit_curve: DepositCurve,
+    /// widens `base_fee`/`fee_tiers` by a realized-volatility-scaled spread
+    /// on each `DexDeposit`. `None` disables volatility-scaled widening.
+    pub volatility_spread: Option<VolatilitySpreadConfig>,
+    /// number of blocks `ExecuteMsg::UpdateConfig` must wait before
+    /// `ExecuteMsg::CommitConfig` may apply it. `ExecuteMsg::SetContractStatus`
+    /// is unaffected and still applies immediately.
+    pub timelock_blocks: u64,
+    /// additional price sources queried alongside the primary x/oracle module
+    /// feed, each implementing `crate::msg::OracleSourceQueryMsg::Price`.
+    /// Empty disables multi-source aggregation, leaving `get_prices` on the
+    /// pre-existing single-feed behavior.
+    pub oracle_contracts: Vec<Addr>,
+    /// minimum number of `oracle_contracts` responses (after dropping any
+    /// older than `max_blocks_old`) required before `get_prices` trusts their
+    /// element-wise median over the primary x/oracle feed. Only meaningful
+    /// when `oracle_contracts` is non-empty.
+    pub min_sources: u64,
+    /// length, in seconds, of the trailing window `PRICE_OBSERVATIONS`
+    /// computes the TWAP over before `DexDeposit` compares it against spot.
+    pub twap_window_seconds: u64,
+    /// maximum allowed deviation, in basis points, between spot
+    /// `price_0_to_1` and its TWAP before `DexDeposit` skips the
+    /// `skew`/rebalance step for that call rather than rebalancing toward a
+    /// possibly-manipulated spot price. `0` disables the guard.
+    pub max_twap_deviation_bps: u64,
+    /// source for a yield-bearing token's redemption rate, applied to
+    /// whichever of `pair_data.token_0`/`token_1` has denom `lst_asset_denom`
+    /// so `get_prices` prices that leg at its accruing fair value (market
+    /// price of the underlying times the redemption rate) instead of the raw
+    /// 1:1 slinky price. Since `deposit`, `withdraw`, `dex_deposit`, and the
+    /// `per_address_cap` check all value the vault through `get_prices`/
+    /// `get_prices_with_fallback`, the adjustment is automatically reflected
+    /// everywhere those paths read a price. `None` disables the adjustment.
+    pub redemption_adapter: Option<RedemptionAdapterConfig>,
+    /// time-based management fee, in basis points out of `10000` per year,
+    /// minted to `fee_collector` as fresh shares (diluting existing holders)
+    /// by `accrue_management_fee` each time `ExecuteMsg::HarvestPerformanceFee`
+    /// runs, pro-rated by the seconds elapsed since `LAST_FEE_ACCRUAL`. `0`
+    /// disables it. Unlike `performance_fee_bps`, which is only ever charged
+    /// on genuine per-share growth, this accrues unconditionally over time.
+    pub management_fee_bps: u64,
+    /// recipient of minted `management_fee_bps` shares. Required (checked at
+    /// harvest time, the same `NoFeeCollectorConfigured` pattern as
+    /// `fee_splitter`/`NoFeeSplitterConfigured`) whenever
+    /// `management_fee_bps` is non-zero.
+    pub fee_collector: Option<Addr>,
+    /// hard ceiling on `total_shares`, independent of `deposit_cap`'s
+    /// USD-denominated limit, checked with `checked_add` everywhere
+    /// `total_shares` is minted (`deposit`, `accrue_management_fee`) so
+    /// neither path can silently wrap past `Uint128::MAX` or exceed an
+    /// operator-chosen supply bound. `None` disables the check.
+    pub max_total_shares: Option<Uint128>,
+    /// when configured, `DexDeposit` quotes a passive maker ladder of
+    /// `MsgPlaceLimitOrder`s stepping away from the center tick instead of
+    /// pooling liquidity with `MsgDeposit`. `None` keeps the pre-existing
+    /// `MsgDeposit` pooling behavior.
+    pub market_making: Option<MarketMakingConfig>,
+    /// external contracts `ExecuteMsg::CollectRewards` claims DEX/gauge
+    /// incentive emissions from, set via `ExecuteMsg::SetRewardClaimContracts`.
+    /// Whatever denom each claim pays out in (other than `pair_data.token_0`/
+    /// `token_1`, which are this vault's own principal) is tracked in
+    /// `DISTRIBUTED_REWARDS` for `ClaimRewards` to pay depositors out of,
+    /// pro-rata by vault shares. Empty disables `CollectRewards`.
+    pub reward_claim_contracts: Vec<Addr>,
+    /// maximum allowed deviation, in basis points, of any individual
+    /// `oracle_contracts` response from `aggregate_oracle_sources`'s median
+    /// before `get_prices` rejects the whole price with
+    /// `ContractError::PriceDeviation`, instead of silently trusting a
+    /// median one manipulated/broken feed could still skew. `0` disables the
+    /// guard. Only meaningful when `oracle_contracts` is non-empty.
+    pub max_oracle_deviation_bps: u64,
+    /// maximum allowed per-pair price move, in basis points per block elapsed
+    /// since `LAST_ACCEPTED_PAIR_PRICE`'s snapshot, before
+    /// `apply_price_circuit_breaker` rejects the new price with
+    /// `ContractError::PriceJump` instead of trusting a single-block oracle
+    /// spike. `0` disables the guard.
+    pub max_price_jump_bps: u64,
+    /// denoms `validate_market`/`resolve_path_price`/`get_prices` treat as a
+    /// quote-side numeraire instead of requiring them to have their own
+    /// oracle price, replacing the hardcoded USD/USDC check that used to
+    /// force USD-quoting and silently price every USD-denominated base as
+    /// exactly 1. Empty disables the special-casing entirely, requiring
+    /// every pair (including a stable base) to resolve through the oracle.
+    pub stable_denoms: Vec<StableDenomConfig>,
+    /// set once, irrevocably, by `ExecuteMsg::FreezeConfig`. While `true`,
+    /// `ExecuteMsg::UpdateConfig` is rejected with `ContractError::ConfigFrozen`
+    /// even if no update is currently staged; there is no way to clear it
+    /// short of a contract migration.
+    pub config_frozen: bool,
+    /// minimum seconds `dex_deposit` requires to have elapsed since
+    /// `LAST_DEX_DEPOSIT` before running again, so an over-frequent cron
+    /// trigger can't churn the vault's DEX position every block.
+    /// `config.owner` bypasses the throttle (the same override
+    /// `config.owner`/`cron_address` already share for the rest of
+    /// `dex_deposit`'s authorization). `0` disables the guard.
+    pub min_dex_deposit_interval_seconds: u64,
+    /// amplification coefficient `A` for an opt-in 2-asset StableSwap curve
+    /// (see `crate::stableswap`) that, when non-zero, overrides `get_prices`'s
+    /// `price_0_to_1` with the curve's marginal price at the vault's own idle
+    /// `pair_data.token_0`/`token_1` bank balances, rather than the raw
+    /// oracle ratio. Higher values flatten the curve around the peg (less
+    /// slippage for a given imbalance), the same tradeoff Curve's own pools
+    /// expose. Composes with `redemption_adapter`: when both are set, the
+    /// leg matching `redemption_adapter`'s `lst_asset_denom` has its balance
+    /// scaled by the redemption rate `get_prices` has just fetched for it
+    /// before the curve is solved, so
+    /// the curve concentrates around the LST's accruing fair value instead
+    /// of a flat 1:1 peg. `0` disables the override and leaves `get_prices`
+    /// on the plain oracle ratio.
+    pub stableswap_amplification: u64,
+    /// maximum allowed deviation, in basis points, between oracle
+    /// `price_0_to_1` and a live Neutron DEX book read before `DexDeposit`
+    /// (and `Swap`) freezes deposits via `ContractStatus::DepositsFrozen`,
+    /// guarding LPs against quoting against a stale/manipulated oracle
+    /// relative to the book it's actually depositing into. Named
+    /// `dex_deviation_bps` rather than reusing `max_price_deviation_bps`
+    /// above, which already means something unrelated (the per-token
+    /// spot-vs-EMA divergence guard for `Deposit`); this one compares the
+    /// oracle against the DEX, not against itself. `0` disables the guard.
+    pub dex_deviation_bps: u64,
+    /// minimum number of blocks after `pause_block` before a later
+    /// `DexDeposit`/`Swap` call may automatically re-check the deviation
+    /// and resume `ContractStatus::Operational` on its own if it's back
+    /// within `dex_deviation_bps`, instead of waiting on an operator's
+    /// `ExecuteMsg::SetContractStatus`. Only ever auto-resumes a freeze this
+    /// guard itself raised (detected via `status_reason`) - a manual freeze
+    /// an operator set for an unrelated reason is never cleared by this.
+    pub dex_deviation_cooldown_blocks: u64,
+    /// minimum atomic `token_0`/`token_1` amount `get_deposit_data` will place
+    /// as the base deposit's leg; a computed leg below this floor is zeroed
+    /// out instead of deposited, the same "don't pay DEX taker fees on an
+    /// economically pointless micro-deposit" rationale
+    /// `min_rebalance_amount_0`/`min_rebalance_amount_1` apply to
+    /// `prepare_state`'s rebalancing swaps. `0` disables the floor, leaving
+    /// the pre-existing fixed `Uint128::new(10)` dust guard as the only
+    /// threshold.
+    pub min_deposit_amount_0: Uint128,
+    pub min_deposit_amount_1: Uint128,
+    /// minimum simulated `amount_in` `prepare_state` requires before it
+    /// pushes a rebalancing `MsgPlaceLimitOrder` for the token_0 -> token_1 /
+    /// token_1 -> token_0 direction respectively; a simulated fill below this
+    /// floor is skipped instead of placed, so a sub-threshold imbalance
+    /// doesn't pay DEX taker fees for no meaningful rebalancing effect. `0`
+    /// disables the floor (any nonzero simulated `amount_in` is placed, the
+    /// pre-existing behavior).
+    pub min_rebalance_amount_0: Uint128,
+    pub min_rebalance_amount_1: Uint128,
+    /// which of `RebalanceStrategy`'s policies `prepare_state` allocates
+    /// idle `token_0`/`token_1` balances across its two clearing ladders
+    /// with, instead of the single hard-wired "offer the full idle balance
+    /// of both sides" policy every vault instance used to be stuck with.
+    pub rebalance_strategy: RebalanceStrategy,
+    /// addresses eligible to approve an `ExecuteMsg::ProposeConfigUpdate`
+    /// via `ExecuteMsg::ApproveConfigUpdate`, mirroring `oracle_contracts`/
+    /// `min_sources`' "list of sources plus a quorum count" shape. Distinct
+    /// from `owner`/`admin`, which retain their own unilateral authority over
+    /// the emergency paths (`SetContractStatus`, `FreezeConfig`,
+    /// `UpdateConfig`/`CommitConfig`) - `signers`/`threshold` only gate the
+    /// `PROPOSALS` flow.
+    pub signers: Vec<Addr>,
+    /// number of distinct `signers` approvals an `ExecuteMsg::ProposeConfigUpdate`
+    /// entry in `PROPOSALS` needs before `ExecuteMsg::ExecuteConfigUpdate` will
+    /// apply it. Only meaningful when `signers` is non-empty.
+    pub threshold: u32,
+    /// how far the freshly computed `tick_index` may drift from
+    /// `LAST_DEPLOYED_STATE`'s before `dex_deposit` bothers re-running
+    /// `prepare_state`/`get_deposit_messages`/`get_limit_order_messages` at
+    /// all, checked alongside whether `fee_tiers`/`base_fee` themselves
+    /// changed since that snapshot. Below both thresholds, the call is a
+    /// no-op priced identically to the last one, so `dex_deposit` records
+    /// `result = "skipped_no_drift"` and returns without touching the
+    /// already-deployed position. `0` disables the guard, the pre-existing
+    /// behavior of always redeploying.
+    pub rebalance_drift_tolerance_ticks: u64,
+}
+
+/// Configures `get_prices`'s optional yield-bearing-token redemption-rate
+/// adjustment, set by `ExecuteMsg::SetRedemptionAdapter`. A single slot
+/// (rather than a denom-keyed registry covering more than two assets) is
+/// sufficient here: this vault's collateral is exactly `pair_data.token_0`/
+/// `token_1`, and at most one of those two legs is ever the yield-bearing
+/// side needing a rate adjustment — the other is priced directly off the
+/// oracle. `lst_asset_denom` is what selects which of the two legs that is.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RedemptionAdapterConfig {
+    pub lst_asset_denom: String,
+    /// where `crate::utils::apply_redemption_rate` fetches the rate from;
+    /// new wrapped/yield-bearing assets onboard by choosing a variant here
+    /// rather than a bespoke code branch.
+    pub source: RedemptionRateSource,
+    /// absolute floor a fetched rate must clear, guarding against a
+    /// compromised or buggy source returning an absurdly low rate.
+    pub min_redemption_rate: PrecDec,
+    /// absolute ceiling a fetched rate must stay under, same rationale as
+    /// `min_redemption_rate`.
+    pub max_redemption_rate: PrecDec,
+    /// maximum allowed increase over `LAST_REDEMPTION_RATE`, in bps of the
+    /// last accepted rate per second elapsed since `updated_at`, checked by
+    /// `apply_redemption_adapter` alongside the absolute band above.
+    pub max_redemption_rate_change_bps: u64,
+    /// max age, in seconds, `LAST_REDEMPTION_RATE::updated_at` may reach
+    /// before `apply_redemption_adapter` rejects with
+    /// `ContractError::RedemptionRateStale` instead of trusting a baseline
+    /// nobody has successfully refreshed in too long.
+    pub max_rate_age_seconds: u64,
+}
+
+/// The shapes `crate::utils::apply_redemption_rate` knows how to fetch a
+/// yield-bearing token's redemption rate from. Replaces what would otherwise
+/// be one hardcoded query shape (or a growing if/else chain) per new wrapped
+/// asset with a single dispatch over this enum.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RedemptionRateSource {
+    /// a fixed, manually-updated rate set via `SetRedemptionAdapter`; no
+    /// network query.
+    StaticConfig { rate: PrecDec },
+    /// `contract` implements `RedemptionRateQueryMsg::GetRedemptionRate {}`
+    /// returning a bare `PrecDec`, the same shape the `lst-oracle` sibling
+    /// contract exposes, net of a `mint_fee_bps` discount some wrappers
+    /// charge on mint.
+    CoreContractExchangeRate { contract: Addr, mint_fee_bps: u64 },
+    /// `contract` implements an ERC-4626-style
+    /// `Cw4626QueryMsg::ConvertToAssets`, reporting how many underlying
+    /// assets a given amount of wrapped shares is worth.
+    ConvertToAssets { contract: Addr },
+    /// composes two independently-fetched rates by multiplication:
+    /// `apply_redemption_rate(primary) * apply_redemption_rate(secondary)`.
+    /// Prices a doubly-wrapped LST (e.g. a maxBTC-style wrapper over an
+    /// underlying LST) whose fair value isn't captured by either leg's rate
+    /// alone.
+    Composed {
+        primary: Box<RedemptionRateSource>,
+        secondary: Box<RedemptionRateSource>,
+    },
+}
+
+/// Configures [`crate::utils::apply_change_limiter`]'s rolling-window check
+/// on how fast `Config::total_shares` may move in a single `Deposit`/
+/// `Withdraw`, so neither a single large deposit nor a coordinated drain can
+/// move the pool faster than governance allows.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ChangeLimiterConfig {
+    /// length, in seconds, of the rolling window the moving average of
+    /// `total_shares` is computed over.
+    pub window_size: u64,
+    /// number of ring-buffer slots `window_size` is split into. More
+    /// divisions track the average more smoothly but recycle each slot
+    /// sooner.
+    pub divisions: u64,
+    /// max allowed relative deviation, as a `PrecDec` fraction (e.g. `0.2`
+    /// for 20%), between a new `total_shares` and the ring's moving average
+    /// before the operation is rejected with
+    /// `ContractError::ChangeLimitExceeded`.
+    pub boundary_offset: PrecDec,
+}
+
+/// Configures a fixed-rate, fixed-window emission of `reward_denom` to vault
+/// depositors, pro-rata by vault shares. `reward_per_second` is derived as
+/// `total_reward / (end_time - start_time)` rather than stored directly.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct IncentiveConfig {
+    pub reward_denom: String,
+    pub total_reward: Uint128,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+/// Tracks cumulative `Withdraw` amounts within the current rolling block window.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct WithdrawalWindow {
+    pub window_start: u64,
+    pub withdrawn_0: Uint128,
+    pub withdrawn_1: Uint128,
+}
+
+pub const WITHDRAWAL_WINDOW: Item<WithdrawalWindow> = Item::new("withdrawal_window");
+
+/// One ring-buffer slot of a [`ChangeLimiterConfig`]'s rolling-window check.
+/// `integral` is `latest_value` weighted by how long this slot has been open
+/// (`now - started_at`), recomputed fresh each time the slot is read or
+/// written rather than accumulated, so it never drifts out of sync with
+/// `started_at`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ChangeLimiterDivision {
+    pub started_at: u64,
+    pub integral: PrecDec,
+    pub latest_value: PrecDec,
+}
+
+/// Fixed-size ring of `Config::change_limiter`'s `divisions` slots tracking
+/// `total_shares`. Absent (lazily initialized) until the first `Deposit`/
+/// `Withdraw` with a `change_limiter` configured.
+pub const CHANGE_LIMITER_DIVISIONS: Item<Vec<ChangeLimiterDivision>> =
+    Item::new("change_limiter_divisions");
+
+/// One beneficiary address's cumulative oracle-valued `Deposit` contribution
+/// and minted shares, checked against `Config::per_address_cap` and readable
+/// privately via `QueryMsg::WithPermit`. Absent until that address's first
+/// `Deposit`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct DepositRecord {
+    pub deposited_value: PrecDec,
+    pub shares_minted: Uint128,
+}
+
+pub const DEPOSITS: Map<Addr, DepositRecord> = Map::new("deposits");
+
+/// exponential moving average of `price_0_to_1` plus the block timestamp it
+/// was last updated, updated on each `DexDeposit` per `Config::ema_alpha`.
+/// Absent until the first `DexDeposit` seeds it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EmaPriceCache {
+    pub price: PrecDec,
+    pub updated_at: u64,
+}
+
+pub const EMA_PRICE: Item<EmaPriceCache> = Item::new("ema_price");
+
+/// Per-token EMAs of `token_0_price`/`token_1_price` plus the block timestamp
+/// they were last updated, updated on each `Deposit` per
+/// `Config::price_ema_tau_seconds`. Distinct from [`EMA_PRICE`], which tracks
+/// the combined `price_0_to_1` ratio for `DexDeposit`'s tick-centering guard
+/// rather than `Deposit`'s per-token divergence guard. Absent until the first
+/// `Deposit` seeds it.
+///
+/// Holds only the running EMA and its last-update timestamp, not a ring
+/// buffer of raw `(height, exchange_rate)` samples - an exponentially
+/// decaying average is fully summarized by its current value, so storing a
+/// window of past samples alongside it would be redundant state we'd still
+/// have to read and fold on every call. `time_decayed_alpha` recovers the
+/// same `alpha = 1 - exp(-dt / tau)` a fixed window of samples would
+/// approximate, from nothing but `dt` and `Config::price_ema_tau_seconds`.
+/// Staleness is guarded independently of the spot price's own
+/// `Config::max_blocks_old`/`PriceTooOld` check: `query_recent_valid_
+/// prices_formatted` rejects a read against this cache older than
+/// `Config::max_ema_age_seconds` via `validate_price_reliability`, while
+/// `apply_price_divergence_guard` (the `Deposit`-path guard below) re-seeds
+/// it on every call and so never observes it stale.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TokenPriceEmaCache {
+    pub token_0_ema: PrecDec,
+    pub token_1_ema: PrecDec,
+    pub updated_at: u64,
+}
+
+pub const TOKEN_PRICE_EMA: Item<TokenPriceEmaCache> = Item::new("token_price_ema");
+
+/// Cached sample of `Config::target_rate_provider`'s redemption/exchange
+/// rate, refreshed once it is older than `Config::target_rate_max_blocks_old`.
+/// `rate` ramps linearly from `prev_rate` at `updated_at` to `rate` over
+/// `Config::target_rate_amortization_seconds`, so a new print never jumps the
+/// effective rate in a single block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TargetRateCache {
+    pub rate: Decimal,
+    pub prev_rate: Decimal,
+    pub block_height: u64,
+    pub updated_at: u64,
+}
+
+pub const TARGET_RATE: Item<TargetRateCache> = Item::new("target_rate");
+
+/// One `price_0_to_1` observation sampled on `DexDeposit`, kept in
+/// `PRICE_HISTORY`'s rolling window for [`crate::volatility`]'s
+/// realized-volatility estimate.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PriceSample {
+    pub price_0_to_1: PrecDec,
+    pub timestamp: u64,
+}
+
+/// Rolling window of the last `Config::volatility_spread`'s `window_size`
+/// `price_0_to_1` samples, oldest first. Absent (lazily initialized) until
+/// the first `DexDeposit` with `volatility_spread` configured.
+pub const PRICE_HISTORY: Item<Vec<PriceSample>> = Item::new("price_history");
+
+/// One `price_0_to_1` observation sampled on `DexDeposit`, kept in
+/// `PRICE_OBSERVATIONS`'s rolling window for [`crate::twap`]'s
+/// time-weighted-average estimate.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PriceObservation {
+    pub price_0_to_1: PrecDec,
+    pub timestamp: u64,
+}
+
+/// Rolling window of `price_0_to_1` observations no older than
+/// `Config::twap_window_seconds`, oldest first, capped at
+/// [`crate::twap::MAX_OBSERVATIONS`] slots. Absent (lazily initialized)
+/// until the first `DexDeposit`.
+pub const PRICE_OBSERVATIONS: Item<Vec<PriceObservation>> = Item::new("price_observations");
+
+/// Widens the deployed fee tier(s) during turbulent periods: `window_size`
+/// recent `price_0_to_1` samples (kept in `PRICE_HISTORY`) feed a
+/// realized-volatility estimate (standard deviation of log-returns), which
+/// is multiplied by `spread_multiplier` into a widening applied, in basis
+/// points, equally to `Config::base_fee` and every `Config::fee_tiers` rung's
+/// `fee` (which doubles as a tick offset, per [`crate::volatility`]), clamped
+/// to `max_spread_bps`. `None` on `Config` disables volatility-scaled
+/// widening entirely, leaving deposits at their configured fee tier(s).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VolatilitySpreadConfig {
+    pub window_size: u64,
+    pub spread_multiplier: PrecDec,
+    pub max_spread_bps: u64,
+}
+
+/// Fixed denominator `FeeSplitterConfig::recipients` weights must sum to.
+pub const FEE_SPLITTER_DENOMINATOR: u64 = 10000;
+
+/// Governed payout split used by `ExecuteMsg::DistributeFees`. `recipients`
+/// are (address, weight) pairs whose weights must sum to
+/// `FEE_SPLITTER_DENOMINATOR`; the last recipient absorbs any integer-division
+/// remainder so the full balance is always paid out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FeeSplitterConfig {
+    pub recipients: Vec<(Addr, u64)>,
+}
+
+/// Graduated killswitch tracked at `Config::status`, set via the admin-only
+/// `ExecuteMsg::SetContractStatus` and checked by `require_deposits_allowed`/
+/// `require_not_frozen` at the top of the deposit- and withdrawal-reply
+/// handlers. See its field doc for exactly what each level blocks.
+/// `DepositsFrozen` covers both "deposits paused" and "withdrawals only"
+/// framings of the same gating rule, since `require_deposits_allowed`/
+/// `require_not_frozen` never need to tell those two apart: either deposits
+/// are blocked and withdrawals stay open, or everything is blocked
+/// (`Frozen`).
+///
+/// `WindDown` is `DepositsFrozen`'s one-way variant for retiring a vault:
+/// deposits stay blocked exactly the same way, but once the last share is
+/// burned (`withdraw`'s `config.total_shares` reaches zero while in this
+/// state) the vault auto-clears `DEPLOYED_PRINCIPAL`/`Config::market_making`
+/// and transitions itself to `Frozen`, so no deposit can ever be re-enabled
+/// on it again - the admin would have to migrate a fresh vault instead.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractStatus {
+    Operational,
+    DepositsFrozen,
+    WindDown,
+    Frozen,
+}
+
+/// How a [`DepositBandConfig`] spreads weight across its ticks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BandWeightProfile {
+    /// equal depth at every tick in the band.
+    Uniform,
+    /// depth tapers linearly from the center tick down to the band's edges.
+    Triangular,
+    /// reserves at each tick follow a constant-product (x*y=k) curve
+    /// centered on the oracle price: token_0 depth grows below the center
+    /// price and token_1 depth grows above it, geometrically spaced by
+    /// `tick_step`, instead of splitting the same ratio at every tick.
+    ConstantProduct,
+    /// depth falls off from the center tick following a normal curve with
+    /// standard deviation `sigma_ticks` (in units of `tick_step`-sized hops,
+    /// not raw ticks), concentrating liquidity near the center more sharply
+    /// than `Triangular`'s linear taper.
+    Gaussian { sigma_ticks: u64 },
+    /// depth falls off from the center tick following a Lorentzian/Cauchy
+    /// curve `w_i ∝ 1 / (1 + (i / amplification)^2)`, the StableSwap-style
+    /// taper a pegged pair (stablecoins, LSD vs underlying) wants: a fatter,
+    /// longer tail than `Gaussian`'s normal curve, so depth stays meaningful
+    /// a few ticks out instead of collapsing to near-zero. Distinct from
+    /// `Config::stableswap_amplification`, which overrides `get_prices`'s
+    /// marginal price with a StableSwap curve's - this `amplification` only
+    /// shapes how one fee tier's deposit is spread across the band, the two
+    /// features compose but don't share state. Per the formula, a *smaller*
+    /// `amplification` concentrates more tightly (every off-center `w_i`
+    /// shrinks toward `0` as `(i / amplification)^2` grows), bottoming out at
+    /// `amplification == 0`, which is treated as the fully concentrated
+    /// single-tick limit rather than dividing by zero. A *larger*
+    /// `amplification` flattens the curve toward `Uniform` as `(i /
+    /// amplification)^2 → 0` for every in-band `i`.
+    StableSwap { amplification: u64 },
+}
+
+/// How `ladder_fee_tiers` places each `Config::fee_tiers` rung's center tick,
+/// selected per-vault via `Config::deposit_curve`. Orthogonal to
+/// `BandWeightProfile::ConstantProduct`, which applies the same `x*y=k`
+/// shape one layer further in - splitting a single, already-placed tier's
+/// deposit across `Config::deposit_band`'s ticks, not choosing where that
+/// tier's center tick sits in the first place.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositCurve {
+    /// every tier's center tick sits exactly `tier.fee` ticks from the base
+    /// tick, the fixed offset `ladder_fee_tiers` has always used.
+    Linear,
+    /// every tier's center tick is instead read off a virtual `x*y=k` curve:
+    /// `k` comes from the vault's current idle balances (the curve's
+    /// depth), re-centered onto the oracle mid-price, then each tier is
+    /// walked `tier.percentage` of the way out along that curve to find its
+    /// marginal price. Depth auto-concentrates near the oracle price and
+    /// thins out symmetrically, the way a concentrated-liquidity position's
+    /// reserves would, instead of `Linear`'s flat per-tier spacing.
+    ConstantProduct,
+}
+
+/// How `prepare_state` allocates a vault's idle `token_0`/`token_1`
+/// balances across its two IoC clearing-ladder loops (sell token_0 for
+/// token_1, and the reverse) before a deposit, selected per-vault via
+/// `Config::rebalance_strategy` instead of being hard-wired to a single
+/// policy. Every variant still launders its clearing amounts through the
+/// same `ladder_clearing_amounts`/`simulate_place_limit_order` path - only
+/// *how much* of each side's idle balance is offered to the ladder (and
+/// whether a side is offered at all) changes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RebalanceStrategy {
+    /// offer the full idle balance of both `token_0` and `token_1` to their
+    /// respective clearing ladders, independently - the pre-existing,
+    /// unconditional `prepare_state` behavior.
+    Balanced,
+    /// scale each side's offered balance by the oracle's current
+    /// `price_0_to_1`, so the side the oracle currently prices as worth more
+    /// per atomic unit offers proportionally less of its raw balance,
+    /// keeping the oracle-priced value offered on each side roughly equal
+    /// instead of letting a lopsided atomic-unit balance dominate the
+    /// clearing ladder.
+    OraclePriceWeighted,
+    /// only ladder-clear one side; the other side's idle balance is left
+    /// untouched; no limit orders are placed for it at all. `sell_token_0`
+    /// picks which side clears: `true` sells token_0 for token_1, `false`
+    /// sells token_1 for token_0.
+    SingleSided { sell_token_0: bool },
+}
+
+/// Spreads the base fee tier's computed `(amount0, amount1)` deposit across
+/// `2 * half_width + 1` ticks stepped by `tick_step` around the center tick,
+/// approximating a continuous-range AMM curve instead of a single-point
+/// order. `half_width` of `0` (the default) deposits entirely at the center
+/// tick, the pre-existing single-tick behavior. `profile` picks the curve
+/// shape `crate::utils::split_deposit_across_band` weights each tick by,
+/// `BandWeightProfile::ConstantProduct` being the `x*y=k` shape: each tick's
+/// share is derived from `crate::utils::constant_product_band_split`'s local
+/// slope of the hyperbola at that tick's price, not a flat per-tick split.
+/// `crate::utils::split_deposit_across_band`'s own
+/// `split_deposit_across_band_conserves_the_total` proptest already asserts
+/// every profile's distributed amounts sum back to the tier allocation
+/// exactly, with no dust loss.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DepositBandConfig {
+    pub half_width: u64,
+    pub tick_step: u64,
+    pub profile: BandWeightProfile,
+}
+
+/// This is already the "pick a deposit strategy" choice: `Config::deposit_band`
+/// being `None` is the plain oracle-centered single-tick placement, and
+/// `Some(DepositBandConfig { profile: BandWeightProfile::ConstantProduct, .. })`
+/// is the xyk curve replication across `[center_tick - half_width * tick_step,
+/// center_tick + half_width * tick_step]`, both selected the same way any
+/// other `Config` field is - at instantiate, or later via
+/// `MigrateMsg::config_override`/`ExecuteMsg::UpdateConfig`. There's no
+/// separate `DepositStrategy` enum because `deposit_band` already is one:
+/// an `Option` discriminates the two modes and `profile` picks the curve
+/// shape within the enabled one.
+
+/// One rung of a multi-tier liquidity ladder, as used by `Config::fee_tiers`.
+/// `percentage` of the base fee tier's computed deposit is placed `fee`
+/// ticks away from the oracle tick instead of all at the center, the same
+/// "fee value doubles as a tick offset" convention `prepare_state` already
+/// uses for `Config::base_fee`. A vault's `fee_tiers`' `percentage`s must sum
+/// to `100`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FeeTier {
+    pub fee: u64,
+    pub percentage: u64,
+}
+
+/// Configures `get_limit_order_messages`' passive maker ladder, the
+/// order-book-quoting alternative to `get_deposit_messages`' `MsgDeposit`
+/// pooling. `rungs` orders are placed on each side of the center tick,
+/// `tick_step` ticks apart, with the heavier inventory side (per `skew`/
+/// `imbalance_bps`, the same bias `get_deposit_data` applies to pooled
+/// deposits) sized larger than the lighter side.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MarketMakingConfig {
+    /// number of orders placed on each side (bid/ask) of the center tick.
+    pub rungs: u64,
+    /// tick distance between consecutive rungs on the same side.
+    pub tick_step: u64,
+    /// how long, in seconds, each placed order stays resting before it
+    /// auto-cancels. `0` places `LimitOrderType::GoodTilCancelled` orders
+    /// instead, which rest until the next `DexDeposit` or an explicit
+    /// withdrawal cancels them.
+    pub order_expiration_seconds: u64,
+}
+
+/// Highest-ever per-share redemption value observed for each token,
+/// checkpointed on every `ExecuteMsg::HarvestPerformanceFee`. Performance fees
+/// are only charged on the portion of a token's per-share value that exceeds
+/// its own high-water mark, so depositors are never charged on value they
+/// contributed themselves, only on genuine per-share growth. Absent until the
+/// first harvest seeds it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PerformanceFeeHighWaterMark {
+    pub token_0_per_share: PrecDec,
+    pub token_1_per_share: PrecDec,
+}
+
+pub const PERFORMANCE_FEE_HWM: Item<PerformanceFeeHighWaterMark> =
+    Item::new("performance_fee_hwm");
+
+/// `env.block.time` of the last `accrue_management_fee` run, used to pro-rate
+/// `Config::management_fee_bps` over the elapsed interval. Absent until the
+/// first harvest seeds it, mirroring `PERFORMANCE_FEE_HWM`'s bootstrap.
+pub const LAST_FEE_ACCRUAL: Item<u64> = Item::new("last_fee_accrual");
+
+/// The tick/amount/fee-tier data of a DEX deposit `MsgDeposit`, either one
+/// dispatched as a `reply_on_error` sub-message awaiting its reply (in
+/// `PENDING_DEX_DEPOSIT`, with `error` left empty), or one whose reply came
+/// back with an error and is now recorded for `ExecuteMsg::RetryDeposit` (in
+/// `FAILED_DEPOSITS`, with `error` set to the failure reason).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FailedDeposit {
+    pub token_a: String,
+    pub token_b: String,
+    pub amounts_a: Vec<String>,
+    pub amounts_b: Vec<String>,
+    pub tick_indexes_a_to_b: Vec<i64>,
+    pub fees: Vec<u64>,
+    pub error: String,
+}
+
+/// Stashed immediately before a DEX deposit `MsgDeposit` is dispatched as a
+/// `reply_on_error` sub-message, since the reply itself carries only the
+/// error, not the original message. A `reply_on_error` sub-message only
+/// replies on failure, so the `reply` entry point on `DEX_DEPOSIT_REPLY_ID`
+/// consumes it only then, moving it into `FAILED_DEPOSITS`; after a
+/// successful deposit it stays until the next dispatch overwrites it.
+pub const PENDING_DEX_DEPOSIT: Item<FailedDeposit> = Item::new("pending_dex_deposit");
+
+/// Incrementing id for `FAILED_DEPOSITS`, bumped each time a DEX deposit
+/// sub-message's reply comes back with an error.
+pub const FAILED_DEPOSIT_SEQ: Item<u64> = Item::new("failed_deposit_seq");
+
+/// DEX deposits that failed and were recorded instead of silently dropping
+/// the idle funds, keyed by an incrementing id. Retried via
+/// `ExecuteMsg::RetryDeposit { id }`, which clears the entry as it
+/// resubmits; a retry that fails again is recorded under a new id.
+pub const FAILED_DEPOSITS: Map<u64, FailedDeposit> = Map::new("failed_deposits");
+
+/// Cumulative token_0/token_1 principal currently parked in DEX positions,
+/// incremented by `dex_deposit` each time it deploys idle funds. Zeroed out
+/// by `handle_dex_withdrawal_reply` once a `create_dex_withdrawal_messages`
+/// batch fully settles, since that batch always exits every open position at
+/// once. The gap between what comes back on exit and this baseline is what
+/// gets credited to `Config::accrued_fees` instead of `Config::balances`.
+pub const DEPLOYED_PRINCIPAL: Item<Balances> = Item::new("deployed_principal");
+
+/// `env.block.time.seconds()` at the end of `dex_deposit`'s last successful
+/// run, checked against `Config::min_dex_deposit_interval_seconds` to
+/// throttle how often a cron trigger can re-run it. Absent before the first
+/// `dex_deposit` call.
+pub const LAST_DEX_DEPOSIT: Item<u64> = Item::new("last_dex_deposit");
+
+/// Snapshot of what `dex_deposit` last actually deployed: the oracle-derived
+/// `tick_index` it priced around and the `fee_tiers`/`base_fee` it laddered
+/// with. Compared against the freshly computed values on the next call so a
+/// run with no meaningful drift (within `Config::rebalance_drift_tolerance_ticks`)
+/// can skip the withdraw-redeposit churn entirely instead of re-placing an
+/// effectively identical position. Absent before the first `dex_deposit` call,
+/// which always runs in full.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LastDeployedState {
+    pub tick_index: i64,
+    pub fee_tiers: Vec<FeeTier>,
+    pub base_fee: u64,
+}
+
+pub const LAST_DEPLOYED_STATE: Item<LastDeployedState> = Item::new("last_deployed_state");
+
+/// An in-flight `create_dex_withdrawal_messages` batch, accumulated across
+/// however many `MsgWithdrawal` sub-messages it dispatched (one per on-chain
+/// DEX deposit) since each reply only carries that single message's reserves.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingWithdrawal {
+    pub remaining: u64,
+    pub received_0: Uint128,
+    pub received_1: Uint128,
+}
+
+/// Set by `create_dex_withdrawal_messages` to the size of the batch it just
+/// built, then drained by `handle_dex_withdrawal_reply` as each
+/// `DEX_WITHDRAW_REPLY_ID` reply comes in; removed once `remaining` hits 0.
+pub const PENDING_DEX_WITHDRAWAL: Item<PendingWithdrawal> = Item::new("pending_dex_withdrawal");
+
+/// Where `handle_user_withdrawal_reply` sends the combined idle + DEX slice
+/// once a `PENDING_USER_WITHDRAWAL` batch settles.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalSettlement {
+    /// `withdraw`'s path: pay `recipient` immediately, subject to
+    /// `min_amount_0_out`/`min_amount_1_out`.
+    Immediate,
+    /// `queue_withdrawal`'s path: no payout yet, instead write a
+    /// `WITHDRAWAL_QUEUE` entry under `(recipient, seq)` that unlocks at
+    /// `release_at`, same as an idle-only queued withdrawal would.
+    Queued { seq: u64, release_at: u64 },
 }
 
+/// An in-flight `withdraw`/`queue_withdrawal` that pulled a pro-rata slice of
+/// shares out of active DEX positions (not just idle `Config::balances`)
+/// instead of paying out (or queuing) less than the sender's fair share
+/// while funds sit deployed. Accumulated across however many `MsgWithdrawal`
+/// sub-messages `create_pro_rata_dex_withdrawal_messages` dispatched (one
+/// per on-chain DEX deposit) since each `DEX_USER_WITHDRAW_REPLY_ID` reply
+/// only carries that single message's reserves; `recipient`/`idle_amount_0`/
+/// `idle_amount_1`/`min_amount_0_out`/`min_amount_1_out`/`deadline`/
+/// `settlement` carry the rest of what the caller already knew synchronously
+/// so `handle_user_withdrawal_reply` can finish the job once the batch settles.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingUserWithdrawal {
+    pub recipient: Addr,
+    pub remaining: u64,
+    pub received_0: Uint128,
+    pub received_1: Uint128,
+    pub idle_amount_0: Uint128,
+    pub idle_amount_1: Uint128,
+    pub denom_0: String,
+    pub denom_1: String,
+    pub min_amount_0_out: Option<Uint128>,
+    pub min_amount_1_out: Option<Uint128>,
+    /// block height by which the withdrawal must settle, re-checked here
+    /// since `handle_user_withdrawal_reply` may finish several blocks after
+    /// `withdraw` submitted the pro-rata `MsgWithdrawal` batch. `None`
+    /// disables the check.
+    pub deadline: Option<u64>,
+    pub settlement: WithdrawalSettlement,
+}
+
+/// Set by `withdraw`/`queue_withdrawal` to the size of the pro-rata DEX
+/// withdrawal batch it just dispatched, then drained by
+/// `handle_user_withdrawal_reply` as each `DEX_USER_WITHDRAW_REPLY_ID` reply
+/// comes in; removed once `remaining` hits 0 and the combined amount has
+/// been paid out or queued, per `settlement`. At most one withdrawal can be
+/// mid-settlement at a time, since both reject a new one while this is
+/// occupied.
+pub const PENDING_USER_WITHDRAWAL: Item<PendingUserWithdrawal> = Item::new("pending_user_withdrawal");
+
+/// Lifecycle of `execute_collect_rewards`'s claim-then-distribute flow.
+/// `Ready` accepts a new `CollectRewards` call; `Claiming` is set while its
+/// `MsgExecute` claim sub-messages are in flight, guarding against a second
+/// overlapping collection stomping the same `PENDING_REWARD_CLAIM_SNAPSHOT`.
+/// `Distributing` is reserved for a future explicit multi-step payout phase:
+/// today's per-depositor distribution is computed lazily (pro-rata by share,
+/// the same `REWARD_PER_SHARE`/`USER_REWARD_DEBT` pattern as `ClaimIncentives`)
+/// whenever `ClaimRewards` is called, so nothing needs to hold the status
+/// there and `handle_reward_claim_reply` resets straight back to `Ready`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardsStatus {
+    #[default]
+    Ready,
+    Claiming,
+    Distributing,
+}
+
+/// Current `RewardsStatus`. Absent (defaults to `Ready`) until the first
+/// `ExecuteMsg::CollectRewards` call.
+pub const REWARDS_STATUS: Item<RewardsStatus> = Item::new("rewards_status");
+
+/// Full balance snapshot (every denom), taken right before
+/// `execute_collect_rewards` dispatches its claim messages, so
+/// `handle_reward_claim_reply` can diff the contract's post-claim balance
+/// against it to learn exactly what came back. No shared reply schema can be
+/// assumed across external reward-claim contracts, so this stands in for
+/// parsing any particular one's reply `data`.
+pub const PENDING_REWARD_CLAIM_SNAPSHOT: Item<CoinList> = Item::new("pending_reward_claim_snapshot");
+
+/// Accumulates `Coin`s across however many distinct denoms a set of claims
+/// produces, merging same-denom amounts instead of appending duplicate
+/// entries. Used for `DISTRIBUTED_REWARDS`' running total of every external
+/// reward denom `CollectRewards` has ever realized.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct CoinList(pub Vec<Coin>);
+
+impl CoinList {
+    pub fn add(&mut self, coin: Coin) {
+        if coin.amount.is_zero() {
+            return;
+        }
+        match self.0.iter_mut().find(|existing| existing.denom == coin.denom) {
+            Some(existing) => existing.amount += coin.amount,
+            None => self.0.push(coin),
+        }
+    }
+}
+
+/// Cumulative total, across every `CollectRewards` call ever settled, of
+/// each external reward denom realized. Informational/queryable; the actual
+/// per-depositor entitlement is tracked by `EXTERNAL_REWARD_PER_SHARE`.
+pub const DISTRIBUTED_REWARDS: Item<CoinList> = Item::new("distributed_rewards");
+
+/// `Config::total_shares` snapshotted at the most recent settled
+/// `CollectRewards`, the denominator `handle_reward_claim_reply` divides
+/// each newly claimed denom's amount by when bumping `EXTERNAL_REWARD_PER_SHARE`.
+pub const CURRENT_TOTAL_SUPPLY: Item<Uint128> = Item::new("current_total_supply");
+
+/// Cumulative reward-per-share for each external reward denom, the same
+/// `REWARD_PER_SHARE` mechanism `ClaimIncentives` uses for
+/// `Config::incentives.reward_denom`, generalized to however many distinct
+/// denoms `CollectRewards` has claimed rather than one fixed denom.
+pub const EXTERNAL_REWARD_PER_SHARE: Map<String, Decimal> = Map::new("external_reward_per_share");
+
+/// `EXTERNAL_REWARD_PER_SHARE` snapshot at each depositor's last
+/// `ClaimRewards`/deposit/withdraw for a given denom, keyed by
+/// `(depositor, denom)`; `pending_incentives(shares, per_share, debt)`
+/// reconstructs what's currently owed for that denom.
+pub const USER_EXTERNAL_REWARD_DEBT: Map<(Addr, String), Decimal> = Map::new("user_external_reward_debt");
+
+/// Fallback fee tiers used when the DEX module's fee-tier query is unavailable.
+pub const FALLBACK_FEE_TIERS: [u64; 12] = [0, 1, 2, 3, 4, 5, 10, 20, 50, 100, 150, 200];
+
+/// Per-denom sub-unit remainder `withdraw`'s
+/// `balances[i].amount.multiply_ratio(amount, total_shares)` floors away on
+/// every partial burn. Each call's fractional loss (`< 1` token) is added
+/// here in full precision; whenever the running total crosses a whole unit,
+/// that unit is carved out into `DUST` and subtracted back out, so `DUST`
+/// only ever holds amounts that are actually real, sendable tokens.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct DustRemainder {
+    pub token_0: Decimal,
+    pub token_1: Decimal,
+}
+pub const DUST_REMAINDER: Item<DustRemainder> = Item::new("dust_remainder");
+
+/// Whole-unit rounding dust `withdraw` has carved out of `DUST_REMAINDER` so
+/// far, per `Config::pair_data` denom - real balance the contract holds that
+/// floor division never assigned to any depositor. Absent (defaults to zero
+/// for both) until the first whole unit accumulates. Swept into the payout
+/// of the withdrawal that burns the last outstanding share, or by
+/// `ExecuteMsg::SweepDust` before then.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct DustBalances {
+    pub token_0: Uint128,
+    pub token_1: Uint128,
+}
+pub const DUST: Item<DustBalances> = Item::new("dust");
+/// DEX fee tiers currently accepted as a valid `base_fee`, refreshed from the
+/// DEX module at instantiate time and via `ExecuteMsg::RefreshFeeTiers {}`.
+pub const ALLOWED_FEE_TIERS: Item<Vec<u64>> = Item::new("allowed_fee_tiers");
+
 // pub const PAIRDATA: Item<PairData> = Item::new("data");
 pub const CONFIG: Item<Config> = Item::new("data");
+/// vault shares owned by each depositor, proportional to their claim on the
+/// idle token balances plus any funds deployed in DEX limit orders. There is
+/// no separate `TOTAL_SHARES: Item<Uint128>` alongside this map -
+/// `Config::total_shares` already is that running total, updated in lockstep
+/// with `SHARES` by every mint (`deposit`) and burn (`withdraw`), with one
+/// deliberate exception: the first deposit's permanently-locked
+/// `MINIMUM_LIQUIDITY` (see below) is folded into `total_shares` but never
+/// credited to any `SHARES` entry, so it can never be reconciled back out
+/// via a withdrawal. `crate::utils::shares_to_mint` is the funder/shares
+/// pricing formula itself: the first deposit seeds `total_shares` 1:1 with
+/// its value (less the permanently-locked `MINIMUM_LIQUIDITY`, guarding the
+/// empty-vault donation/inflation attack), and every later deposit mints
+/// `value * total_shares / vault_value_before`
+/// - `vault_value_before` already folds in whatever's deployed on the DEX,
+/// not just idle balances (see `book_aware_prices`/`total_vault_value`'s
+/// callers in `execute::deposit`). `withdraw` burns the caller's shares for
+/// `shares / total_shares` of both token balances, plus the same slice of
+/// every open DEX position via `create_pro_rata_dex_withdrawal_messages`
+/// whenever liquidity is deployed.
+pub const SHARES: Map<Addr, Uint128> = Map::new("shares");
+
+/// each depositor's shares currently bonded to `Config::staking_target` via
+/// `ExecuteMsg::Bond`, a subset of their `SHARES`. Still counted in `SHARES`
+/// (bonded shares keep earning the vault's own yield on top of the staking
+/// target's), but `withdraw` refuses to burn them until unbonded and matured.
+pub const BONDED_SHARES: Map<Addr, Uint128> = Map::new("bonded_shares");
+
+/// a single `ExecuteMsg::Unbond` request still within `Config::
+/// unbonding_period_seconds` of its own shares becoming withdrawable again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct UnbondingEntry {
+    pub amount: Uint128,
+    pub release_at: u64,
+}
+
+/// each depositor's in-flight unbonding requests, oldest first. Entries with
+/// `release_at <= now` are matured and no longer lock `withdraw`, but are
+/// only actually pruned the next time `bond`/`unbond`/`withdraw` touches the
+/// address (the same lazy-rollover style as `WITHDRAWAL_WINDOW`).
+pub const UNBONDING_SHARES: Map<Addr, Vec<UnbondingEntry>> = Map::new("unbonding_shares");
+
+/// a single deposit tranche minted as a transferable position NFT alongside
+/// the depositor's ordinary `SHARES` entry, so an LP stake can be handed off
+/// or used as collateral without moving the underlying funds. `shares` is a
+/// claim on `owner`'s `SHARES` balance, released back to ordinary fungible
+/// shares when `ExecuteMsg::WithdrawPosition` burns the token.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PositionNft {
+    pub owner: Addr,
+    pub shares: Uint128,
+}
+
+/// incrementing id for `POSITIONS`, bumped each time `ExecuteMsg::Deposit`
+/// mints a new position NFT. Ids are assigned internally; callers never
+/// choose their own `token_id`.
+pub const NEXT_POSITION_ID: Item<u64> = Item::new("next_position_id");
+
+/// position NFTs minted by `ExecuteMsg::Deposit` and burned by
+/// `ExecuteMsg::WithdrawPosition { token_id }`, keyed by the token id.
+pub const POSITIONS: Map<u64, PositionNft> = Map::new("positions");
+
+/// secondary index of `POSITIONS` by owner, so `QueryMsg::Tokens` can
+/// enumerate an address's positions without a full `POSITIONS` scan. Kept in
+/// sync with `POSITIONS` on every mint/burn/transfer.
+pub const POSITIONS_BY_OWNER: Map<(Addr, u64), Empty> = Map::new("positions_by_owner");
+
+/// a queued exit requested via `ExecuteMsg::QueueWithdrawal { shares }`: the
+/// shares are burned and their redemption value snapshotted immediately, so
+/// `token_0`/`token_1` are fixed at request time and don't move with later
+/// deposits/withdrawals. `ExecuteMsg::Claim` pays it out once `release_at`
+/// has passed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct UnbondEntry {
+    pub token_0: Coin,
+    pub token_1: Coin,
+    pub release_at: u64,
+}
+
+/// incrementing id for `WITHDRAWAL_QUEUE`, bumped on each
+/// `ExecuteMsg::QueueWithdrawal` so the same address can have multiple
+/// entries in flight at once.
+pub const WITHDRAWAL_QUEUE_SEQ: Item<u64> = Item::new("withdrawal_queue_seq");
+
+/// in-flight `ExecuteMsg::QueueWithdrawal` requests, keyed by
+/// `(requester, seq)`. `ExecuteMsg::Claim` iterates a requester's prefix,
+/// pays out and removes every entry whose `release_at <= now`, and leaves
+/// the rest untouched.
+pub const WITHDRAWAL_QUEUE: Map<(Addr, u64), UnbondEntry> = Map::new("withdrawal_queue");
+
+/// a point-in-time record of the vault's total share supply and idle
+/// token_0/token_1 balances, appended by every `Deposit`/`Receive`/
+/// `Withdraw`/`WithdrawPosition`/`QueueWithdrawal` so `GetSharePriceAtHeight`/
+/// `GetTwapSharePrice` can reconstruct a manipulation-resistant historical
+/// share price instead of only the current spot value.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Snapshot {
+    pub total_shares: Uint128,
+    pub total_token_0: Uint128,
+    pub total_token_1: Uint128,
+}
+
+/// `Snapshot`s keyed by the block height they were recorded at; overwritten
+/// if more than one deposit/withdrawal lands in the same block.
+pub const SNAPSHOTS: Map<u64, Snapshot> = Map::new("snapshots");
+
+/// cumulative `incentives` reward earned per vault share, accrued up to
+/// `LAST_REWARD_TIME`. Scaled like any other `Decimal`; multiply by a
+/// depositor's shares and subtract their `USER_REWARD_DEBT` to get their claim.
+pub const REWARD_PER_SHARE: Item<Decimal> = Item::new("reward_per_share");
+/// unix timestamp `REWARD_PER_SHARE` was last accrued to.
+pub const LAST_REWARD_TIME: Item<u64> = Item::new("last_reward_time");
+/// `REWARD_PER_SHARE` snapshot at each depositor's last deposit/withdraw/claim,
+/// so only rewards accrued since then are owed to them.
+pub const USER_REWARD_DEBT: Map<Addr, Decimal> = Map::new("user_reward_debt");
+
+/// Identifies a registered trading pair by its two denoms, ordered exactly
+/// as the caller provided them (`denom_0` is the base side, `denom_1` the
+/// quote side) — the same `(token_0, token_1)` convention `PairData` already
+/// uses, just without the decimals/`CurrencyPair` metadata a lookup key
+/// doesn't need.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PairKey {
+    pub denom_0: String,
+    pub denom_1: String,
+}
+
+/// Vault-wide registry of trading pairs this deployment is aware of, keyed by
+/// `(denom_0, denom_1)` — the "pool keys collection" groundwork for running
+/// one vault across many markets: `ExecuteMsg::RegisterPair`/`DeregisterPair`
+/// maintain it with duplicate-registration guards, and `QueryMsg::ListPairs`
+/// enumerates it paginated, same shape as `POSITIONS_BY_OWNER`. `Config`
+/// still carries the single active `pair_data` this contract instance
+/// actually trades; dispatching `get_deposit_messages` per registered pair
+/// is follow-up work once `Config` itself is split into per-pair state.
+pub const REGISTERED_PAIRS: Map<(String, String), PairData> = Map::new("registered_pairs");
+
+/// A `crate::msg::ConfigOverride` staged by `ExecuteMsg::UpdateConfig`,
+/// reusing the same "optional field overrides" shape `MigrateMsg` already
+/// applies, plus the block height it becomes eligible to apply at.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingConfigUpdate {
+    pub update: crate::msg::ConfigOverride,
+    pub effective_block: u64,
+}
+
+/// Staged `ExecuteMsg::UpdateConfig` awaiting `ExecuteMsg::CommitConfig` once
+/// `effective_block` is reached, or `ExecuteMsg::CancelConfig` to discard it.
+/// Absent when no update is staged; only one update may be staged at a time.
+pub const PENDING_CONFIG: Item<PendingConfigUpdate> = Item::new("pending_config");
+
+/// the live `Config` snapshotted by the last `ExecuteMsg::CommitConfig`,
+/// immediately before its override was applied. `ExecuteMsg::RevertConfig`
+/// restores it, a one-step rollback of the most recently committed update.
+/// Absent until the first `CommitConfig`.
+pub const PREVIOUS_CONFIG: Item<Config> = Item::new("previous_config");
+
+/// Append-only audit trail: the full `Config` as it stood immediately after
+/// each `ExecuteMsg::CommitConfig`/`RevertConfig`, keyed by the block height
+/// it took effect at. Lets indexers/LPs reconstruct the vault's entire
+/// config history instead of trusting only the `changed_<field>` attributes
+/// on each call's response.
+pub const CONFIG_HISTORY: Map<u64, Config> = Map::new("config_history");
+
+/// The block height and full response of the last successful
+/// [`crate::utils::get_prices`] fetch, refreshed by every execute-path call
+/// through [`crate::utils::get_prices_with_fallback`]. Served back in place
+/// of a failing fetch as long as it is no older than `Config::max_blocks_old`;
+/// absent until the first such call succeeds.
+pub const LAST_GOOD_PRICE: Item<(u64, crate::msg::CombinedPriceResponse)> =
+    Item::new("last_good_price");
+
+/// `LAST_REDEMPTION_RATE`'s stored rate plus the timestamp it was accepted
+/// at, so `apply_redemption_adapter` can bound how fast the rate is allowed
+/// to move per second elapsed, not just per call.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RedemptionRateCache {
+    pub rate: PrecDec,
+    pub updated_at: u64,
+}
+
+/// The last redemption rate `get_prices` accepted from `Config::
+/// redemption_adapter`, refreshed by every execute-path call through
+/// `crate::utils::get_prices_with_fallback`. A freshly fetched rate no
+/// greater than this is rejected with `ContractError::
+/// RedemptionRateNotIncreasing`, since redemption rates only ever grow; one
+/// that grows faster than `RedemptionAdapterConfig::
+/// max_redemption_rate_change_bps` per second since `updated_at` is rejected
+/// with `ContractError::RedemptionRateOutOfBounds`. Absent until the first
+/// successful fetch.
+pub const LAST_REDEMPTION_RATE: Item<RedemptionRateCache> = Item::new("last_redemption_rate");
+
+/// Per-pair circuit-breaker snapshot: the last price `apply_price_circuit_
+/// breaker` accepted for a `CurrencyPair` (keyed by `"{base}/{quote}"`) and
+/// the block height it was accepted at, so a later fetch's allowed deviation
+/// can scale with blocks elapsed rather than using a single fixed bound
+/// regardless of how long it's been since the last check. Absent for a pair
+/// until its first successful price fetch.
+pub const LAST_ACCEPTED_PAIR_PRICE: Map<String, (PrecDec, u64)> = Map::new("last_accepted_pair_price");
+
+/// Smoothed per-`instance` APY observation used in place of the raw
+/// `ApySourceQueryMsg::GetApy` sample by `query_calculated_fee_tiers`, so one
+/// noisy or stale reading can't whipsaw `fee_tiers`/`oracle_skew` within a
+/// single block. `alpha` is carried alongside the running average (rather
+/// than folded into `Config`) since it's an `execute_update_apy_ema`-caller
+/// choice that can vary per `instance`, not a vault-wide parameter. Seeded to
+/// the first observation outright; updated thereafter via `ema = alpha *
+/// raw + (1 - alpha) * prev_ema`. Absent until `execute_update_apy_ema`'s
+/// first call for that `instance`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ApyEmaCache {
+    pub ema_apy: PrecDec,
+    pub alpha: PrecDec,
+    pub last_block: u64,
+}
+
+pub const APY_EMA: Map<String, ApyEmaCache> = Map::new("apy_ema");
+
+/// One `ExecuteMsg::ProposeConfigUpdate` entry: the staged `ConfigOverride`
+/// plus the distinct `Config::signers` addresses that have approved it so
+/// far via `ExecuteMsg::ApproveConfigUpdate`. `ExecuteMsg::ExecuteConfigUpdate`
+/// applies `update` once `approvals.len() >= Config::threshold`, the same
+/// `apply_to`/`Config::validate` path `commit_config` uses for the
+/// timelocked single-admin flow.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConfigProposal {
+    pub update: crate::msg::ConfigOverride,
+    pub approvals: Vec<Addr>,
+}
+
+/// Open `ConfigProposal`s, keyed by a `PROPOSAL_SEQ`-assigned id. An entry is
+/// removed once `ExecuteMsg::ExecuteConfigUpdate` applies it.
+pub const PROPOSALS: Map<u64, ConfigProposal> = Map::new("proposals");
+
+/// Next id `ExecuteMsg::ProposeConfigUpdate` assigns into `PROPOSALS`,
+/// monotonically increasing and never reused (the same pattern
+/// `FAILED_DEPOSIT_SEQ`/`WITHDRAWAL_QUEUE_SEQ` use).
+pub const PROPOSAL_SEQ: Item<u64> = Item::new("proposal_seq");
@@ -0,0 +1,35 @@
+use std::str::FromStr;
+
+use test_case::test_case;
+
+use crate::quantity::BasisPoints;
+
+#[test_case("30bps" => 30; "bare bps suffix")]
+#[test_case("0bps" => 0; "zero bps")]
+#[test_case("0.30%" => 30; "two-decimal percent")]
+#[test_case("1%" => 100; "whole percent")]
+#[test_case("0.003" => 30; "bare fraction")]
+#[test_case("1" => 1; "bare integer is bps, not a whole-unit fraction")]
+#[test_case("15" => 15; "bare integer round-trips raw legacy configs unchanged")]
+#[test_case(" 30bps " => 30; "surrounding whitespace is trimmed")]
+fn test_basis_points_from_str(raw: &str) -> u64 {
+    BasisPoints::from_str(raw).unwrap().bps()
+}
+
+#[test_case("30bp"; "missing the final s")]
+#[test_case("bps"; "no digits before the suffix")]
+#[test_case("0.001%"; "more precision than a percent can represent in bps")]
+#[test_case("0.00001"; "more precision than a bare fraction can represent in bps")]
+#[test_case("-1"; "negative values are rejected")]
+#[test_case(""; "empty input")]
+#[test_case("abc"; "non-numeric input")]
+fn test_basis_points_from_str_rejects(raw: &str) {
+    assert!(BasisPoints::from_str(raw).is_err());
+}
+
+#[test_case("30bps" => "30bps"; "bps input is already canonical")]
+#[test_case("0.30%" => "30bps"; "percent canonicalizes to bps")]
+#[test_case("0.003" => "30bps"; "fraction canonicalizes to bps")]
+fn test_basis_points_round_trips_to_canonical_form(raw: &str) -> String {
+    BasisPoints::from_str(raw).unwrap().to_string()
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use proptest::prelude::*;
+
+use crate::error::ContractError;
+use crate::msg::CombinedPriceResponse;
+use crate::state::MINIMUM_LIQUIDITY;
+use crate::utils::{shares_to_mint, total_vault_value};
+use cosmwasm_std::Uint128;
+use neutron_std::types::neutron::util::precdec::PrecDec;
+
+// Randomized sequences of deposits only (no withdrawals -
+// `deposit_withdraw_sequence_preserves_share_accounting` in
+// `invariants_tests` already covers interleaved deposit/withdraw behavior),
+// driven through `shares_to_mint`/`total_vault_value` - the same accounting
+// primitives `execute::deposit`/`deposit_internal` call - rather than the
+// handler itself, since that takes `DepsMut`/`Env` and this harness, like
+// the rest of the test suite, only exercises pure functions.
+// `mint_shares_checked`'s only other job, `Config::max_total_shares`
+// enforcement, already has dedicated coverage in `utils_tests`, so isn't
+// re-tested here; what's in scope is the `shares_to_mint` math itself plus
+// the first-deposit `MINIMUM_LIQUIDITY` burn that `deposit_internal` folds
+// into `config.total_shares` around it.
+const USER_COUNT: u8 = 4;
+
+#[derive(Clone, Debug)]
+struct DepositStep {
+    user: u8,
+    amount_0: u128,
+    amount_1: u128,
+}
+
+fn deposit_step_strategy() -> impl Strategy<Value = DepositStep> {
+    (0..USER_COUNT, 1u128..=1_000_000u128, 1u128..=1_000_000u128)
+        .prop_map(|(user, amount_0, amount_1)| DepositStep { user, amount_0, amount_1 })
+}
+
+fn prices_at(token_0_price_bps: u128, token_1_price_bps: u128) -> CombinedPriceResponse {
+    CombinedPriceResponse {
+        token_0_price: PrecDec::from_ratio(token_0_price_bps, 10_000u128),
+        token_1_price: PrecDec::from_ratio(token_1_price_bps, 10_000u128),
+        price_0_to_1: PrecDec::from_ratio(token_0_price_bps, token_1_price_bps),
+        token_0_price_raw: PrecDec::from_ratio(token_0_price_bps, 10_000u128),
+        token_1_price_raw: PrecDec::from_ratio(token_1_price_bps, 10_000u128),
+        token_0_confidence: None,
+        token_1_confidence: None,
+        token_0_ema: PrecDec::from_ratio(token_0_price_bps, 10_000u128),
+        token_1_ema: PrecDec::from_ratio(token_1_price_bps, 10_000u128),
+        redemption_rate: None,
+    }
+}
+
+/// Applies one deposit to the running `balance_0`/`balance_1`/`total_shares`/
+/// `shares` state, mirroring `deposit_internal`'s minting order exactly:
+/// price the deposit, mint against the pre-deposit value, fold
+/// `MINIMUM_LIQUIDITY` into `total_shares` on the very first deposit, then
+/// credit the minted shares. Returns the minted amount, or `None` for a
+/// below-`MINIMUM_LIQUIDITY`/zero-value deposit that the real handler would
+/// reject with `ContractError::DepositBelowMinimumLiquidity` without
+/// mutating any state.
+fn apply_deposit(
+    balance_0: &mut Uint128,
+    balance_1: &mut Uint128,
+    total_shares: &mut Uint128,
+    shares: &mut HashMap<u8, Uint128>,
+    prices: &CombinedPriceResponse,
+    step: &DepositStep,
+) -> Option<Uint128> {
+    let amount_0 = Uint128::new(step.amount_0);
+    let amount_1 = Uint128::new(step.amount_1);
+    let value_before = total_vault_value(*balance_0, *balance_1, prices).unwrap();
+    let deposit_value = total_vault_value(amount_0, amount_1, prices).unwrap();
+
+    let minted = match shares_to_mint(deposit_value, *total_shares, value_before) {
+        Ok(minted) if !minted.is_zero() => minted,
+        _ => return None,
+    };
+
+    *balance_0 += amount_0;
+    *balance_1 += amount_1;
+    if total_shares.is_zero() {
+        *total_shares = MINIMUM_LIQUIDITY;
+    }
+    *total_shares += minted;
+    *shares.entry(step.user).or_insert(Uint128::zero()) += minted;
+
+    Some(minted)
+}
+
+proptest! {
+    // `total_shares` never drifts from what was actually credited to
+    // holders: every share in circulation is either sitting in `shares` or
+    // permanently burned as `MINIMUM_LIQUIDITY`.
+    #[test]
+    fn prop_total_shares_equals_sum_of_minted(
+        steps in proptest::collection::vec(deposit_step_strategy(), 1..=20),
+        token_0_price_bps in 1u128..=100_000u128,
+        token_1_price_bps in 1u128..=100_000u128,
+    ) {
+        let prices = prices_at(token_0_price_bps, token_1_price_bps);
+        let mut balance_0 = Uint128::zero();
+        let mut balance_1 = Uint128::zero();
+        let mut total_shares = Uint128::zero();
+        let mut shares: HashMap<u8, Uint128> = HashMap::new();
+
+        for step in &steps {
+            apply_deposit(&mut balance_0, &mut balance_1, &mut total_shares, &mut shares, &prices, step);
+        }
+
+        let sum_of_holdings: Uint128 = shares.values().copied().fold(Uint128::zero(), |acc, s| acc + s);
+        if total_shares.is_zero() {
+            prop_assert!(sum_of_holdings.is_zero());
+        } else {
+            prop_assert_eq!(total_shares, MINIMUM_LIQUIDITY + sum_of_holdings);
+        }
+    }
+
+    // Minting is monotone in the deposit's priced value: given the same
+    // pre-deposit `total_shares`/`total_value_before`, a strictly larger
+    // deposit never mints fewer shares than a smaller one.
+    #[test]
+    fn prop_minting_monotone_in_deposit_value(
+        total_shares in 0u128..=1_000_000_000u128,
+        total_value_before_bps in 1u128..=1_000_000_000u128,
+        value_a_bps in 1u128..=1_000_000_000u128,
+        extra_bps in 0u128..=1_000_000_000u128,
+    ) {
+        let total_shares = Uint128::new(total_shares);
+        let total_value_before = PrecDec::from_ratio(total_value_before_bps, 1u128);
+        let value_a = PrecDec::from_ratio(value_a_bps, 1u128);
+        let value_b = PrecDec::from_ratio(value_a_bps + extra_bps, 1u128);
+
+        let minted_a = shares_to_mint(value_a, total_shares, total_value_before);
+        let minted_b = shares_to_mint(value_b, total_shares, total_value_before);
+
+        if let (Ok(minted_a), Ok(minted_b)) = (minted_a, minted_b) {
+            prop_assert!(minted_b >= minted_a);
+        }
+    }
+
+    // A depositor never walks away with shares collectively worth more than
+    // what they put in - floor rounding in `shares_to_mint` always favors
+    // the holders already in the pool, never the one minting new shares.
+    #[test]
+    fn prop_minted_shares_never_worth_more_than_deposited(
+        steps in proptest::collection::vec(deposit_step_strategy(), 1..=20),
+        token_0_price_bps in 1u128..=100_000u128,
+        token_1_price_bps in 1u128..=100_000u128,
+    ) {
+        let prices = prices_at(token_0_price_bps, token_1_price_bps);
+        let mut balance_0 = Uint128::zero();
+        let mut balance_1 = Uint128::zero();
+        let mut total_shares = Uint128::zero();
+        let mut shares: HashMap<u8, Uint128> = HashMap::new();
+
+        for step in &steps {
+            let amount_0 = Uint128::new(step.amount_0);
+            let amount_1 = Uint128::new(step.amount_1);
+            let deposit_value = total_vault_value(amount_0, amount_1, &prices).unwrap();
+
+            if let Some(minted) = apply_deposit(&mut balance_0, &mut balance_1, &mut total_shares, &mut shares, &prices, step) {
+                let value_after = total_vault_value(balance_0, balance_1, &prices).unwrap();
+                let per_share_after = value_after / PrecDec::from_ratio(total_shares, 1u128);
+                let minted_value = per_share_after * PrecDec::from_ratio(minted, 1u128);
+                prop_assert!(minted_value <= deposit_value);
+            }
+        }
+    }
+
+    // `shares_to_mint` is a pure function of its three inputs: depositing the
+    // same value against the same pre-state twice (e.g. two users depositing
+    // identically at the same instant) must mint identical shares both
+    // times.
+    #[test]
+    fn prop_repeated_identical_deposits_mint_identical_shares(
+        total_shares in 0u128..=1_000_000_000u128,
+        total_value_before_bps in 1u128..=1_000_000_000u128,
+        deposit_value_bps in 1u128..=1_000_000_000u128,
+    ) {
+        let total_shares = Uint128::new(total_shares);
+        let total_value_before = PrecDec::from_ratio(total_value_before_bps, 1u128);
+        let deposit_value = PrecDec::from_ratio(deposit_value_bps, 1u128);
+
+        let first = shares_to_mint(deposit_value, total_shares, total_value_before);
+        let second = shares_to_mint(deposit_value, total_shares, total_value_before);
+
+        match (first, second) {
+            (Ok(first), Ok(second)) => prop_assert_eq!(first, second),
+            (Err(first), Err(second)) => {
+                prop_assert_eq!(
+                    matches!(first, ContractError::DepositBelowMinimumLiquidity),
+                    matches!(second, ContractError::DepositBelowMinimumLiquidity)
+                );
+            }
+            _ => prop_assert!(false, "identical inputs produced divergent Ok/Err results"),
+        }
+    }
+}
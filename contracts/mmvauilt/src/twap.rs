@@ -0,0 +1,71 @@
+use cosmwasm_std::DepsMut;
+use neutron_std::types::neutron::util::precdec::PrecDec;
+
+use crate::error::ContractResult;
+use crate::state::{PriceObservation, PRICE_OBSERVATIONS};
+
+/// Ring-buffer cap on `PRICE_OBSERVATIONS`, independent of
+/// `Config::twap_window_seconds` — a vault configured with a very long
+/// window still only ever holds this many samples, the oldest evicted first.
+pub const MAX_OBSERVATIONS: usize = 32;
+
+/// Appends `price_0_to_1` sampled at `timestamp` to `PRICE_OBSERVATIONS`,
+/// evicting samples older than `twap_window_seconds` and, failing that, the
+/// oldest sample(s) once the window exceeds [`MAX_OBSERVATIONS`], then
+/// returns the updated window for [`twap_price`] to consume.
+pub fn record_price_observation(
+    deps: &DepsMut,
+    price_0_to_1: PrecDec,
+    timestamp: u64,
+    twap_window_seconds: u64,
+) -> ContractResult<Vec<PriceObservation>> {
+    let mut observations = PRICE_OBSERVATIONS.may_load(deps.storage)?.unwrap_or_default();
+    observations.push(PriceObservation {
+        price_0_to_1,
+        timestamp,
+    });
+    observations.retain(|obs| timestamp.saturating_sub(obs.timestamp) <= twap_window_seconds);
+    while observations.len() > MAX_OBSERVATIONS {
+        observations.remove(0);
+    }
+    PRICE_OBSERVATIONS.save(deps.storage, &observations)?;
+    Ok(observations)
+}
+
+/// Time-weighted average of `observations`' `price_0_to_1`: holds each
+/// sample's price constant over the span up to the next sample, summed and
+/// divided by the total span covered. Falls back to the latest sample's spot
+/// price for a single observation or a zero total span, rather than dividing
+/// by zero.
+pub fn twap_price(observations: &[PriceObservation]) -> PrecDec {
+    let Some(last) = observations.last() else {
+        return PrecDec::zero();
+    };
+    if observations.len() < 2 {
+        return last.price_0_to_1;
+    }
+
+    let mut weighted_sum = PrecDec::zero();
+    let mut total_span: u64 = 0;
+    for pair in observations.windows(2) {
+        let span = pair[1].timestamp.saturating_sub(pair[0].timestamp);
+        weighted_sum += pair[0].price_0_to_1 * PrecDec::from_ratio(span, 1u128);
+        total_span += span;
+    }
+
+    if total_span == 0 {
+        return last.price_0_to_1;
+    }
+    weighted_sum / PrecDec::from_ratio(total_span, 1u128)
+}
+
+/// `|spot - twap| / twap` in basis points, the same shape
+/// [`crate::utils::check_price_divergence`] uses for the EMA guard. `0` when
+/// `twap` is zero, since there's nothing yet to have deviated from.
+pub fn twap_deviation_bps(spot: PrecDec, twap: PrecDec) -> PrecDec {
+    if twap == PrecDec::zero() {
+        return PrecDec::zero();
+    }
+    let diff = if spot > twap { spot - twap } else { twap - spot };
+    (diff / twap) * PrecDec::from_ratio(10000u128, 1u128)
+}
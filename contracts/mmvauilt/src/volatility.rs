@@ -0,0 +1,73 @@
+use cosmwasm_std::DepsMut;
+use neutron_std::types::neutron::util::precdec::PrecDec;
+
+use crate::error::{ContractError, ContractResult};
+use crate::state::{PriceSample, VolatilitySpreadConfig, PRICE_HISTORY};
+
+/// Appends `price_0_to_1` sampled at `timestamp` to `PRICE_HISTORY`, evicting
+/// the oldest sample(s) once the window exceeds `window_size`, then returns
+/// the updated window for [`realized_volatility`] to consume.
+pub fn record_price_sample(
+    deps: &DepsMut,
+    price_0_to_1: PrecDec,
+    timestamp: u64,
+    window_size: u64,
+) -> ContractResult<Vec<PriceSample>> {
+    let mut history = PRICE_HISTORY.may_load(deps.storage)?.unwrap_or_default();
+    history.push(PriceSample {
+        price_0_to_1,
+        timestamp,
+    });
+    while history.len() as u64 > window_size {
+        history.remove(0);
+    }
+    PRICE_HISTORY.save(deps.storage, &history)?;
+    Ok(history)
+}
+
+/// Standard deviation of consecutive log-returns across `history`'s
+/// `price_0_to_1` samples, a realized-volatility estimate: flat price paths
+/// return near `0.0`, a steady trend returns a small positive value, and a
+/// whipsawing path returns a larger one. `0.0` for fewer than two samples,
+/// since there's no return to measure dispersion from yet. Converts through
+/// `f64` for `ln`/`sqrt`, the same escape hatch
+/// `constant_product_band_split` uses for math `PrecDec` doesn't expose.
+pub fn realized_volatility(history: &[PriceSample]) -> ContractResult<f64> {
+    if history.len() < 2 {
+        return Ok(0.0);
+    }
+
+    let prices: Vec<f64> = history
+        .iter()
+        .map(|sample| {
+            sample
+                .price_0_to_1
+                .to_string()
+                .parse::<f64>()
+                .map_err(|_| ContractError::ConversionError)
+        })
+        .collect::<ContractResult<Vec<f64>>>()?;
+
+    let log_returns: Vec<f64> = prices
+        .windows(2)
+        .map(|pair| (pair[1] / pair[0]).ln())
+        .collect();
+
+    let mean = log_returns.iter().sum::<f64>() / log_returns.len() as f64;
+    let variance = log_returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / log_returns.len() as f64;
+    Ok(variance.sqrt())
+}
+
+/// Maps `realized_volatility`'s log-return standard deviation to a widened
+/// spread, in basis points, via `cfg.spread_multiplier`, clamped to
+/// `cfg.max_spread_bps` so a single whipsaw can't widen a deposit out to an
+/// unbounded fee/tick offset.
+pub fn dynamic_spread_bps(volatility: f64, cfg: &VolatilitySpreadConfig) -> ContractResult<u64> {
+    let multiplier = cfg
+        .spread_multiplier
+        .to_string()
+        .parse::<f64>()
+        .map_err(|_| ContractError::ConversionError)?;
+    let spread_bps = (volatility * multiplier * 10000.0).max(0.0).round() as u64;
+    Ok(spread_bps.min(cfg.max_spread_bps))
+}